@@ -0,0 +1,6 @@
+-- Add priority to cloze_notes so important marks can be surfaced first in the hub
+-- Range 0-10, higher is more important. Existing marks start at the neutral 5
+ALTER TABLE cloze_notes ADD COLUMN priority INTEGER NOT NULL DEFAULT 5;
+
+-- Index for priority ordering within status filtering
+CREATE INDEX idx_cloze_notes_status_priority ON cloze_notes(status, priority);
@@ -0,0 +1,21 @@
+-- Persistent edit history for texts
+-- Each row is the diff from the previous version: the span starting at edit_start
+-- (UTF-16 units) had removed_text replaced by inserted_text. The newest revision
+-- matches texts.content; older versions are rebuilt by undoing diffs newest-first.
+CREATE TABLE text_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    text_id INTEGER NOT NULL,
+    revision_number INTEGER NOT NULL,
+    edit_start INTEGER NOT NULL,
+    removed_text TEXT NOT NULL,
+    inserted_text TEXT NOT NULL,
+    content_length INTEGER NOT NULL,
+    -- 'edit' for normal edits, 'restore' when an earlier revision was restored
+    source TEXT NOT NULL DEFAULT 'edit',
+    restored_from INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (text_id) REFERENCES texts(id) ON DELETE CASCADE,
+    UNIQUE(text_id, revision_number)
+);
+
+CREATE INDEX idx_text_revisions_text ON text_revisions(text_id, revision_number);
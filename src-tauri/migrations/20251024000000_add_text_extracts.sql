@@ -0,0 +1,8 @@
+-- Incremental-reading extracts: a text created from a passage of another text
+-- keeps a link to its parent and the UTF-16 range it was copied from.
+-- Extracts survive deletion of their parent; the link is just cleared.
+ALTER TABLE texts ADD COLUMN parent_text_id INTEGER REFERENCES texts(id) ON DELETE SET NULL;
+ALTER TABLE texts ADD COLUMN parent_start_position INTEGER;
+ALTER TABLE texts ADD COLUMN parent_end_position INTEGER;
+
+CREATE INDEX idx_texts_parent_text_id ON texts(parent_text_id);
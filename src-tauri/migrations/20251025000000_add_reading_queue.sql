@@ -0,0 +1,15 @@
+-- Incremental reading queue across the library
+-- Each queued text is read a bit at a time, then postponed; the postpone interval
+-- grows with every postpone, faster for low-priority texts.
+-- Priority range 0-10, higher is more important (same scale as cloze_notes.priority)
+CREATE TABLE reading_queue (
+    text_id INTEGER PRIMARY KEY REFERENCES texts(id) ON DELETE CASCADE,
+    priority INTEGER NOT NULL DEFAULT 5,
+    interval_days REAL NOT NULL DEFAULT 1.0,
+    next_read_at DATETIME NOT NULL,
+    postpone_count INTEGER NOT NULL DEFAULT 0,
+    last_read_at DATETIME,
+    added_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_reading_queue_next_read_at ON reading_queue(next_read_at);
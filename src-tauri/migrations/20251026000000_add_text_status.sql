@@ -0,0 +1,14 @@
+-- Text lifecycle: 'inbox' (not yet triaged), 'reading', 'finished', 'archived'
+-- Driven by activity: the first read range moves a text to reading and full progress
+-- to finished. Archiving is manual.
+ALTER TABLE texts ADD COLUMN status TEXT NOT NULL DEFAULT 'inbox'
+    CHECK (status IN ('inbox', 'reading', 'finished', 'archived'));
+
+-- Archived texts can take their cards out of review
+ALTER TABLE texts ADD COLUMN review_suspended BOOLEAN NOT NULL DEFAULT 0;
+
+-- Texts that already have reading progress are being read
+UPDATE texts SET status = 'reading'
+WHERE id IN (SELECT DISTINCT text_id FROM read_ranges);
+
+CREATE INDEX idx_texts_status ON texts(status);
@@ -0,0 +1,14 @@
+-- Sentence boundaries, stored alongside paragraphs (same raw UTF-16 position space)
+-- Texts created before this migration get their sentences on first use.
+CREATE TABLE sentences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    text_id INTEGER NOT NULL,
+    sentence_index INTEGER NOT NULL,
+    start_position INTEGER NOT NULL,
+    end_position INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (text_id) REFERENCES texts(id) ON DELETE CASCADE,
+    UNIQUE(text_id, sentence_index)
+);
+
+CREATE INDEX idx_sentences_text_index ON sentences(text_id, sentence_index);
@@ -0,0 +1,14 @@
+-- Parts of a text left out of reading progress and next-unread navigation
+-- (reference lists, navboxes, boilerplate). Replaces inline [[exclude]]...[[/exclude]]
+-- tags, which are moved into this table by the app at startup.
+-- Positions are rendered (DOM space) UTF-16, like read_ranges. A text's ranges are
+-- kept merged: no two overlap or touch.
+CREATE TABLE excluded_ranges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    text_id INTEGER NOT NULL REFERENCES texts(id) ON DELETE CASCADE,
+    start_position INTEGER NOT NULL,
+    end_position INTEGER NOT NULL,
+    CHECK (end_position > start_position)
+);
+
+CREATE INDEX idx_excluded_ranges_text_id ON excluded_ranges(text_id, start_position);
@@ -0,0 +1,4 @@
+-- Language of a text's content as a BCP 47 code (e.g. "en", "de", "pt-br"), when known.
+-- Set by the importers from the source (Wikipedia subdomain, page lang attribute,
+-- EPUB dc:language); NULL for texts created before this column existed.
+ALTER TABLE texts ADD COLUMN language TEXT;
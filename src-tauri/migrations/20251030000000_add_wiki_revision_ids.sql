@@ -0,0 +1,4 @@
+-- MediaWiki page and revision a text was imported from, as returned by the parse API.
+-- The revision id tells whether the article has changed since, for refreshing it.
+ALTER TABLE texts ADD COLUMN wiki_page_id INTEGER;
+ALTER TABLE texts ADD COLUMN wiki_revision_id INTEGER;
@@ -0,0 +1,3 @@
+-- Sentences were segmented on the raw content, but are read against the rendered
+-- text like read and excluded ranges. Texts get them again, in DOM space, on first use.
+DELETE FROM sentences;
@@ -0,0 +1,269 @@
+// Excluded range commands
+//
+// Parts of a text left out of reading progress and next-unread navigation. Ranges are
+// rendered (DOM space) positions, like read ranges, and each text's ranges are stored
+// merged (see services::excluded_ranges).
+//
+// Exclusions used to be inline "[[exclude]]...[[/exclude]]" tags in the content. Tags
+// in new texts are converted on creation, and tags in existing texts at startup.
+
+use crate::commands::flashcard_hub::to_rendered_position;
+use crate::commands::text_revisions::{record_revision, RevisionSource};
+use crate::commands::texts::regenerate_paragraphs;
+use crate::db::Database;
+use crate::models::excluded_range::ExcludedRange;
+use crate::services::excluded_ranges;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+
+/// A text's excluded ranges as (start, end) pairs, in order
+pub(crate) async fn fetch_excluded_ranges(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+) -> Result<Vec<(i64, i64)>, String> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT start_position, end_position
+        FROM excluded_ranges
+        WHERE text_id = ?
+        ORDER BY start_position ASC
+        "#,
+        text_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch excluded ranges: {}", e))?;
+
+    Ok(rows.into_iter().map(|r| (r.start_position, r.end_position)).collect())
+}
+
+/// Replace a text's excluded ranges with `ranges`, which must already be merged
+pub(crate) async fn save_excluded_ranges(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    ranges: &[(i64, i64)],
+) -> Result<(), String> {
+    sqlx::query!("DELETE FROM excluded_ranges WHERE text_id = ?", text_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to delete excluded ranges: {}", e))?;
+
+    for (start, end) in ranges {
+        sqlx::query!(
+            "INSERT INTO excluded_ranges (text_id, start_position, end_position) VALUES (?, ?, ?)",
+            text_id,
+            start,
+            end
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to store excluded range: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Move a text's excluded ranges to follow an edit of `edit_start..edit_end`
+/// (see services::excluded_ranges::apply_edit), clamped to the new rendered length
+pub(crate) async fn apply_edit_to_excluded_ranges(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    edit_start: i64,
+    edit_end: i64,
+    length_delta: i64,
+    new_content_length: i64,
+) -> Result<(), String> {
+    let ranges = fetch_excluded_ranges(&mut *conn, text_id).await?;
+    let edited = excluded_ranges::merge_ranges(
+        excluded_ranges::apply_edit(&ranges, edit_start, edit_end, length_delta)
+            .into_iter()
+            .map(|(start, end)| (start, end.min(new_content_length)))
+            .collect(),
+    );
+
+    if edited != ranges {
+        save_excluded_ranges(&mut *conn, text_id, &edited).await?;
+    }
+    Ok(())
+}
+
+/// Strip inline exclude tags from `content`
+///
+/// Returns the content without tags and the excluded ranges in rendered positions, or
+/// None if there are no tags. Tags don't count in rendered positions, so the ranges
+/// are the same for the content with and without them.
+pub(crate) fn inline_exclusions(content: &str) -> Option<(String, Vec<(i64, i64)>)> {
+    let (cleaned, raw_ranges) = excluded_ranges::strip_inline_tags(content)?;
+
+    let ranges = raw_ranges
+        .iter()
+        .map(|&(start, end)| {
+            (to_rendered_position(&cleaned, start), to_rendered_position(&cleaned, end))
+        })
+        .collect();
+
+    Some((cleaned, excluded_ranges::merge_ranges(ranges)))
+}
+
+/// Move inline exclude tags in existing texts into stored ranges
+///
+/// Marks and read ranges stay where they are, since their rendered positions don't
+/// change. Paragraphs and sentences are regenerated and the edit is recorded as a
+/// revision. Each text is converted in its own transaction; a text that fails is
+/// logged and left with its tags, so one bad text doesn't hold up the others.
+/// Returns the number of texts converted.
+pub(crate) async fn migrate_inline_exclude_tags(pool: &Pool<Sqlite>) -> Result<usize, String> {
+    let texts = sqlx::query!(
+        r#"SELECT id as "id!", content FROM texts WHERE content LIKE '%[[exclude]]%'"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch texts: {}", e))?;
+
+    let mut converted = 0;
+    for text in texts {
+        match convert_inline_exclude_tags(pool, text.id, &text.content).await {
+            Ok(true) => converted += 1,
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to convert inline exclude tags in text {}: {}", text.id, e),
+        }
+    }
+
+    Ok(converted)
+}
+
+/// Convert one text's inline exclude tags, returning whether it had any
+async fn convert_inline_exclude_tags(
+    pool: &Pool<Sqlite>,
+    text_id: i64,
+    original: &str,
+) -> Result<bool, String> {
+    let Some((content, inline_ranges)) = inline_exclusions(original) else {
+        return Ok(false);
+    };
+    let now = Utc::now();
+    let content_length = content.encode_utf16().count() as i64;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let mut ranges = fetch_excluded_ranges(&mut tx, text_id).await?;
+    ranges.extend(inline_ranges);
+    save_excluded_ranges(&mut tx, text_id, &excluded_ranges::merge_ranges(ranges)).await?;
+
+    sqlx::query!(
+        "UPDATE texts SET content = ?, content_length = ?, updated_at = ? WHERE id = ?",
+        content,
+        content_length,
+        now,
+        text_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update text: {}", e))?;
+
+    regenerate_paragraphs(&mut tx, text_id, &content).await?;
+    record_revision(&mut tx, text_id, original, &content, RevisionSource::Edit, now).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(true)
+}
+
+async fn list_excluded_ranges(pool: &Pool<Sqlite>, text_id: i64) -> Result<Vec<ExcludedRange>, String> {
+    sqlx::query_as!(
+        ExcludedRange,
+        r#"
+        SELECT
+            id as "id!",
+            text_id as "text_id!",
+            start_position as "start_position!",
+            end_position as "end_position!"
+        FROM excluded_ranges
+        WHERE text_id = ?
+        ORDER BY start_position ASC
+        "#,
+        text_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch excluded ranges: {}", e))
+}
+
+/// Exclude or include a range of a text and return its updated ranges
+async fn update_excluded_ranges(
+    pool: &Pool<Sqlite>,
+    text_id: i64,
+    start_pos: i64,
+    end_pos: i64,
+    exclude: bool,
+) -> Result<Vec<ExcludedRange>, String> {
+    if start_pos < 0 || end_pos <= start_pos {
+        return Err(format!("Invalid range {}..{}", start_pos, end_pos));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let ranges = fetch_excluded_ranges(&mut tx, text_id).await?;
+    let updated = if exclude {
+        excluded_ranges::add_range(&ranges, start_pos, end_pos)
+    } else {
+        excluded_ranges::remove_range(&ranges, start_pos, end_pos)
+    };
+    save_excluded_ranges(&mut tx, text_id, &updated).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    list_excluded_ranges(pool, text_id).await
+}
+
+#[tauri::command]
+pub async fn get_excluded_ranges(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<ExcludedRange>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    list_excluded_ranges(pool, text_id).await
+}
+
+/// Leave a rendered range out of progress and navigation, merging with existing ranges
+#[tauri::command]
+pub async fn exclude_range(
+    text_id: i64,
+    start_pos: i64,
+    end_pos: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<ExcludedRange>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    update_excluded_ranges(pool, text_id, start_pos, end_pos, true).await
+}
+
+/// Count a rendered range again, trimming or splitting the excluded ranges it overlaps
+#[tauri::command]
+pub async fn include_range(
+    text_id: i64,
+    start_pos: i64,
+    end_pos: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<ExcludedRange>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    update_excluded_ranges(pool, text_id, start_pos, end_pos, false).await
+}
@@ -0,0 +1,693 @@
+// Extract commands
+//
+// Incremental reading: a passage of a long text can be extracted into its own text,
+// which keeps a link to its parent and the range it came from. Extracts can be
+// extracted from again, forming a tree below the original article.
+//
+// A long text can also be split at its headers into one child text per section,
+// with its marks and cards moved along and its read ranges copied. Extracts and
+// sections inherit the excluded ranges in the span they cover.
+//
+// Ranges use the same rendered (DOM space) UTF-16 positions as marks and read ranges,
+// so the extract receives the passage as the reader saw it.
+
+use crate::commands::excluded_ranges::{fetch_excluded_ranges, save_excluded_ranges};
+use crate::commands::flashcard_hub::{process_content_to_dom_space, to_rendered_position};
+use crate::commands::reading::calculate_unread_portions;
+use crate::db::Database;
+use crate::models::folder::Folder;
+use crate::models::read_range::ReadRange;
+use crate::models::text::Text;
+use crate::services::excluded_ranges;
+use crate::services::mark_anchor::slice_utf16;
+use crate::services::parser::{detect_paragraphs, store_paragraphs};
+use crate::services::sentence_segmenter::{segment_sentences, store_sentences};
+use crate::services::text_splitter;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Longest title derived from an extract's first line
+const MAX_EXTRACT_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractNode {
+    pub id: i64,
+    pub title: String,
+    pub content_length: i64,
+    pub parent_text_id: Option<i64>,
+    pub parent_start_position: Option<i64>,
+    pub parent_end_position: Option<i64>,
+    pub children: Vec<ExtractNode>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitResult {
+    pub folder: Folder,
+    pub texts: Vec<Text>,
+    /// Marks spanning a section boundary, left on the source text and flagged for review
+    pub unmoved_marks: Vec<i64>,
+}
+
+/// The parts of a parent text its extracts inherit
+struct ParentText {
+    title: String,
+    source_url: Option<String>,
+    content: String,
+    metadata: Option<String>,
+    author: Option<String>,
+    publication_date: Option<String>,
+    publisher: Option<String>,
+    access_date: Option<String>,
+    doi: Option<String>,
+    isbn: Option<String>,
+    language: Option<String>,
+    folder_id: Option<String>,
+}
+
+/// A child text to create under a parent
+struct ChildText<'a> {
+    title: &'a str,
+    source: &'a str,
+    content: &'a str,
+    folder_id: Option<&'a str>,
+    parent_start_position: i64,
+    parent_end_position: i64,
+}
+
+async fn fetch_parent(pool: &Pool<Sqlite>, parent_text_id: i64) -> Result<ParentText, String> {
+    sqlx::query_as!(
+        ParentText,
+        r#"
+        SELECT title, source_url, content, metadata, author, publication_date,
+               publisher, access_date, doi, isbn, language, folder_id
+        FROM texts
+        WHERE id = ?
+        "#,
+        parent_text_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch parent text: {}", e))?
+    .ok_or_else(|| "Parent text not found".to_string())
+}
+
+/// Insert a child text with its paragraphs and sentences, returning its id
+/// Citation details are inherited so the child can still be attributed
+async fn insert_child_text(
+    conn: &mut SqliteConnection,
+    parent_text_id: i64,
+    parent: &ParentText,
+    child: ChildText<'_>,
+    now: DateTime<Utc>,
+) -> Result<i64, String> {
+    let content_length = child.content.encode_utf16().count() as i64;
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO texts (
+            title, source, source_url, content, content_length,
+            ingested_at, updated_at, metadata,
+            author, publication_date, publisher, access_date, doi, isbn, language, folder_id,
+            parent_text_id, parent_start_position, parent_end_position
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        child.title,
+        child.source,
+        parent.source_url,
+        child.content,
+        content_length,
+        now,
+        now,
+        parent.metadata,
+        parent.author,
+        parent.publication_date,
+        parent.publisher,
+        parent.access_date,
+        parent.doi,
+        parent.isbn,
+        parent.language,
+        child.folder_id,
+        parent_text_id,
+        child.parent_start_position,
+        child.parent_end_position
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to insert text: {}", e))?;
+
+    let text_id = result.last_insert_rowid();
+
+    // The child covers parent_start_position..parent_end_position of the parent's
+    // rendered text, so it inherits the exclusions in that span
+    let parent_excluded = fetch_excluded_ranges(&mut *conn, parent_text_id).await?;
+    let excluded = excluded_ranges::slice_ranges(
+        &parent_excluded,
+        child.parent_start_position,
+        child.parent_end_position,
+    );
+    save_excluded_ranges(&mut *conn, text_id, &excluded).await?;
+
+    let paragraphs = detect_paragraphs(child.content);
+    store_paragraphs(&mut *conn, text_id, &paragraphs)
+        .await
+        .map_err(|e| format!("Failed to store paragraphs: {}", e))?;
+    store_sentences(&mut *conn, text_id, &segment_sentences(&process_content_to_dom_space(child.content)))
+        .await
+        .map_err(|e| format!("Failed to store sentences: {}", e))?;
+
+    Ok(text_id)
+}
+
+async fn fetch_text(conn: &mut SqliteConnection, text_id: i64) -> Result<Text, String> {
+    sqlx::query_as!(
+        Text,
+        r#"
+        SELECT
+            id as "id!", title, source, source_url, content, content_length as "content_length!",
+            ingested_at as "ingested_at: _", updated_at as "updated_at: _",
+            metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+            wiki_page_id, wiki_revision_id, folder_id,
+            parent_text_id, parent_start_position, parent_end_position, status
+        FROM texts
+        WHERE id = ?
+        "#,
+        text_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch created text: {}", e))
+}
+
+/// Title for an extract with no explicit title: its first non-empty line, shortened
+fn default_extract_title(content: &str, parent_title: &str) -> String {
+    let Some(first_line) = content.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return format!("{} (extract)", parent_title);
+    };
+
+    if first_line.chars().count() <= MAX_EXTRACT_TITLE_CHARS {
+        return first_line.to_string();
+    }
+    let shortened: String = first_line.chars().take(MAX_EXTRACT_TITLE_CHARS).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// Copy `start_position..end_position` of a text into a new child text in the same folder
+///
+/// With `mark_as_read`, the extracted range is also marked as read in the parent so the
+/// reader moves on from it there.
+#[tauri::command]
+pub async fn create_extract(
+    parent_text_id: i64,
+    start_position: i64,
+    end_position: i64,
+    title: Option<String>,
+    mark_as_read: Option<bool>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Text, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+    let user_id = 1;
+
+    let parent = fetch_parent(pool, parent_text_id).await?;
+
+    let dom_content = process_content_to_dom_space(&parent.content);
+    let content = slice_utf16(&dom_content, start_position, end_position)
+        .ok_or_else(|| format!("Invalid extract range {}..{}", start_position, end_position))?;
+    if content.trim().is_empty() {
+        return Err("Cannot extract an empty passage".to_string());
+    }
+
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| default_extract_title(&content, &parent.title));
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let text_id = insert_child_text(
+        &mut tx,
+        parent_text_id,
+        &parent,
+        ChildText {
+            title: &title,
+            source: "extract",
+            content: &content,
+            folder_id: parent.folder_id.as_deref(),
+            parent_start_position: start_position,
+            parent_end_position: end_position,
+        },
+        now,
+    )
+    .await?;
+
+    if mark_as_read.unwrap_or(false) {
+        let existing_ranges = sqlx::query_as!(
+            ReadRange,
+            r#"
+            SELECT
+                id as "id!",
+                text_id as "text_id!",
+                user_id as "user_id!",
+                start_position as "start_position!",
+                end_position as "end_position!",
+                marked_at as "marked_at: _",
+                is_auto_completed as "is_auto_completed: bool"
+            FROM read_ranges
+            WHERE text_id = ? AND user_id = ?
+            ORDER BY start_position ASC
+            "#,
+            parent_text_id,
+            user_id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to fetch existing read ranges: {}", e))?;
+
+        for (unread_start, unread_end) in
+            calculate_unread_portions(start_position, end_position, &existing_ranges)
+        {
+            sqlx::query!(
+                r#"
+                INSERT INTO read_ranges (
+                    text_id, user_id, start_position, end_position, marked_at, is_auto_completed
+                )
+                VALUES (?, ?, ?, ?, ?, 0)
+                "#,
+                parent_text_id,
+                user_id,
+                unread_start,
+                unread_end,
+                now
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to insert read range: {}", e))?;
+        }
+
+        sqlx::query!(
+            "UPDATE texts SET status = 'reading' WHERE id = ? AND status = 'inbox'",
+            parent_text_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update text status: {}", e))?;
+    }
+
+    let text = fetch_text(&mut tx, text_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(text)
+}
+
+/// A text and all of its extracts, recursively, in the order they were created
+#[tauri::command]
+pub async fn get_extract_tree(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<ExtractNode, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE tree(id) AS (
+            SELECT id FROM texts WHERE id = ?
+            UNION
+            SELECT t.id FROM texts t INNER JOIN tree ON t.parent_text_id = tree.id
+        )
+        SELECT t.id as "id!", t.title, t.content_length as "content_length!",
+               t.parent_text_id, t.parent_start_position, t.parent_end_position
+        FROM texts t
+        INNER JOIN tree ON tree.id = t.id
+        ORDER BY t.id ASC
+        "#,
+        text_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch extract tree: {}", e))?;
+
+    let mut children_of: HashMap<i64, Vec<ExtractNode>> = HashMap::new();
+    let mut root = None;
+
+    // Ids only grow, so walking newest first attaches every child before its parent is built
+    for row in rows.into_iter().rev() {
+        let node = ExtractNode {
+            id: row.id,
+            title: row.title,
+            content_length: row.content_length,
+            parent_text_id: row.parent_text_id,
+            parent_start_position: row.parent_start_position,
+            parent_end_position: row.parent_end_position,
+            children: children_of
+                .remove(&row.id)
+                .map(|mut children| {
+                    children.reverse();
+                    children
+                })
+                .unwrap_or_default(),
+        };
+
+        match node.parent_text_id {
+            Some(parent_id) if node.id != text_id => children_of.entry(parent_id).or_default().push(node),
+            _ => root = Some(node),
+        }
+    }
+
+    root.ok_or_else(|| "Text not found".to_string())
+}
+
+/// Split a text at its headers into one child text per section
+///
+/// Sections start at every header, or only at headers of `max_level` and shallower
+/// (2 for "== Section ==" or "## Section"). The children go into a new folder named after the source
+/// text, inside the source's folder. Marks (with their cards) move to the section they
+/// fall in, rebased to its start. Marks spanning a section boundary stay on the source
+/// and are flagged for review. Read ranges are copied to each section they overlap, so
+/// the source text keeps its own progress.
+#[tauri::command]
+pub async fn split_text_by_headers(
+    text_id: i64,
+    max_level: Option<usize>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<SplitResult, String> {
+    let db = db.lock().await;
+    split_by_headers(db.pool(), text_id, max_level, Utc::now()).await
+}
+
+async fn split_by_headers(
+    pool: &Pool<Sqlite>,
+    text_id: i64,
+    max_level: Option<usize>,
+    now: DateTime<Utc>,
+) -> Result<SplitResult, String> {
+    let source = fetch_parent(pool, text_id).await?;
+    let sections = text_splitter::split_sections(&source.content, max_level);
+    if sections.len() < 2 {
+        return Err("Text has no headers to split on".to_string());
+    }
+
+    // Marks and read ranges are in rendered (DOM) space; sections start at line starts,
+    // so rendering the prefix before a section gives its rendered start
+    let dom_ranges: Vec<(i64, i64)> = sections
+        .iter()
+        .map(|section| {
+            let start = to_rendered_position(&source.content, section.start);
+            let length = process_content_to_dom_space(&section.content).encode_utf16().count() as i64;
+            (start, start + length)
+        })
+        .collect();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let folder_id = Uuid::new_v4().to_string();
+    let folder_timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO folders (id, name, parent_id, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        folder_id,
+        source.title,
+        source.folder_id,
+        folder_timestamp,
+        folder_timestamp
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    let mut child_ids = Vec::with_capacity(sections.len());
+    for (section, &(dom_start, dom_end)) in sections.iter().zip(&dom_ranges) {
+        let title = section.title.as_deref().unwrap_or(&source.title);
+        let child_id = insert_child_text(
+            &mut tx,
+            text_id,
+            &source,
+            ChildText {
+                title,
+                source: "section",
+                content: &section.content,
+                folder_id: Some(&folder_id),
+                parent_start_position: dom_start,
+                parent_end_position: dom_end,
+            },
+            now,
+        )
+        .await?;
+        child_ids.push(child_id);
+    }
+
+    let marks = sqlx::query!(
+        r#"
+        SELECT id as "id!", start_position as "start_position!", end_position as "end_position!"
+        FROM cloze_notes
+        WHERE text_id = ? AND start_position IS NOT NULL AND end_position IS NOT NULL
+        "#,
+        text_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch marks: {}", e))?;
+
+    let mut unmoved_marks = Vec::new();
+    for mark in marks {
+        let target = dom_ranges
+            .iter()
+            .position(|&(start, end)| start <= mark.start_position && mark.end_position <= end);
+        let Some(index) = target else {
+            // No single section holds the whole mark; the source still does, so it stays there
+            sqlx::query!(
+                r#"
+                UPDATE cloze_notes
+                SET status = 'needs_review',
+                    notes = 'Spans a section boundary - kept on the original text',
+                    updated_at = ?
+                WHERE id = ? AND status NOT IN ('buried', 'converted')
+                "#,
+                now,
+                mark.id
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to flag mark {}: {}", mark.id, e))?;
+
+            unmoved_marks.push(mark.id);
+            continue;
+        };
+        let (child_id, offset) = (child_ids[index], dom_ranges[index].0);
+
+        sqlx::query!(
+            r#"
+            UPDATE cloze_notes
+            SET text_id = ?, start_position = start_position - ?, end_position = end_position - ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            child_id,
+            offset,
+            offset,
+            now,
+            mark.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to move mark: {}", e))?;
+
+        sqlx::query!(
+            "UPDATE flashcards SET text_id = ?, updated_at = ? WHERE cloze_note_id = ?",
+            child_id,
+            now,
+            mark.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to move flashcards: {}", e))?;
+    }
+
+    let ranges = sqlx::query!(
+        r#"
+        SELECT user_id as "user_id!", start_position, end_position, marked_at, session_id, is_auto_completed
+        FROM read_ranges
+        WHERE text_id = ?
+        "#,
+        text_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
+
+    for range in &ranges {
+        for (&child_id, &(start, end)) in child_ids.iter().zip(&dom_ranges) {
+            let piece_start = range.start_position.max(start);
+            let piece_end = range.end_position.min(end);
+            if piece_start >= piece_end {
+                continue;
+            }
+            let (piece_start, piece_end) = (piece_start - start, piece_end - start);
+
+            sqlx::query!(
+                r#"
+                INSERT INTO read_ranges (
+                    text_id, user_id, start_position, end_position, marked_at, session_id, is_auto_completed
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+                child_id,
+                range.user_id,
+                piece_start,
+                piece_end,
+                range.marked_at,
+                range.session_id,
+                range.is_auto_completed
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to copy read range: {}", e))?;
+        }
+    }
+
+    // Sections that received reading progress are already being read
+    for &child_id in &child_ids {
+        sqlx::query!(
+            r#"
+            UPDATE texts SET status = 'reading'
+            WHERE id = ? AND EXISTS (SELECT 1 FROM read_ranges WHERE text_id = ?)
+            "#,
+            child_id,
+            child_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update text status: {}", e))?;
+    }
+
+    let folder = sqlx::query_as!(
+        Folder,
+        r#"
+        SELECT id, name, parent_id, created_at, updated_at
+        FROM folders
+        WHERE id = ?
+        "#,
+        folder_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch created folder: {}", e))?;
+
+    let mut texts = Vec::with_capacity(child_ids.len());
+    for &child_id in &child_ids {
+        texts.push(fetch_text(&mut tx, child_id).await?);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(SplitResult {
+        folder,
+        texts,
+        unmoved_marks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Renders as "Lead text.\n\nBody\n\nMore text.": the lead is 0..10, the body section 12..28
+    const ARTICLE: &str = "Lead text.\n\n== Body ==\n\nMore text.";
+
+    async fn insert_mark(pool: &Pool<Sqlite>, text_id: i64, start: i64, end: i64) -> i64 {
+        sqlx::query(
+            "INSERT INTO cloze_notes (text_id, original_text, parsed_segments, cloze_count, start_position, end_position) \
+             VALUES (?, 'mark', '[]', 1, ?, ?)",
+        )
+        .bind(text_id)
+        .bind(start)
+        .bind(end)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    async fn read_ranges(pool: &Pool<Sqlite>, text_id: i64) -> Vec<(i64, i64)> {
+        sqlx::query_as("SELECT start_position, end_position FROM read_ranges WHERE text_id = ? ORDER BY start_position")
+            .bind(text_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_split_keeps_source_progress_and_flags_boundary_marks() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+
+        let text_id = sqlx::query("INSERT INTO texts (title, content, content_length) VALUES ('Article', ?, ?)")
+            .bind(ARTICLE)
+            .bind(ARTICLE.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        sqlx::query("INSERT INTO read_ranges (text_id, start_position, end_position) VALUES (?, 0, 28)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        // Excludes "text.\n\nBody More"
+        sqlx::query("INSERT INTO excluded_ranges (text_id, start_position, end_position) VALUES (?, 5, 22)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        // "text.\n\nBody" crosses into the body section, "More" sits inside it
+        let crossing = insert_mark(pool, text_id, 5, 16).await;
+        let inside = insert_mark(pool, text_id, 18, 22).await;
+
+        let result = split_by_headers(pool, text_id, None, Utc::now()).await.unwrap();
+        let (lead, body) = (result.texts[0].id, result.texts[1].id);
+
+        assert_eq!(read_ranges(pool, text_id).await, vec![(0, 28)]);
+        assert_eq!(read_ranges(pool, lead).await, vec![(0, 10)]);
+        assert_eq!(read_ranges(pool, body).await, vec![(0, 16)]);
+        assert_eq!(fetch_excluded_ranges(&mut pool.acquire().await.unwrap(), lead).await.unwrap(), vec![(5, 10)]);
+        assert_eq!(fetch_excluded_ranges(&mut pool.acquire().await.unwrap(), body).await.unwrap(), vec![(0, 10)]);
+
+        assert_eq!(result.unmoved_marks, vec![crossing]);
+        let (owner, status): (i64, String) = sqlx::query_as("SELECT text_id, status FROM cloze_notes WHERE id = ?")
+            .bind(crossing)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!((owner, status.as_str()), (text_id, "needs_review"));
+
+        let moved: (i64, i64, i64) =
+            sqlx::query_as("SELECT text_id, start_position, end_position FROM cloze_notes WHERE id = ?")
+                .bind(inside)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        assert_eq!(moved, (body, 6, 10));
+    }
+}
@@ -1,4 +1,8 @@
+use crate::commands::flashcards::ClozeCommandError;
 use crate::db::Database;
+use crate::services::cloze_suggester::{ClozeSuggester, ClozeSuggestion, LibraryWordIndex};
+use crate::services::duplicate_detector::{self, DuplicateMatch, DEFAULT_DUPLICATE_THRESHOLD};
+use crate::services::mark_anchor::{self, AnchorMatch};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -14,6 +18,42 @@ pub enum ScopeType {
     Text,
 }
 
+/// Ordering for marks in the hub
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HubOrder {
+    /// Least recently seen first (default)
+    #[default]
+    Seen,
+    /// Highest priority first
+    Priority,
+    /// Grouped by text, in reading order within each text
+    Position,
+    /// Alphabetical by text title, then reading order
+    Title,
+    /// Oldest marks first
+    Age,
+    /// One mark from each text in turn, highest priority first within a text
+    RoundRobin,
+}
+
+impl HubOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HubOrder::Seen => "seen",
+            HubOrder::Priority => "priority",
+            HubOrder::Position => "position",
+            HubOrder::Title => "title",
+            HubOrder::Age => "age",
+            HubOrder::RoundRobin => "round_robin",
+        }
+    }
+}
+
+/// Mark priority range, higher is more important
+pub const MIN_MARK_PRIORITY: i64 = 0;
+pub const MAX_MARK_PRIORITY: i64 = 10;
+
 /// Mark with context for display in the creation hub
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +67,7 @@ pub struct MarkWithContext {
     pub before_context: String,
     pub after_context: String,
     pub has_card: bool,
+    pub priority: i64,
     pub created_at: DateTime<Utc>,
 }
 
@@ -64,6 +105,41 @@ pub struct CreatedCard {
     pub text_title: String,
 }
 
+/// Cards created from a mark, plus existing notes that look like the same fact
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCardsResult {
+    pub cards: Vec<CreatedCard>,
+    pub duplicates: Vec<DuplicateMatch>,
+}
+
+/// Mark flagged after a text edit, with its best match in the current text
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkNeedingReview {
+    pub id: i64,
+    pub text_id: i64,
+    pub text_title: String,
+    /// Marked text as it was before the edit
+    pub original_text: String,
+    /// Why the mark was flagged
+    pub notes: Option<String>,
+    /// Stored range, which may no longer point at the marked text
+    pub start_position: Option<i64>,
+    pub end_position: Option<i64>,
+    /// Best match for the marked text in the current content, if any
+    pub proposed: Option<AnchorMatch>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Ranked cloze suggestions for a single mark
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkSuggestions {
+    pub mark: MarkWithContext,
+    pub suggestions: Vec<ClozeSuggestion>,
+}
+
 /// Convert UTF-16 code unit offset to byte offset
 /// Positions in the database are UTF-16 code units (matching JavaScript's string.length)
 /// but Rust string slicing requires byte offsets
@@ -127,6 +203,52 @@ fn extract_context_from_positions(content: &str, start_pos: i64, end_pos: i64, c
     (before_context, after_context, start_pos, end_pos)
 }
 
+/// Extract the paragraph surrounding a marked range, using stored positions in DOM space
+///
+/// Works like `extract_context_from_positions`, but the context stops at the
+/// enclosing paragraph's line breaks instead of a fixed window. `max_chars` caps
+/// each side (in UTF-16 units) so very long paragraphs stay readable.
+/// Returns (before, marked, after), or None when the positions don't fit the content.
+pub(crate) fn extract_paragraph_from_positions(
+    content: &str,
+    start_pos: i64,
+    end_pos: i64,
+    max_chars: usize,
+) -> Option<(String, String, String)> {
+    let dom_content = process_content_to_dom_space(content);
+    let dom_utf16_len = dom_content.encode_utf16().count();
+
+    if start_pos < 0 || end_pos < 0 {
+        return None;
+    }
+    let start_utf16 = start_pos as usize;
+    let end_utf16 = end_pos as usize;
+    if start_utf16 >= dom_utf16_len || end_utf16 > dom_utf16_len || start_utf16 >= end_utf16 {
+        return None;
+    }
+
+    let start_byte = utf16_offset_to_byte_offset(&dom_content, start_utf16);
+    let end_byte = utf16_offset_to_byte_offset(&dom_content, end_utf16);
+
+    // Expand to the enclosing paragraph
+    let paragraph_start = dom_content[..start_byte].rfind('\n').map_or(0, |i| i + 1);
+    let paragraph_end = dom_content[end_byte..]
+        .find('\n')
+        .map_or(dom_content.len(), |i| end_byte + i);
+
+    // Clamp each side to max_chars
+    let window_start_byte =
+        utf16_offset_to_byte_offset(&dom_content, start_utf16.saturating_sub(max_chars));
+    let window_end_byte =
+        utf16_offset_to_byte_offset(&dom_content, std::cmp::min(end_utf16 + max_chars, dom_utf16_len));
+
+    let before = &dom_content[paragraph_start.max(window_start_byte)..start_byte];
+    let marked = &dom_content[start_byte..end_byte];
+    let after = &dom_content[end_byte..paragraph_end.min(window_end_byte)];
+
+    Some((before.to_string(), marked.to_string(), after.to_string()))
+}
+
 /// Parse a markdown link starting at the given byte index
 /// Returns (link_text, url, end_index) or None if no valid link found
 /// Properly handles URLs with parentheses like https://en.wikipedia.org/wiki/Name_(disambiguation)
@@ -210,7 +332,7 @@ fn strip_markdown_links(text: &str) -> String {
 
 /// Process content to DOM space by stripping [[exclude]] tags and markdown links
 /// This matches how the frontend renders content
-fn process_content_to_dom_space(content: &str) -> String {
+pub(crate) fn process_content_to_dom_space(content: &str) -> String {
     use regex::Regex;
 
     // First, strip [[exclude]] tags (keep the content inside)
@@ -229,6 +351,17 @@ fn process_content_to_dom_space(content: &str) -> String {
     cleaned
 }
 
+/// DOM-space UTF-16 position of the raw UTF-16 position `raw_utf16` in `content`
+///
+/// Renders the prefix before the position, so it's only exact at positions outside
+/// link, header and exclude markup.
+pub(crate) fn to_rendered_position(content: &str, raw_utf16: i64) -> i64 {
+    let prefix: Vec<u16> = content.encode_utf16().take(raw_utf16 as usize).collect();
+    process_content_to_dom_space(&String::from_utf16_lossy(&prefix))
+        .encode_utf16()
+        .count() as i64
+}
+
 /// Helper to extract context around marked text in content using string search
 /// This is a fallback for old marks that don't have stored positions
 fn extract_context(content: &str, marked_text: &str, context_chars: usize) -> (String, String, i64, i64) {
@@ -252,6 +385,49 @@ fn extract_context(content: &str, marked_text: &str, context_chars: usize) -> (S
     }
 }
 
+/// A pending mark as the hub queries fetch it, the same columns for every scope
+struct HubMarkRow {
+    id: i64,
+    text_id: i64,
+    title: String,
+    content: String,
+    original_text: String,
+    start_position: Option<i64>,
+    end_position: Option<i64>,
+    priority: i64,
+    created_at: DateTime<Utc>,
+    card_count: i64,
+}
+
+impl From<HubMarkRow> for MarkWithContext {
+    fn from(row: HubMarkRow) -> Self {
+        // Use stored positions if available, otherwise fallback to string search
+        let (before_context, after_context, start_pos, end_pos) = match (row.start_position, row.end_position) {
+            (Some(start), Some(end)) => {
+                extract_context_from_positions(&row.content, start, end, 200)
+            }
+            _ => {
+                // Fallback to string search for old marks without positions
+                extract_context(&row.content, &row.original_text, 200)
+            }
+        };
+
+        MarkWithContext {
+            id: row.id,
+            text_id: row.text_id,
+            text_title: row.title,
+            start_position: start_pos,
+            end_position: end_pos,
+            marked_text: row.original_text,
+            before_context,
+            after_context,
+            has_card: row.card_count > 0,
+            priority: row.priority,
+            created_at: row.created_at,
+        }
+    }
+}
+
 /// Get marks for the flashcard creation hub based on scope
 ///
 /// # Arguments
@@ -259,16 +435,30 @@ fn extract_context(content: &str, marked_text: &str, context_chars: usize) -> (S
 /// * `scope_id` - Optional ID for folder or text scope
 /// * `limit` - Maximum number of marks to return (default varies by scope)
 /// * `offset` - Offset for pagination (default 0)
+/// * `order` - Mark ordering (default: least recently seen first)
 #[tauri::command]
 pub async fn get_hub_marks(
     scope: ScopeType,
     scope_id: Option<String>,
     limit: Option<i64>,
     offset: Option<i64>,
+    order: Option<HubOrder>,
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<HubMarksResponse, String> {
     let db = db.lock().await;
-    let pool = db.pool();
+
+    hub_marks(db.pool(), scope, scope_id, limit, offset, order.unwrap_or_default()).await
+}
+
+async fn hub_marks(
+    pool: &sqlx::SqlitePool,
+    scope: ScopeType,
+    scope_id: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    order: HubOrder,
+) -> Result<HubMarksResponse, String> {
+    let order_mode = order.as_str();
     let clamped_limit = match scope {
         ScopeType::Library => limit.unwrap_or(1000).clamp(1, 1000),
         ScopeType::Folder => limit.unwrap_or(200).clamp(1, 200),
@@ -276,7 +466,7 @@ pub async fn get_hub_marks(
     };
     let offset_val = offset.unwrap_or(0);
 
-    let (total_count, marks) = match scope {
+    let (total_count, rows) = match scope {
         ScopeType::Library => {
             // Get total count first
             let total_count: i64 = sqlx::query_scalar!(
@@ -291,26 +481,43 @@ pub async fn get_hub_marks(
             .await
             .map_err(|e| format!("Failed to count library marks: {}", e))?;
 
-            // Get all pending marks from entire library
-            let rows = sqlx::query!(
+            // Get all pending marks from entire library. The ordering is bound once as
+            // hub_order.mode and each CASE only sorts in its own mode; the other scopes
+            // run the same query with their filter added.
+            let rows = sqlx::query_as!(
+                HubMarkRow,
                 r#"
+                WITH hub_order(mode) AS (SELECT ?)
                 SELECT
-                    cn.id,
+                    cn.id as "id!",
                     cn.text_id,
                     t.title,
                     t.content,
                     cn.original_text,
                     cn.start_position,
                     cn.end_position,
-                    cn.created_at,
-                    (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as card_count
+                    cn.priority,
+                    cn.created_at as "created_at: DateTime<Utc>",
+                    (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as "card_count!: i64"
                 FROM cloze_notes cn
                 INNER JOIN texts t ON cn.text_id = t.id
+                CROSS JOIN hub_order ho
                 WHERE cn.status IN ('pending', 'skipped')
                   AND (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) = 0
-                ORDER BY cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                ORDER BY 
+                    CASE WHEN ho.mode = 'priority' THEN cn.priority END DESC,
+                    CASE WHEN ho.mode = 'round_robin' THEN ROW_NUMBER() OVER (
+                        PARTITION BY cn.text_id
+                        ORDER BY cn.priority DESC, cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                    ) END ASC,
+                    CASE WHEN ho.mode = 'title' THEN t.title END COLLATE NOCASE ASC,
+                    CASE WHEN ho.mode IN ('position', 'title', 'round_robin') THEN cn.text_id END ASC,
+                    CASE WHEN ho.mode IN ('position', 'title') THEN cn.start_position END ASC,
+                    CASE WHEN ho.mode = 'age' THEN cn.created_at END ASC,
+                    cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
                 LIMIT ? OFFSET ?
                 "#,
+                order_mode,
                 clamped_limit,
                 offset_val
             )
@@ -318,35 +525,7 @@ pub async fn get_hub_marks(
             .await
             .map_err(|e| format!("Failed to fetch library marks: {}", e))?;
 
-            let marks: Vec<MarkWithContext> = rows.into_iter()
-                .map(|row| {
-                    // Use stored positions if available, otherwise fallback to string search
-                    let (before_context, after_context, start_pos, end_pos) = match (row.start_position, row.end_position) {
-                        (Some(start), Some(end)) => {
-                            extract_context_from_positions(&row.content, start, end, 200)
-                        }
-                        _ => {
-                            // Fallback to string search for old marks without positions
-                            extract_context(&row.content, &row.original_text, 200)
-                        }
-                    };
-
-                    MarkWithContext {
-                        id: row.id.unwrap_or(0),
-                        text_id: row.text_id,
-                        text_title: row.title,
-                        start_position: start_pos,
-                        end_position: end_pos,
-                        marked_text: row.original_text,
-                        before_context,
-                        after_context,
-                        has_card: row.card_count > 0,
-                        created_at: DateTime::from_naive_utc_and_offset(row.created_at, Utc),
-                    }
-                })
-                .collect();
-
-            (total_count, marks)
+            (total_count, rows)
         }
         ScopeType::Folder => {
             let folder_id = scope_id
@@ -377,20 +556,24 @@ pub async fn get_hub_marks(
             .map_err(|e| format!("Failed to count folder marks: {}", e))?;
 
             // Get pending marks from texts in specified folder AND all its subfolders (recursive)
-            let rows = sqlx::query!(
+            let rows = sqlx::query_as!(
+                HubMarkRow,
                 r#"
+                WITH hub_order(mode) AS (SELECT ?)
                 SELECT
-                    cn.id,
+                    cn.id as "id!",
                     cn.text_id,
                     t.title,
                     t.content,
                     cn.original_text,
                     cn.start_position,
                     cn.end_position,
-                    cn.created_at,
-                    (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as card_count
+                    cn.priority,
+                    cn.created_at as "created_at: DateTime<Utc>",
+                    (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as "card_count!: i64"
                 FROM cloze_notes cn
                 INNER JOIN texts t ON cn.text_id = t.id
+                CROSS JOIN hub_order ho
                 WHERE cn.status IN ('pending', 'skipped')
                   AND (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) = 0
                   AND t.folder_id IN (
@@ -402,9 +585,20 @@ pub async fn get_hub_marks(
                     )
                     SELECT id FROM folder_tree
                 )
-                ORDER BY cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                ORDER BY 
+                    CASE WHEN ho.mode = 'priority' THEN cn.priority END DESC,
+                    CASE WHEN ho.mode = 'round_robin' THEN ROW_NUMBER() OVER (
+                        PARTITION BY cn.text_id
+                        ORDER BY cn.priority DESC, cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                    ) END ASC,
+                    CASE WHEN ho.mode = 'title' THEN t.title END COLLATE NOCASE ASC,
+                    CASE WHEN ho.mode IN ('position', 'title', 'round_robin') THEN cn.text_id END ASC,
+                    CASE WHEN ho.mode IN ('position', 'title') THEN cn.start_position END ASC,
+                    CASE WHEN ho.mode = 'age' THEN cn.created_at END ASC,
+                    cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
                 LIMIT ? OFFSET ?
                 "#,
+                order_mode,
                 folder_id,
                 clamped_limit,
                 offset_val
@@ -413,35 +607,7 @@ pub async fn get_hub_marks(
             .await
             .map_err(|e| format!("Failed to fetch folder marks: {}", e))?;
 
-            let marks: Vec<MarkWithContext> = rows.into_iter()
-                .map(|row| {
-                    // Use stored positions if available, otherwise fallback to string search
-                    let (before_context, after_context, start_pos, end_pos) = match (row.start_position, row.end_position) {
-                        (Some(start), Some(end)) => {
-                            extract_context_from_positions(&row.content, start, end, 200)
-                        }
-                        _ => {
-                            // Fallback to string search for old marks without positions
-                            extract_context(&row.content, &row.original_text, 200)
-                        }
-                    };
-
-                    MarkWithContext {
-                        id: row.id.unwrap_or(0),
-                        text_id: row.text_id,
-                        text_title: row.title,
-                        start_position: start_pos,
-                        end_position: end_pos,
-                        marked_text: row.original_text,
-                        before_context,
-                        after_context,
-                        has_card: row.card_count > 0,
-                        created_at: DateTime::from_naive_utc_and_offset(row.created_at, Utc),
-                    }
-                })
-                .collect();
-
-            (total_count, marks)
+            (total_count, rows)
         }
         ScopeType::Text => {
             let text_id = scope_id
@@ -465,26 +631,41 @@ pub async fn get_hub_marks(
             .map_err(|e| format!("Failed to count text marks: {}", e))?;
 
             // Get pending marks from specific text
-            let rows = sqlx::query!(
+            let rows = sqlx::query_as!(
+                HubMarkRow,
                 r#"
+                WITH hub_order(mode) AS (SELECT ?)
                 SELECT
-                    cn.id,
+                    cn.id as "id!",
                     cn.text_id,
                     t.title,
                     t.content,
                     cn.original_text,
                     cn.start_position,
                     cn.end_position,
-                    cn.created_at,
+                    cn.priority,
+                    cn.created_at as "created_at: DateTime<Utc>",
                     (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as "card_count!: i64"
                 FROM cloze_notes cn
                 INNER JOIN texts t ON cn.text_id = t.id
+                CROSS JOIN hub_order ho
                 WHERE cn.status IN ('pending', 'skipped')
                   AND (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) = 0
                   AND cn.text_id = ?
-                ORDER BY cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                ORDER BY 
+                    CASE WHEN ho.mode = 'priority' THEN cn.priority END DESC,
+                    CASE WHEN ho.mode = 'round_robin' THEN ROW_NUMBER() OVER (
+                        PARTITION BY cn.text_id
+                        ORDER BY cn.priority DESC, cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
+                    ) END ASC,
+                    CASE WHEN ho.mode = 'title' THEN t.title END COLLATE NOCASE ASC,
+                    CASE WHEN ho.mode IN ('position', 'title', 'round_robin') THEN cn.text_id END ASC,
+                    CASE WHEN ho.mode IN ('position', 'title') THEN cn.start_position END ASC,
+                    CASE WHEN ho.mode = 'age' THEN cn.created_at END ASC,
+                    cn.last_seen_at ASC NULLS FIRST, cn.created_at ASC
                 LIMIT ? OFFSET ?
                 "#,
+                order_mode,
                 text_id,
                 clamped_limit,
                 offset_val
@@ -493,38 +674,11 @@ pub async fn get_hub_marks(
             .await
             .map_err(|e| format!("Failed to fetch text marks: {}", e))?;
 
-            let marks: Vec<MarkWithContext> = rows.into_iter()
-                .map(|row| {
-                    // Use stored positions if available, otherwise fallback to string search
-                    let (before_context, after_context, start_pos, end_pos) = match (row.start_position, row.end_position) {
-                        (Some(start), Some(end)) => {
-                            extract_context_from_positions(&row.content, start, end, 200)
-                        }
-                        _ => {
-                            // Fallback to string search for old marks without positions
-                            extract_context(&row.content, &row.original_text, 200)
-                        }
-                    };
-
-                    MarkWithContext {
-                        id: row.id.unwrap_or(0),
-                        text_id: row.text_id,
-                        text_title: row.title,
-                        start_position: start_pos,
-                        end_position: end_pos,
-                        marked_text: row.original_text,
-                        before_context,
-                        after_context,
-                        has_card: row.card_count > 0,
-                        created_at: DateTime::from_naive_utc_and_offset(row.created_at, Utc),
-                    }
-                })
-                .collect();
-
-            (total_count, marks)
+            (total_count, rows)
         }
     };
 
+    let marks: Vec<MarkWithContext> = rows.into_iter().map(MarkWithContext::from).collect();
     let marks_len = marks.len() as i64;
     Ok(HubMarksResponse {
         marks,
@@ -534,16 +688,112 @@ pub async fn get_hub_marks(
     })
 }
 
+/// Suggest cloze deletions for a mark using offline heuristics
+///
+/// Rarity of words is judged against every other text in the library, using word
+/// counts kept for the session in `word_index`.
+#[tauri::command]
+pub async fn suggest_clozes_for_mark(
+    mark_id: i64,
+    limit: Option<usize>,
+    db: State<'_, Arc<Mutex<Database>>>,
+    word_index: State<'_, Mutex<LibraryWordIndex>>,
+) -> Result<MarkSuggestions, String> {
+    let db = db.lock().await;
+    let mut word_index = word_index.lock().await;
+    let pool = db.pool();
+    let limit = limit.unwrap_or(5).clamp(1, 20);
+
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            cn.id as "id!",
+            cn.text_id,
+            t.title,
+            t.content,
+            cn.original_text,
+            cn.start_position,
+            cn.end_position,
+            cn.priority,
+            cn.created_at,
+            (SELECT COUNT(*) FROM flashcards WHERE cloze_note_id = cn.id) as "card_count!: i64"
+        FROM cloze_notes cn
+        INNER JOIN texts t ON cn.text_id = t.id
+        WHERE cn.id = ?
+        "#,
+        mark_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch mark: {}", e))?
+    .ok_or_else(|| "Mark not found".to_string())?;
+
+    // Only texts added or edited since the last suggestion are loaded and counted
+    let library_texts = sqlx::query!(
+        r#"SELECT id as "id!", updated_at as "updated_at: DateTime<Utc>" FROM texts"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch library texts: {}", e))?;
+    let library_texts: Vec<(i64, DateTime<Utc>)> =
+        library_texts.into_iter().map(|text| (text.id, text.updated_at)).collect();
+
+    for text_id in word_index.stale_texts(&library_texts) {
+        let text = sqlx::query!(
+            r#"SELECT content, updated_at as "updated_at: DateTime<Utc>" FROM texts WHERE id = ?"#,
+            text_id
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch library text: {}", e))?;
+        word_index.insert(text_id, text.updated_at, &text.content);
+    }
+
+    let words = ClozeSuggester::candidate_words(&row.original_text);
+    let (word_counts, library_text_count) = word_index.counts(&words, row.text_id);
+    let suggestions = ClozeSuggester::suggest(
+        &row.original_text,
+        &word_counts,
+        library_text_count,
+        limit,
+    );
+
+    let (before_context, after_context, start_pos, end_pos) = match (row.start_position, row.end_position) {
+        (Some(start), Some(end)) => extract_context_from_positions(&row.content, start, end, 200),
+        _ => extract_context(&row.content, &row.original_text, 200),
+    };
+
+    Ok(MarkSuggestions {
+        mark: MarkWithContext {
+            id: row.id,
+            text_id: row.text_id,
+            text_title: row.title,
+            start_position: start_pos,
+            end_position: end_pos,
+            marked_text: row.original_text,
+            before_context,
+            after_context,
+            has_card: row.card_count > 0,
+            priority: row.priority,
+            created_at: DateTime::from_naive_utc_and_offset(row.created_at, Utc),
+        },
+        suggestions,
+    })
+}
+
 /// Skip a mark - mark as skipped for current session (will reappear later)
 /// Updates last_seen_at and increments session_count
+/// With `lower_priority`, also drops the mark's priority by one step
 #[tauri::command]
 pub async fn skip_mark(
     mark_id: i64,
+    lower_priority: Option<bool>,
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<(), String> {
     let db = db.lock().await;
     let pool = db.pool();
     let now = Utc::now();
+    let priority_step: i64 = if lower_priority.unwrap_or(false) { 1 } else { 0 };
 
     sqlx::query!(
         r#"
@@ -551,10 +801,13 @@ pub async fn skip_mark(
         SET status = 'skipped',
             last_seen_at = ?,
             session_count = session_count + 1,
+            priority = MAX(priority - ?, ?),
             updated_at = ?
         WHERE id = ?
         "#,
         now,
+        priority_step,
+        MIN_MARK_PRIORITY,
         now,
         mark_id
     )
@@ -565,6 +818,213 @@ pub async fn skip_mark(
     Ok(())
 }
 
+/// Set a mark's priority, clamped to the 0-10 range
+#[tauri::command]
+pub async fn set_mark_priority(
+    mark_id: i64,
+    priority: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<i64, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+    let priority = priority.clamp(MIN_MARK_PRIORITY, MAX_MARK_PRIORITY);
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE cloze_notes
+        SET priority = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+        priority,
+        now,
+        mark_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to set mark priority: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err("Mark not found".to_string());
+    }
+
+    Ok(priority)
+}
+
+/// List marks flagged as 'needs_review' after text edits
+/// Each mark comes with its best match in the text's current content
+#[tauri::command]
+pub async fn get_marks_needing_review(
+    text_id: Option<i64>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<MarkNeedingReview>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            cn.id as "id!",
+            cn.text_id,
+            t.title,
+            t.content,
+            cn.original_text,
+            cn.notes,
+            cn.start_position,
+            cn.end_position,
+            cn.updated_at
+        FROM cloze_notes cn
+        INNER JOIN texts t ON cn.text_id = t.id
+        WHERE cn.status = 'needs_review'
+          AND (? IS NULL OR cn.text_id = ?)
+        ORDER BY cn.text_id ASC, cn.start_position ASC
+        "#,
+        text_id,
+        text_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch flagged marks: {}", e))?;
+
+    let marks = rows
+        .into_iter()
+        .map(|row| {
+            let dom_content = process_content_to_dom_space(&row.content);
+            let proposed = mark_anchor::reanchor(
+                &dom_content,
+                &row.original_text,
+                row.start_position,
+                row.end_position,
+                0.0,
+            );
+
+            MarkNeedingReview {
+                id: row.id,
+                text_id: row.text_id,
+                text_title: row.title,
+                original_text: row.original_text,
+                notes: row.notes,
+                start_position: row.start_position,
+                end_position: row.end_position,
+                proposed,
+                updated_at: DateTime::from_naive_utc_and_offset(row.updated_at, Utc),
+            }
+        })
+        .collect();
+
+    Ok(marks)
+}
+
+/// Move a mark to a new range and return it to 'pending'
+/// The marked text is refreshed from the current content so context stays in sync
+async fn anchor_mark(
+    pool: &sqlx::SqlitePool,
+    mark_id: i64,
+    start_position: i64,
+    end_position: i64,
+) -> Result<AnchorMatch, String> {
+    let content: String = sqlx::query_scalar!(
+        r#"
+        SELECT t.content
+        FROM cloze_notes cn
+        INNER JOIN texts t ON cn.text_id = t.id
+        WHERE cn.id = ?
+        "#,
+        mark_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch mark text: {}", e))?
+    .ok_or_else(|| "Mark not found".to_string())?;
+
+    let dom_content = process_content_to_dom_space(&content);
+    let marked_text = mark_anchor::slice_utf16(&dom_content, start_position, end_position)
+        .ok_or_else(|| format!("Invalid range {}..{} for this text", start_position, end_position))?;
+
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        UPDATE cloze_notes
+        SET start_position = ?,
+            end_position = ?,
+            original_text = ?,
+            status = 'pending',
+            notes = NULL,
+            updated_at = ?
+        WHERE id = ?
+        "#,
+        start_position,
+        end_position,
+        marked_text,
+        now,
+        mark_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to re-anchor mark: {}", e))?;
+
+    Ok(AnchorMatch {
+        start: start_position,
+        end: end_position,
+        matched_text: marked_text,
+        confidence: 1.0,
+    })
+}
+
+/// Accept the proposed new range for a flagged mark
+#[tauri::command]
+pub async fn accept_mark_anchor(
+    mark_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<AnchorMatch, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let row = sqlx::query!(
+        r#"
+        SELECT t.content, cn.original_text, cn.start_position, cn.end_position
+        FROM cloze_notes cn
+        INNER JOIN texts t ON cn.text_id = t.id
+        WHERE cn.id = ?
+        "#,
+        mark_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch mark: {}", e))?
+    .ok_or_else(|| "Mark not found".to_string())?;
+
+    let dom_content = process_content_to_dom_space(&row.content);
+    let proposed = mark_anchor::reanchor(
+        &dom_content,
+        &row.original_text,
+        row.start_position,
+        row.end_position,
+        0.0,
+    )
+    .ok_or_else(|| "No match for the marked text in the current content".to_string())?;
+
+    let anchored = anchor_mark(pool, mark_id, proposed.start, proposed.end).await?;
+    Ok(AnchorMatch {
+        confidence: proposed.confidence,
+        ..anchored
+    })
+}
+
+/// Re-anchor a flagged mark to a range picked by the user
+#[tauri::command]
+pub async fn set_mark_range(
+    mark_id: i64,
+    start_position: i64,
+    end_position: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<AnchorMatch, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    anchor_mark(pool, mark_id, start_position, end_position).await
+}
+
 /// Delete a mark - permanently remove from database
 /// Flashcards created from this mark will be preserved (ON DELETE SET NULL)
 #[tauri::command]
@@ -671,13 +1131,14 @@ pub async fn get_hub_stats(
 
 /// Create flashcards from a mark with cloze deletions
 /// Parses cloze text, creates multiple flashcards (one per cloze deletion), and marks the cloze note as 'converted'
+/// Also reports existing notes whose text is a likely duplicate of the new cards
 #[tauri::command]
 pub async fn create_card_from_mark(
     mark_id: i64,
     selected_text: String,
     cloze_text: String,
     db_state: State<'_, Arc<Mutex<Database>>>,
-) -> Result<Vec<CreatedCard>, String> {
+) -> Result<CreateCardsResult, ClozeCommandError> {
     use crate::services::cloze_parser::ClozeParser;
 
     let db = db_state.lock().await;
@@ -700,21 +1161,26 @@ pub async fn create_card_from_mark(
     .map_err(|e| format!("Failed to fetch cloze note: {}", e))?;
 
     // Parse the cloze text to extract segments and cloze numbers
-    let parsed = ClozeParser::parse(&cloze_text)
-        .map_err(|e| format!("Failed to parse cloze text: {}", e))?;
+    let parsed = ClozeParser::parse_for_cards(&cloze_text)?;
 
     let cloze_numbers = ClozeParser::extract_cloze_numbers(&cloze_text)
         .map_err(|e| format!("Failed to extract cloze numbers: {}", e))?;
 
-    if cloze_numbers.is_empty() {
-        return Err("No cloze deletions found in text".to_string());
-    }
-
     let parsed_segments_json = serde_json::to_string(&parsed.segments)
         .map_err(|e| format!("Failed to serialize parsed segments: {}", e))?;
 
     let cloze_count = cloze_numbers.len() as i64;
 
+    // Look for duplicates before this note gets a cloze count and joins the comparison set
+    let duplicates = duplicate_detector::find_duplicates_for_text(
+        pool,
+        &cloze_text,
+        Some(mark_id),
+        DEFAULT_DUPLICATE_THRESHOLD,
+    )
+    .await
+    .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+
     // Update the cloze note with parsed segments and cloze count
     sqlx::query!(
         r#"
@@ -857,5 +1323,153 @@ pub async fn create_card_from_mark(
         .map_err(|e| format!("Failed to update cloze note status: {}", e))?;
     }
 
-    Ok(created_cards)
+    Ok(CreateCardsResult {
+        cards: created_cards,
+        duplicates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two texts with marks whose priority, position, age and last view all order differently
+    async fn hub_fixture() -> Database {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+
+        sqlx::query(
+            "INSERT INTO folders (id, name) VALUES ('root', 'Root'); \
+             INSERT INTO folders (id, name, parent_id) VALUES ('child', 'Child', 'root'); \
+             INSERT INTO texts (id, title, content, content_length) VALUES (1, 'Beta', '0123456789 abcdefghij', 21); \
+             INSERT INTO texts (id, title, content, content_length, folder_id) VALUES (2, 'alpha', '0123456789', 10, 'child');",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+
+        // (name, text, start, priority, created_at, last_seen_at)
+        let marks = [
+            ("m1", 1, 10, 5, "2025-01-03 00:00:00", None),
+            ("m2", 1, 0, 9, "2025-01-06 00:00:00", Some("2025-02-01 00:00:00")),
+            ("m3", 1, 5, 1, "2025-01-05 00:00:00", Some("2025-01-15 00:00:00")),
+            ("m4", 2, 3, 7, "2025-01-02 00:00:00", Some("2025-01-10 00:00:00")),
+            ("m5", 2, 1, 8, "2025-01-04 00:00:00", None),
+        ];
+        for (name, text_id, start, priority, created_at, last_seen_at) in marks {
+            sqlx::query(
+                "INSERT INTO cloze_notes (text_id, original_text, parsed_segments, cloze_count, \
+                 start_position, end_position, priority, created_at, last_seen_at) \
+                 VALUES (?, ?, '[]', 0, ?, ?, ?, ?, ?)",
+            )
+            .bind(text_id)
+            .bind(name)
+            .bind(start)
+            .bind(start + 1)
+            .bind(priority)
+            .bind(created_at)
+            .bind(last_seen_at)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        db
+    }
+
+    async fn hub_order(db: &Database, scope: ScopeType, scope_id: Option<&str>, order: HubOrder) -> Vec<String> {
+        hub_marks(db.pool(), scope, scope_id.map(str::to_string), None, None, order)
+            .await
+            .unwrap()
+            .marks
+            .into_iter()
+            .map(|mark| mark.marked_text)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_hub_orderings() {
+        let db = hub_fixture().await;
+
+        let cases = [
+            (HubOrder::Seen, ["m1", "m5", "m4", "m3", "m2"]),
+            (HubOrder::Priority, ["m2", "m5", "m4", "m1", "m3"]),
+            (HubOrder::Position, ["m2", "m3", "m1", "m5", "m4"]),
+            (HubOrder::Title, ["m5", "m4", "m2", "m3", "m1"]),
+            (HubOrder::Age, ["m4", "m1", "m5", "m3", "m2"]),
+            (HubOrder::RoundRobin, ["m2", "m5", "m1", "m4", "m3"]),
+        ];
+        for (order, expected) in cases {
+            assert_eq!(hub_order(&db, ScopeType::Library, None, order).await, expected, "{:?}", order);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hub_orderings_within_scopes() {
+        let db = hub_fixture().await;
+
+        assert_eq!(
+            hub_order(&db, ScopeType::Text, Some("1"), HubOrder::Priority).await,
+            ["m2", "m1", "m3"]
+        );
+        assert_eq!(
+            hub_order(&db, ScopeType::Text, Some("1"), HubOrder::Position).await,
+            ["m2", "m3", "m1"]
+        );
+        assert_eq!(
+            hub_order(&db, ScopeType::Folder, Some("root"), HubOrder::Position).await,
+            ["m5", "m4"]
+        );
+        assert_eq!(
+            hub_order(&db, ScopeType::Folder, Some("root"), HubOrder::Seen).await,
+            ["m5", "m4"]
+        );
+    }
+
+    const PARAGRAPHS: &str = "First paragraph here.\nSecond one follows.\nThird.";
+
+    fn paragraph(start: i64, end: i64, max_chars: usize) -> Option<(String, String, String)> {
+        extract_paragraph_from_positions(PARAGRAPHS, start, end, max_chars)
+    }
+
+    fn parts(before: &str, marked: &str, after: &str) -> Option<(String, String, String)> {
+        Some((before.to_string(), marked.to_string(), after.to_string()))
+    }
+
+    #[test]
+    fn test_extract_paragraph_at_start_and_end_of_text() {
+        assert_eq!(paragraph(0, 5, 500), parts("", "First", " paragraph here."));
+        assert_eq!(paragraph(42, 48, 500), parts("", "Third.", ""));
+        assert_eq!(paragraph(16, 21, 500), parts("First paragraph ", "here.", ""));
+    }
+
+    #[test]
+    fn test_extract_paragraph_across_paragraph_boundary() {
+        assert_eq!(
+            paragraph(16, 28, 500),
+            parts("First paragraph ", "here.\nSecond", " one follows.")
+        );
+    }
+
+    #[test]
+    fn test_extract_paragraph_caps_each_side() {
+        assert_eq!(paragraph(6, 15, 3), parts("st ", "paragraph", " he"));
+    }
+
+    #[test]
+    fn test_extract_paragraph_in_dom_space() {
+        // The link renders as its text, so DOM positions skip the URL
+        let content = "See [rivers](https://example.org/wiki/River_(water)) flow.\nNext.";
+        assert_eq!(
+            extract_paragraph_from_positions(content, 4, 10, 500),
+            parts("See ", "rivers", " flow.")
+        );
+    }
+
+    #[test]
+    fn test_extract_paragraph_rejects_positions_outside_content() {
+        assert_eq!(paragraph(40, 49, 500), None);
+        assert_eq!(paragraph(5, 5, 500), None);
+        assert_eq!(paragraph(-1, 5, 500), None);
+    }
 }
@@ -1,7 +1,8 @@
 use crate::db::Database;
 use crate::models::flashcard::Flashcard;
-use crate::services::cloze_parser::ClozeParser;
+use crate::services::cloze_parser::{ClozeDiagnostic, ClozeNumberDiff, ClozeParser};
 use crate::services::cloze_renderer::ClozeRenderer;
+use crate::services::duplicate_detector::{self, DuplicateCluster, DuplicateMatch, DEFAULT_DUPLICATE_THRESHOLD};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -29,33 +30,79 @@ pub struct ClozeNoteWithPositions {
     pub updated_at: chrono::DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditClozeNoteResult {
+    pub flashcards: Vec<Flashcard>,
+    pub cloze_numbers: ClozeNumberDiff,
+}
+
+/// Error from a command that creates or edits cards from cloze text
+///
+/// When the cloze text itself is the problem, `diagnostic` locates it for the editor.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClozeCommandError {
+    pub message: String,
+    pub diagnostic: Option<ClozeDiagnostic>,
+}
+
+impl From<String> for ClozeCommandError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            diagnostic: None,
+        }
+    }
+}
+
+impl From<ClozeDiagnostic> for ClozeCommandError {
+    fn from(diagnostic: ClozeDiagnostic) -> Self {
+        Self {
+            message: format!("Failed to parse cloze text: {}", diagnostic.message),
+            diagnostic: Some(diagnostic),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFlashcardsResult {
+    pub flashcards: Vec<Flashcard>,
+    pub duplicates: Vec<DuplicateMatch>,
+}
+
 #[tauri::command]
 pub async fn create_flashcard_from_cloze(
     text_id: i64,
     selected_text: String,
     cloze_text: String,
     db: State<'_, Arc<Mutex<Database>>>,
-) -> Result<Vec<Flashcard>, String> {
+) -> Result<CreateFlashcardsResult, ClozeCommandError> {
     let db = db.lock().await;
     let pool = db.pool();
     let user_id = 1;
     let now = Utc::now();
 
-    let parsed = ClozeParser::parse(&cloze_text)
-        .map_err(|e| format!("Failed to parse cloze text: {}", e))?;
+    let parsed = ClozeParser::parse_for_cards(&cloze_text)?;
 
     let cloze_numbers = ClozeParser::extract_cloze_numbers(&cloze_text)
         .map_err(|e| format!("Failed to extract cloze numbers: {}", e))?;
 
-    if cloze_numbers.is_empty() {
-        return Err("No cloze deletions found in text".to_string());
-    }
-
     let parsed_segments_json = serde_json::to_string(&parsed.segments)
         .map_err(|e| format!("Failed to serialize parsed segments: {}", e))?;
 
     let cloze_count = cloze_numbers.len() as i64;
 
+    let duplicates = duplicate_detector::find_duplicates_for_text(
+        pool,
+        &cloze_text,
+        None,
+        DEFAULT_DUPLICATE_THRESHOLD,
+    )
+    .await
+    .map_err(|e| format!("Failed to check for duplicates: {}", e))?;
+
     let cloze_note_result = sqlx::query!(
         r#"
         INSERT INTO cloze_notes (text_id, user_id, original_text, parsed_segments, cloze_count, created_at, updated_at)
@@ -165,7 +212,29 @@ pub async fn create_flashcard_from_cloze(
         next_display_index += 1;
     }
 
-    Ok(flashcards)
+    Ok(CreateFlashcardsResult {
+        flashcards,
+        duplicates,
+    })
+}
+
+/// Group near-duplicate notes across the whole library
+#[tauri::command]
+pub async fn get_duplicate_clusters(
+    threshold: Option<f64>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<DuplicateCluster>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let notes = duplicate_detector::load_note_fingerprints(pool, None)
+        .await
+        .map_err(|e| format!("Failed to load cloze notes: {}", e))?;
+
+    Ok(duplicate_detector::cluster(
+        &notes,
+        threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD),
+    ))
 }
 
 #[tauri::command]
@@ -237,6 +306,220 @@ pub async fn delete_flashcard(
     Ok(())
 }
 
+/// Edit the cloze text of an existing note and reconcile its flashcards
+///
+/// Cards whose cloze number survives the edit keep their FSRS state and review history,
+/// cards for removed cloze numbers are deleted, and new cloze numbers get fresh cards.
+#[tauri::command]
+pub async fn edit_cloze_note(
+    cloze_note_id: i64,
+    cloze_text: String,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<EditClozeNoteResult, ClozeCommandError> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let user_id = 1;
+    let now = Utc::now();
+
+    let parsed = ClozeParser::parse_for_cards(&cloze_text)?;
+
+    let cloze_numbers = ClozeParser::extract_cloze_numbers(&cloze_text)
+        .map_err(|e| format!("Failed to extract cloze numbers: {}", e))?;
+
+    let cloze_note = sqlx::query!(
+        r#"
+        SELECT id as "id!", text_id
+        FROM cloze_notes
+        WHERE id = ?
+        "#,
+        cloze_note_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch cloze note: {}", e))?
+    .ok_or_else(|| "Cloze note not found".to_string())?;
+
+    let existing_cards = sqlx::query!(
+        r#"
+        SELECT id as "id!", original_text, cloze_number as "cloze_number!"
+        FROM flashcards
+        WHERE cloze_note_id = ?
+        ORDER BY display_index ASC
+        "#,
+        cloze_note_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch flashcards for cloze note: {}", e))?;
+
+    if existing_cards.is_empty() {
+        return Err("Cloze note has no flashcards to edit".to_string().into());
+    }
+
+    let existing_numbers: Vec<u32> = existing_cards
+        .iter()
+        .map(|card| card.cloze_number as u32)
+        .collect();
+    let diff = ClozeParser::diff_cloze_numbers(&existing_numbers, &cloze_numbers);
+
+    // All cards of a note share the same source passage
+    let selected_text = existing_cards[0].original_text.clone();
+
+    let parsed_segments_json = serde_json::to_string(&parsed.segments)
+        .map_err(|e| format!("Failed to serialize parsed segments: {}", e))?;
+
+    let cloze_count = cloze_numbers.len() as i64;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    sqlx::query!(
+        r#"
+        UPDATE cloze_notes
+        SET original_text = ?,
+            parsed_segments = ?,
+            cloze_count = ?,
+            updated_at = ?
+        WHERE id = ?
+        "#,
+        cloze_text,
+        parsed_segments_json,
+        cloze_count,
+        now,
+        cloze_note_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update cloze note: {}", e))?;
+
+    // Kept cards only get the new text - scheduling columns are left untouched
+    sqlx::query!(
+        r#"
+        UPDATE flashcards
+        SET cloze_text = ?, updated_at = ?
+        WHERE cloze_note_id = ?
+        "#,
+        cloze_text,
+        now,
+        cloze_note_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update flashcards: {}", e))?;
+
+    for cloze_number in &diff.removed {
+        let cloze_number = *cloze_number as i64;
+        sqlx::query!(
+            r#"
+            DELETE FROM flashcards
+            WHERE cloze_note_id = ? AND cloze_number = ?
+            "#,
+            cloze_note_id,
+            cloze_number
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to delete flashcard for cloze {}: {}", cloze_number, e))?;
+    }
+
+    if !diff.added.is_empty() {
+        let max_display_result = sqlx::query!(
+            "SELECT COALESCE(MAX(display_index), 0) as max_idx FROM flashcards WHERE text_id = ?",
+            cloze_note.text_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to get max display_index: {}", e))?;
+
+        let display_indices = max_display_result.max_idx + 1..;
+
+        for (display_index, cloze_number) in display_indices.zip(&diff.added) {
+            let cloze_index = *cloze_number as i64;
+            sqlx::query!(
+                r#"
+                INSERT INTO flashcards (
+                    text_id, user_id, original_text, cloze_text, cloze_index,
+                    display_index, cloze_number, cloze_note_id,
+                    created_at, updated_at, due,
+                    stability, difficulty, elapsed_days, scheduled_days,
+                    reps, lapses, state, last_review
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                cloze_note.text_id,
+                user_id,
+                selected_text,
+                cloze_text,
+                cloze_index,
+                display_index,
+                cloze_index,
+                cloze_note_id,
+                now,
+                now,
+                now,
+                0.0,
+                0.0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                None::<chrono::DateTime<Utc>>
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to create flashcard for cloze {}: {}", cloze_number, e))?;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    let flashcards = sqlx::query_as!(
+        Flashcard,
+        r#"
+        SELECT
+            flashcards.id as "id!",
+            flashcards.text_id as "text_id!",
+            flashcards.user_id as "user_id!",
+            flashcards.original_text,
+            flashcards.cloze_text,
+            flashcards.cloze_index as "cloze_index!",
+            flashcards.display_index as "display_index!",
+            flashcards.cloze_number as "cloze_number!",
+            flashcards.created_at as "created_at: _",
+            flashcards.updated_at as "updated_at: _",
+            flashcards.cloze_note_id,
+            flashcards.due as "due: _",
+            flashcards.stability as "stability!",
+            flashcards.difficulty as "difficulty!",
+            flashcards.elapsed_days as "elapsed_days!",
+            flashcards.scheduled_days as "scheduled_days!",
+            flashcards.reps as "reps!",
+            flashcards.lapses as "lapses!",
+            flashcards.state as "state!",
+            flashcards.last_review as "last_review: _",
+            texts.title as text_title
+        FROM flashcards
+        INNER JOIN texts ON flashcards.text_id = texts.id
+        WHERE flashcards.cloze_note_id = ?
+        ORDER BY flashcards.cloze_number ASC
+        "#,
+        cloze_note_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch flashcards: {}", e))?;
+
+    Ok(EditClozeNoteResult {
+        flashcards,
+        cloze_numbers: diff,
+    })
+}
+
 #[tauri::command]
 pub async fn get_flashcard_preview(
     cloze_text: String,
@@ -251,6 +534,12 @@ pub async fn get_flashcard_preview(
     })
 }
 
+/// Lint cloze text as it is typed, without creating anything
+#[tauri::command]
+pub async fn validate_cloze_text(cloze_text: String) -> Result<Vec<ClozeDiagnostic>, String> {
+    Ok(ClozeParser::diagnose(&cloze_text))
+}
+
 /// Create a mark (cloze_note without flashcards) for later processing in Create Cards hub
 #[tauri::command]
 pub async fn create_mark(
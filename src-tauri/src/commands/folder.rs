@@ -9,12 +9,12 @@
 // - Get texts in a folder
 //
 
+use crate::commands::excluded_ranges::fetch_excluded_ranges;
 use crate::db::Database;
 use crate::models::folder::Folder;
 use crate::models::text::Text;
 use crate::models::read_range::ReadRange;
-use crate::services::parser;
-use crate::services::range_calculator::RangeCalculator;
+use crate::services::excluded_ranges;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -243,7 +243,9 @@ pub async fn get_texts_in_folder(
             SELECT
                 id as "id!", title, source, source_url, content, content_length as "content_length!",
                 ingested_at as "ingested_at: _", updated_at as "updated_at: _",
-                metadata, author, publication_date, publisher, access_date, doi, isbn, folder_id
+                metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+                wiki_page_id, wiki_revision_id, folder_id,
+                parent_text_id, parent_start_position, parent_end_position, status
             FROM texts
             WHERE folder_id = ?
             ORDER BY ingested_at DESC
@@ -260,7 +262,9 @@ pub async fn get_texts_in_folder(
             SELECT
                 id as "id!", title, source, source_url, content, content_length as "content_length!",
                 ingested_at as "ingested_at: _", updated_at as "updated_at: _",
-                metadata, author, publication_date, publisher, access_date, doi, isbn, folder_id
+                metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+                wiki_page_id, wiki_revision_id, folder_id,
+                parent_text_id, parent_start_position, parent_end_position, status
             FROM texts
             WHERE folder_id IS NULL
             ORDER BY ingested_at DESC
@@ -285,7 +289,7 @@ pub async fn calculate_folder_progress(
 
     let text_ids = sqlx::query!(
         r#"
-        SELECT id, content_length, content
+        SELECT id as "id!", content_length, content
         FROM texts
         WHERE folder_id IN (
             WITH RECURSIVE folder_tree AS (
@@ -314,7 +318,12 @@ pub async fn calculate_folder_progress(
 
     for text_record in text_ids {
         let total_chars = text_record.content_length;
-        let excluded_chars = parser::calculate_excluded_character_count(&text_record.content);
+        let excluded = fetch_excluded_ranges(
+            &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+            text_record.id,
+        )
+        .await?;
+        let excluded_chars = excluded_ranges::covered_length(&excluded);
         let countable_chars = total_chars - excluded_chars;
 
         if countable_chars <= 0 {
@@ -342,7 +351,7 @@ pub async fn calculate_folder_progress(
         .await
         .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
 
-        let read_chars = RangeCalculator::calculate_read_characters(ranges);
+        let read_chars = excluded_ranges::countable_read_length(&ranges, &excluded);
         let progress = (read_chars as f64 / countable_chars as f64) * 100.0;
 
         total_progress += progress;
@@ -0,0 +1,230 @@
+// File import commands
+//
+// Creates texts from local files. Markdown, plain text and HTML files
+// (services::file_import) and PDFs (services::pdf_import) become one text each; one
+// file failing doesn't stop the rest of the batch.
+// An EPUB becomes a folder named after the book with a text per chapter
+// (services::epub), imported all or nothing.
+
+use crate::commands::texts::insert_text;
+use crate::db::Database;
+use crate::models::folder::Folder;
+use crate::models::text::{CreateTextRequest, Text};
+use crate::services::epub::parse_epub;
+use crate::services::file_import::parse_document;
+use crate::services::pdf_import::parse_pdf;
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub texts: Vec<Text>,
+    pub failures: Vec<ImportFailure>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpubImportResult {
+    pub folder: Folder,
+    pub texts: Vec<Text>,
+}
+
+/// Read and parse a file into a text request, telling PDFs apart by their extension
+async fn read_file_request(path: &str, folder_id: Option<String>) -> Result<CreateTextRequest, String> {
+    let file_path = Path::new(path);
+    let is_pdf = file_path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+
+    let (source, document) = if is_pdf {
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        // Extraction is CPU-bound and pdf-extract panics on some malformed files,
+        // so it runs on the blocking pool where a panic comes back as a JoinError
+        let pdf_path = file_path.to_path_buf();
+        let document = tokio::task::spawn_blocking(move || parse_pdf(&pdf_path, &bytes))
+            .await
+            .map_err(|e| format!("Failed to parse PDF: {}", e))?
+            .map_err(|e| format!("Failed to parse PDF: {}", e))?;
+        ("pdf", document)
+    } else {
+        let raw = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let document = parse_document(file_path, &raw).map_err(|e| format!("Failed to parse file: {}", e))?;
+        ("file", document)
+    };
+
+    Ok(CreateTextRequest {
+        title: document.title,
+        source: source.to_string(),
+        source_url: Some(path.to_string()),
+        content: document.content,
+        metadata: None,
+        author: document.author,
+        publication_date: document.publication_date,
+        publisher: None,
+        access_date: None,
+        doi: document.doi,
+        isbn: None,
+        language: None,
+        wiki_page_id: None,
+        wiki_revision_id: None,
+        folder_id,
+    })
+}
+
+/// Import Markdown, plain text, HTML and PDF files as texts, detecting each file's format
+#[tauri::command]
+pub async fn import_files(
+    paths: Vec<String>,
+    folder_id: Option<String>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<ImportResult, String> {
+    // Read and parse before taking the database lock
+    let mut requests = Vec::with_capacity(paths.len());
+    for path in paths {
+        let request = read_file_request(&path, folder_id.clone()).await;
+        requests.push((path, request));
+    }
+
+    let db = db.lock().await;
+    let mut conn = db
+        .pool()
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    let mut result = ImportResult {
+        texts: Vec::new(),
+        failures: Vec::new(),
+    };
+    for (path, request) in requests {
+        match request {
+            Ok(request) => match insert_text(&mut conn, request).await {
+                Ok(text) => result.texts.push(text),
+                Err(error) => result.failures.push(ImportFailure { path, error }),
+            },
+            Err(error) => result.failures.push(ImportFailure { path, error }),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Import a PDF as one text, with its DOI when the first page has one
+#[tauri::command]
+pub async fn import_pdf(
+    path: String,
+    folder_id: Option<String>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Text, String> {
+    if !path.to_lowercase().ends_with(".pdf") {
+        return Err(format!("Not a PDF file: {}", path));
+    }
+    let request = read_file_request(&path, folder_id).await?;
+
+    let db = db.lock().await;
+    let mut conn = db
+        .pool()
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    insert_text(&mut conn, request).await
+}
+
+/// Import an EPUB as a folder named after the book, inside `folder_id`, with one text
+/// per chapter in reading order. Every chapter gets the book's metadata.
+#[tauri::command]
+pub async fn import_epub(
+    path: String,
+    folder_id: Option<String>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<EpubImportResult, String> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let book = parse_epub(&bytes).map_err(|e| format!("Failed to parse EPUB: {}", e))?;
+
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let book_folder_id = Uuid::new_v4().to_string();
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    sqlx::query!(
+        r#"
+        INSERT INTO folders (id, name, parent_id, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        book_folder_id,
+        book.metadata.title,
+        folder_id,
+        now,
+        now
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    let metadata = &book.metadata;
+    let mut texts = Vec::with_capacity(book.chapters.len());
+    for chapter in book.chapters {
+        let request = CreateTextRequest {
+            title: chapter.title,
+            source: "epub".to_string(),
+            source_url: Some(path.clone()),
+            content: chapter.content,
+            metadata: None,
+            author: metadata.author.clone(),
+            publication_date: metadata.publication_date.clone(),
+            publisher: metadata.publisher.clone(),
+            access_date: None,
+            doi: None,
+            isbn: metadata.isbn.clone(),
+            language: metadata.language.clone(),
+            wiki_page_id: None,
+            wiki_revision_id: None,
+            folder_id: Some(book_folder_id.clone()),
+        };
+        texts.push(insert_text(&mut tx, request).await?);
+    }
+
+    let folder = sqlx::query_as!(
+        Folder,
+        r#"
+        SELECT id, name, parent_id, created_at, updated_at
+        FROM folders
+        WHERE id = ?
+        "#,
+        book_folder_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch created folder: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(EpubImportResult { folder, texts })
+}
@@ -1,7 +1,8 @@
+use crate::commands::excluded_ranges::fetch_excluded_ranges;
 use crate::db::Database;
 use crate::models::read_range::ReadRange;
+use crate::services::excluded_ranges;
 use crate::services::parser;
-use crate::services::range_calculator::RangeCalculator;
 use serde::Serialize;
 use std::sync::Arc;
 use tauri::State;
@@ -122,7 +123,12 @@ pub async fn get_text_statistics(
     let paragraph_count = calculate_paragraph_count(&text.content);
 
     let total_chars = text.content_length;
-    let excluded_chars = parser::calculate_excluded_character_count(&text.content);
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+    let excluded_chars = excluded_ranges::covered_length(&excluded);
     let header_chars = parser::calculate_header_character_count(&text.content);
     let countable_chars = total_chars - excluded_chars - header_chars;
 
@@ -147,7 +153,7 @@ pub async fn get_text_statistics(
     .await
     .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
 
-    let read_chars = RangeCalculator::calculate_read_characters(ranges.clone());
+    let read_chars = excluded_ranges::countable_read_length(&ranges, &excluded);
     let read_percentage = if countable_chars > 0 {
         (read_chars as f64 / countable_chars as f64) * 100.0
     } else {
@@ -265,7 +271,7 @@ pub async fn get_folder_statistics(
             INNER JOIN folder_tree ft ON f.parent_id = ft.id
         )
         SELECT
-            t.id,
+            t.id as "id!",
             t.content_length,
             t.content
         FROM texts t
@@ -285,7 +291,12 @@ pub async fn get_folder_statistics(
 
     for text in &texts {
         let total_chars = text.content_length;
-        let excluded_chars = parser::calculate_excluded_character_count(&text.content);
+        let excluded = fetch_excluded_ranges(
+            &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+            text.id,
+        )
+        .await?;
+        let excluded_chars = excluded_ranges::covered_length(&excluded);
         let header_chars = parser::calculate_header_character_count(&text.content);
         let countable_chars = total_chars - excluded_chars - header_chars;
 
@@ -314,7 +325,7 @@ pub async fn get_folder_statistics(
         .await
         .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
 
-        let read_chars = RangeCalculator::calculate_read_characters(ranges);
+        let read_chars = excluded_ranges::countable_read_length(&ranges, &excluded);
         let progress = (read_chars as f64 / countable_chars as f64) * 100.0;
 
         total_progress += progress;
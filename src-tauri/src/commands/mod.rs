@@ -1,5 +1,6 @@
 pub mod texts;
 pub mod reading;
+pub mod excluded_ranges;
 pub mod flashcards;
 pub mod flashcard_hub;
 pub mod review;
@@ -9,3 +10,8 @@ pub mod settings;
 pub mod statistics;
 pub mod library_statistics;
 pub mod batch_operations;
+pub mod text_revisions;
+pub mod extracts;
+pub mod reading_queue;
+pub mod import;
+pub mod web_article;
@@ -1,10 +1,17 @@
+use crate::commands::excluded_ranges::fetch_excluded_ranges;
+use crate::commands::flashcard_hub::{process_content_to_dom_space, to_rendered_position};
 use crate::db::Database;
 use crate::models::paragraph::Paragraph;
 use crate::models::read_range::ReadRange;
+use crate::models::sentence::Sentence;
+use crate::services::excluded_ranges;
+use crate::services::outline::{self, MarkPosition, OutlineSection};
 use crate::services::parser;
 use crate::services::range_calculator::RangeCalculator;
+use crate::services::sentence_segmenter;
 use chrono::Utc;
 use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -115,12 +122,21 @@ pub async fn mark_range_as_read(
         .map_err(|e| format!("Failed to insert read range: {}", e))?;
     }
 
+    // The first read range moves a text out of the inbox
+    sqlx::query!(
+        "UPDATE texts SET status = 'reading' WHERE id = ? AND status = 'inbox'",
+        text_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update text status: {}", e))?;
+
     Ok(())
 }
 
 /// Calculate which portions of a new range are not already covered by existing ranges
 /// Returns a vector of (start, end) tuples representing unread portions
-fn calculate_unread_portions(
+pub(crate) fn calculate_unread_portions(
     new_start: i64,
     new_end: i64,
     existing_ranges: &[ReadRange],
@@ -224,7 +240,12 @@ pub async fn calculate_text_progress(
     .map_err(|e| format!("Failed to fetch text: {}", e))?;
 
     let total_chars = text_result.content_length;
-    let excluded_chars = parser::calculate_excluded_character_count(&text_result.content);
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+    let excluded_chars = excluded_ranges::covered_length(&excluded);
     let header_chars = parser::calculate_header_character_count(&text_result.content);
     let countable_chars = total_chars - excluded_chars - header_chars;
 
@@ -253,9 +274,28 @@ pub async fn calculate_text_progress(
     .await
     .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
 
-    let read_chars = RangeCalculator::calculate_read_characters(ranges);
+    let read_chars = excluded_ranges::countable_read_length(&ranges, &excluded);
     let progress = (read_chars as f64 / countable_chars as f64) * 100.0;
 
+    // Full progress finishes a text; losing it (e.g. after unmarking) resumes reading.
+    // Archived texts keep their status
+    let is_finished = progress >= 100.0;
+    sqlx::query!(
+        r#"
+        UPDATE texts
+        SET status = CASE WHEN ? THEN 'finished' ELSE 'reading' END
+        WHERE id = ?
+          AND status != 'archived'
+          AND (CASE WHEN ? THEN status IN ('inbox', 'reading') ELSE status = 'finished' END)
+        "#,
+        is_finished,
+        text_id,
+        is_finished
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update text status: {}", e))?;
+
     Ok(progress)
 }
 
@@ -344,9 +384,16 @@ pub async fn get_next_unread_paragraph(
     .await
     .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
 
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+
     for paragraph in paragraphs {
         let is_read = RangeCalculator::is_position_read(paragraph.start_position, &ranges);
-        if !is_read {
+        let is_excluded = excluded_ranges::is_excluded(paragraph.start_position, &excluded);
+        if !is_read && !is_excluded {
             return Ok(Some(paragraph));
         }
     }
@@ -389,6 +436,136 @@ pub async fn get_previous_paragraph(
     Ok(paragraph)
 }
 
+/// A text's sentences in order
+/// Texts stored before sentences were tracked are segmented on first use.
+async fn fetch_sentences(pool: &Pool<Sqlite>, text_id: i64) -> Result<Vec<Sentence>, String> {
+    let select = || {
+        sqlx::query_as!(
+            Sentence,
+            r#"
+            SELECT
+                id as "id!",
+                text_id as "text_id!",
+                sentence_index as "sentence_index!",
+                start_position as "start_position!",
+                end_position as "end_position!",
+                created_at as "created_at: _"
+            FROM sentences
+            WHERE text_id = ?
+            ORDER BY sentence_index ASC
+            "#,
+            text_id
+        )
+        .fetch_all(pool)
+    };
+
+    let sentences = select()
+        .await
+        .map_err(|e| format!("Failed to fetch sentences: {}", e))?;
+    if !sentences.is_empty() {
+        return Ok(sentences);
+    }
+
+    let content = sqlx::query_scalar!("SELECT content FROM texts WHERE id = ?", text_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch text: {}", e))?
+        .ok_or_else(|| "Text not found".to_string())?;
+
+    // Sentences are read against the rendered text, like read and excluded ranges
+    let sentences = sentence_segmenter::segment_sentences(&process_content_to_dom_space(&content));
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+    sentence_segmenter::store_sentences(&mut conn, text_id, &sentences)
+        .await
+        .map_err(|e| format!("Failed to store sentences: {}", e))?;
+    drop(conn);
+
+    select()
+        .await
+        .map_err(|e| format!("Failed to fetch sentences: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_sentences(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<Sentence>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    fetch_sentences(pool, text_id).await
+}
+
+/// First sentence starting at or after `current_pos` that isn't read or excluded
+#[tauri::command]
+pub async fn get_next_unread_sentence(
+    text_id: i64,
+    current_pos: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Option<Sentence>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let user_id = 1;
+
+    let sentences = fetch_sentences(pool, text_id).await?;
+
+    let ranges = sqlx::query_as!(
+        ReadRange,
+        r#"
+        SELECT
+            id as "id!",
+            text_id as "text_id!",
+            user_id as "user_id!",
+            start_position as "start_position!",
+            end_position as "end_position!",
+            marked_at as "marked_at: _",
+            is_auto_completed as "is_auto_completed: bool"
+        FROM read_ranges
+        WHERE text_id = ? AND user_id = ?
+        "#,
+        text_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
+
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+
+    Ok(sentences
+        .into_iter()
+        .filter(|s| s.start_position >= current_pos)
+        .find(|s| {
+            !RangeCalculator::is_position_read(s.start_position, &ranges)
+                && !excluded_ranges::is_excluded(s.start_position, &excluded)
+        }))
+}
+
+/// Last sentence starting before `current_pos`
+#[tauri::command]
+pub async fn get_previous_sentence(
+    text_id: i64,
+    current_pos: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Option<Sentence>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let sentences = fetch_sentences(pool, text_id).await?;
+
+    Ok(sentences
+        .into_iter()
+        .rev()
+        .find(|s| s.start_position < current_pos))
+}
+
 #[tauri::command]
 pub async fn get_most_recently_read_text(
     db: State<'_, Arc<Mutex<Database>>>,
@@ -551,9 +728,102 @@ pub async fn get_countable_length(
     .map_err(|e| format!("Failed to fetch text: {}", e))?;
 
     let total_chars = text_result.content_length;
-    let excluded_chars = parser::calculate_excluded_character_count(&text_result.content);
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+    let excluded_chars = excluded_ranges::covered_length(&excluded);
     let header_chars = parser::calculate_header_character_count(&text_result.content);
     let countable_chars = total_chars - excluded_chars - header_chars;
 
     Ok(countable_chars)
 }
+
+/// Table of contents for a text, with reading progress and mark/card counts per section
+/// Positions are rendered (DOM space), like read ranges and marks
+#[tauri::command]
+pub async fn get_text_outline(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<OutlineSection>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let user_id = 1;
+
+    let content = sqlx::query_scalar!("SELECT content FROM texts WHERE id = ?", text_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch text: {}", e))?
+        .ok_or_else(|| "Text not found".to_string())?;
+
+    let ranges = sqlx::query_as!(
+        ReadRange,
+        r#"
+        SELECT
+            id as "id!",
+            text_id as "text_id!",
+            user_id as "user_id!",
+            start_position as "start_position!",
+            end_position as "end_position!",
+            marked_at as "marked_at: _",
+            is_auto_completed as "is_auto_completed: bool"
+        FROM read_ranges
+        WHERE text_id = ? AND user_id = ?
+        "#,
+        text_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
+
+    let marks: Vec<MarkPosition> = sqlx::query!(
+        r#"
+        SELECT
+            cn.start_position as "start_position!",
+            (SELECT COUNT(*) FROM flashcards f WHERE f.cloze_note_id = cn.id) as "card_count!: i64"
+        FROM cloze_notes cn
+        WHERE cn.text_id = ? AND cn.start_position IS NOT NULL
+        "#,
+        text_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch marks: {}", e))?
+    .into_iter()
+    .map(|row| MarkPosition {
+        start_position: row.start_position,
+        card_count: row.card_count,
+    })
+    .collect();
+
+    let to_rendered = |raw_position: i64| to_rendered_position(&content, raw_position);
+
+    Ok(outline::build_outline(&content, to_rendered, &ranges, &marks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sentences_are_segmented_in_dom_space() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+        let content = "[Rivers](https://example.org) flow. Lakes rest.";
+
+        let text_id = sqlx::query("INSERT INTO texts (title, content, content_length) VALUES ('Linked', ?, ?)")
+            .bind(content)
+            .bind(content.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+
+        // "Rivers flow. Lakes rest." once the link markup is gone
+        let sentences = fetch_sentences(pool, text_id).await.unwrap();
+        let positions: Vec<(i64, i64)> = sentences.iter().map(|s| (s.start_position, s.end_position)).collect();
+        assert_eq!(positions, vec![(0, 12), (13, 24)]);
+    }
+}
@@ -0,0 +1,303 @@
+// Reading queue commands
+//
+// Library-wide "what should I read next": texts are queued with a priority, read a
+// bit at a time, and postponed with a growing interval (see services::reading_queue).
+
+use crate::db::Database;
+use crate::services::reading_queue::{
+    self, DEFAULT_READING_PRIORITY, INITIAL_INTERVAL_DAYS, MAX_READING_PRIORITY, MIN_READING_PRIORITY,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingQueueItem {
+    pub text_id: i64,
+    pub title: String,
+    pub content_length: i64,
+    pub read_characters: i64,
+    pub priority: i64,
+    pub interval_days: f64,
+    pub next_read_at: DateTime<Utc>,
+    pub postpone_count: i64,
+    pub last_read_at: Option<DateTime<Utc>>,
+    pub added_at: DateTime<Utc>,
+    pub is_due: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextReadingItem {
+    /// Top due item, None when nothing is due or today's budget is used up
+    pub item: Option<ReadingQueueItem>,
+    /// Characters read today across all texts
+    pub read_today: i64,
+    pub daily_budget: Option<i64>,
+    pub budget_remaining: Option<i64>,
+}
+
+async fn fetch_queue_item(
+    pool: &Pool<Sqlite>,
+    text_id: i64,
+    now: DateTime<Utc>,
+) -> Result<ReadingQueueItem, String> {
+    sqlx::query_as!(
+        ReadingQueueItem,
+        r#"
+        SELECT
+            rq.text_id as "text_id!",
+            t.title,
+            t.content_length as "content_length!",
+            COALESCE((SELECT SUM(end_position - start_position) FROM read_ranges WHERE text_id = rq.text_id), 0) as "read_characters!: i64",
+            rq.priority,
+            rq.interval_days,
+            rq.next_read_at as "next_read_at: _",
+            rq.postpone_count,
+            rq.last_read_at as "last_read_at: _",
+            rq.added_at as "added_at: _",
+            rq.next_read_at <= ? as "is_due!: bool"
+        FROM reading_queue rq
+        INNER JOIN texts t ON t.id = rq.text_id
+        WHERE rq.text_id = ?
+        "#,
+        now,
+        text_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch reading queue item: {}", e))?
+    .ok_or_else(|| "Text is not in the reading queue".to_string())
+}
+
+/// Queue a text for reading, due now
+/// If it's already queued, only its priority is updated
+#[tauri::command]
+pub async fn add_to_reading_queue(
+    text_id: i64,
+    priority: Option<i64>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<ReadingQueueItem, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+    let priority = priority.map(|p| p.clamp(MIN_READING_PRIORITY, MAX_READING_PRIORITY));
+    let initial_priority = priority.unwrap_or(DEFAULT_READING_PRIORITY);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO reading_queue (text_id, priority, interval_days, next_read_at, added_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(text_id) DO UPDATE SET
+            priority = COALESCE(?, priority)
+        "#,
+        text_id,
+        initial_priority,
+        INITIAL_INTERVAL_DAYS,
+        now,
+        now,
+        priority
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to add text to reading queue: {}", e))?;
+
+    fetch_queue_item(pool, text_id, now).await
+}
+
+#[tauri::command]
+pub async fn remove_from_reading_queue(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<(), String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    sqlx::query!("DELETE FROM reading_queue WHERE text_id = ?", text_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to remove text from reading queue: {}", e))?;
+
+    Ok(())
+}
+
+/// Set a queued text's priority, clamped to the 0-10 range
+#[tauri::command]
+pub async fn set_reading_priority(
+    text_id: i64,
+    priority: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<i64, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let priority = priority.clamp(MIN_READING_PRIORITY, MAX_READING_PRIORITY);
+
+    let result = sqlx::query!(
+        "UPDATE reading_queue SET priority = ? WHERE text_id = ?",
+        priority,
+        text_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to set reading priority: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err("Text is not in the reading queue".to_string());
+    }
+
+    Ok(priority)
+}
+
+/// Done reading a text for now: grow its interval and schedule it out of the way
+#[tauri::command]
+pub async fn postpone_reading_item(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<ReadingQueueItem, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+
+    let current = sqlx::query!(
+        "SELECT priority, interval_days FROM reading_queue WHERE text_id = ?",
+        text_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch reading queue item: {}", e))?
+    .ok_or_else(|| "Text is not in the reading queue".to_string())?;
+
+    let interval_days = reading_queue::next_interval(current.interval_days, current.priority);
+    let next_read_at = reading_queue::next_read_at(now, interval_days);
+
+    sqlx::query!(
+        r#"
+        UPDATE reading_queue
+        SET interval_days = ?,
+            next_read_at = ?,
+            postpone_count = postpone_count + 1,
+            last_read_at = ?
+        WHERE text_id = ?
+        "#,
+        interval_days,
+        next_read_at,
+        now,
+        text_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to postpone reading item: {}", e))?;
+
+    fetch_queue_item(pool, text_id, now).await
+}
+
+/// The whole queue: due items by priority, then upcoming items by date
+#[tauri::command]
+pub async fn get_reading_queue(
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<ReadingQueueItem>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+
+    sqlx::query_as!(
+        ReadingQueueItem,
+        r#"
+        SELECT
+            rq.text_id as "text_id!",
+            t.title,
+            t.content_length as "content_length!",
+            COALESCE((SELECT SUM(end_position - start_position) FROM read_ranges WHERE text_id = rq.text_id), 0) as "read_characters!: i64",
+            rq.priority,
+            rq.interval_days,
+            rq.next_read_at as "next_read_at: _",
+            rq.postpone_count,
+            rq.last_read_at as "last_read_at: _",
+            rq.added_at as "added_at: _",
+            rq.next_read_at <= ? as "is_due!: bool"
+        FROM reading_queue rq
+        INNER JOIN texts t ON t.id = rq.text_id
+        ORDER BY rq.next_read_at <= ? DESC,
+                 CASE WHEN rq.next_read_at <= ? THEN -rq.priority ELSE 0 END ASC,
+                 rq.next_read_at ASC
+        "#,
+        now,
+        now,
+        now
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch reading queue: {}", e))
+}
+
+/// The highest-priority due text, given a daily reading budget in characters
+///
+/// Finished and archived texts are skipped. Characters read today count every read
+/// range marked since midnight (UTC).
+/// Once the budget is used up no item is returned, even if texts are still due.
+#[tauri::command]
+pub async fn get_next_reading_item(
+    daily_budget: Option<i64>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<NextReadingItem, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+    let user_id = 1;
+    let start_of_day = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    let read_today = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(end_position - start_position), 0) as "total!: i64"
+        FROM read_ranges
+        WHERE user_id = ? AND marked_at >= ?
+        "#,
+        user_id,
+        start_of_day
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch today's reading: {}", e))?;
+
+    let budget_remaining = reading_queue::budget_remaining(daily_budget, read_today);
+    if budget_remaining == Some(0) {
+        return Ok(NextReadingItem {
+            item: None,
+            read_today,
+            daily_budget,
+            budget_remaining,
+        });
+    }
+
+    let next_text_id = sqlx::query_scalar!(
+        r#"
+        SELECT rq.text_id as "text_id!"
+        FROM reading_queue rq
+        INNER JOIN texts t ON t.id = rq.text_id
+        WHERE rq.next_read_at <= ?
+          AND t.status NOT IN ('finished', 'archived')
+        ORDER BY rq.priority DESC, rq.next_read_at ASC
+        LIMIT 1
+        "#,
+        now
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch next reading item: {}", e))?;
+
+    let item = match next_text_id {
+        Some(text_id) => Some(fetch_queue_item(pool, text_id, now).await?),
+        None => None,
+    };
+
+    Ok(NextReadingItem {
+        item,
+        read_today,
+        daily_budget,
+        budget_remaining,
+    })
+}
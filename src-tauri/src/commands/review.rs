@@ -1,3 +1,4 @@
+use crate::commands::flashcard_hub::extract_paragraph_from_positions;
 use crate::db::Database;
 use crate::models::flashcard::Flashcard;
 use crate::models::study_filter::StudyFilter;
@@ -5,6 +6,7 @@ use crate::models::study_limits::{DailyProgress, LimitStatus, StudyLimits};
 use crate::services::fsrs::{FSRSScheduler, SchedulingInfo};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -17,6 +19,41 @@ pub struct GradeResult {
     pub next_due: DateTime<Utc>,
 }
 
+/// Passage a card was created from, shown on the back of the card
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceContext {
+    pub cloze_note_id: i64,
+    pub before: String,
+    pub marked: String,
+    pub after: String,
+    pub start_position: i64,
+    pub end_position: i64,
+}
+
+/// Due card with the surrounding paragraph from its source text
+/// Flattened so the payload stays a superset of `Flashcard`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewCard {
+    #[serde(flatten)]
+    pub flashcard: Flashcard,
+    pub source_context: Option<SourceContext>,
+}
+
+/// Where a card came from, for opening the reader at that spot
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardSourceLocation {
+    pub flashcard_id: i64,
+    pub cloze_note_id: Option<i64>,
+    pub text_id: i64,
+    pub text_title: String,
+    /// UTF-16 range in DOM space, None for cards without stored positions
+    pub start_position: Option<i64>,
+    pub end_position: Option<i64>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReviewStats {
@@ -53,12 +90,96 @@ pub struct CardState {
     pub due: DateTime<Utc>,
 }
 
+/// Attach the source paragraph to each card that has a positioned cloze note
+///
+/// Notes and texts are each fetched in one query for the whole batch, and every
+/// text's content only once however many of its cards are due.
+async fn attach_source_context(
+    pool: &sqlx::SqlitePool,
+    flashcards: Vec<Flashcard>,
+) -> Result<Vec<ReviewCard>, String> {
+    const SOURCE_CONTEXT_CHARS: usize = 500;
+
+    let mut note_ids: Vec<i64> = flashcards.iter().filter_map(|f| f.cloze_note_id).collect();
+    note_ids.sort_unstable();
+    note_ids.dedup();
+
+    let mut positions: HashMap<i64, (i64, i64)> = HashMap::new();
+    if !note_ids.is_empty() {
+        let query_str = format!(
+            "SELECT id, start_position, end_position FROM cloze_notes \
+             WHERE id IN ({}) AND start_position IS NOT NULL AND end_position IS NOT NULL",
+            vec!["?"; note_ids.len()].join(",")
+        );
+        let mut query = sqlx::query_as::<_, (i64, i64, i64)>(&query_str);
+        for id in &note_ids {
+            query = query.bind(id);
+        }
+        let rows = query
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch cloze notes: {}", e))?;
+        positions.extend(rows.into_iter().map(|(id, start, end)| (id, (start, end))));
+    }
+
+    let mut text_ids: Vec<i64> = flashcards
+        .iter()
+        .filter(|f| f.cloze_note_id.is_some_and(|id| positions.contains_key(&id)))
+        .map(|f| f.text_id)
+        .collect();
+    text_ids.sort_unstable();
+    text_ids.dedup();
+
+    let mut contents: HashMap<i64, String> = HashMap::new();
+    if !text_ids.is_empty() {
+        let query_str = format!(
+            "SELECT id, content FROM texts WHERE id IN ({})",
+            vec!["?"; text_ids.len()].join(",")
+        );
+        let mut query = sqlx::query_as::<_, (i64, String)>(&query_str);
+        for id in &text_ids {
+            query = query.bind(id);
+        }
+        let rows = query
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch text content: {}", e))?;
+        contents.extend(rows);
+    }
+
+    Ok(flashcards
+        .into_iter()
+        .map(|flashcard| {
+            let source_context = flashcard.cloze_note_id.and_then(|cloze_note_id| {
+                let &(start, end) = positions.get(&cloze_note_id)?;
+                let content = contents.get(&flashcard.text_id)?;
+
+                extract_paragraph_from_positions(content, start, end, SOURCE_CONTEXT_CHARS).map(
+                    |(before, marked, after)| SourceContext {
+                        cloze_note_id,
+                        before,
+                        marked,
+                        after,
+                        start_position: start,
+                        end_position: end,
+                    },
+                )
+            });
+
+            ReviewCard {
+                flashcard,
+                source_context,
+            }
+        })
+        .collect())
+}
+
 #[tauri::command]
 pub async fn get_due_cards(
     limit: Option<i64>,
     order: Option<String>,
     db: State<'_, Arc<Mutex<Database>>>,
-) -> Result<Vec<Flashcard>, String> {
+) -> Result<Vec<ReviewCard>, String> {
     let db = db.lock().await;
     let pool = db.pool();
     let now = Utc::now();
@@ -96,6 +217,7 @@ pub async fn get_due_cards(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE datetime(flashcards.due) <= datetime(?)
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY flashcards.created_at ASC
                 LIMIT ?
                 "#,
@@ -137,6 +259,7 @@ pub async fn get_due_cards(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE datetime(flashcards.due) <= datetime(?)
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY RANDOM()
                 LIMIT ?
                 "#,
@@ -150,7 +273,7 @@ pub async fn get_due_cards(
         }
     };
 
-    Ok(flashcards)
+    attach_source_context(pool, flashcards).await
 }
 
 #[tauri::command]
@@ -380,30 +503,7 @@ pub async fn get_review_stats(
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<ReviewStats, String> {
     let db = db.lock().await;
-    let pool = db.pool();
-    let now = Utc::now();
-
-    let stats = sqlx::query!(
-        r#"
-        SELECT
-            COUNT(CASE WHEN datetime(due) <= datetime(?) THEN 1 END) as "due_count!",
-            COUNT(CASE WHEN state = 0 THEN 1 END) as "new_count!",
-            COUNT(CASE WHEN state IN (1, 3) THEN 1 END) as "learning_count!",
-            COUNT(CASE WHEN state = 2 THEN 1 END) as "review_count!"
-        FROM flashcards
-        "#,
-        now
-    )
-    .fetch_one(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch review stats: {}", e))?;
-
-    Ok(ReviewStats {
-        due_count: stats.due_count,
-        new_count: stats.new_count,
-        learning_count: stats.learning_count,
-        review_count: stats.review_count,
-    })
+    review_stats(db.pool(), StudyFilter::Global, Utc::now()).await
 }
 
 #[tauri::command]
@@ -449,7 +549,7 @@ pub async fn get_due_cards_filtered(
     limit: Option<i64>,
     order: Option<String>,
     db: State<'_, Arc<Mutex<Database>>>,
-) -> Result<Vec<Flashcard>, String> {
+) -> Result<Vec<ReviewCard>, String> {
     let db = db.lock().await;
     let pool = db.pool();
     let now = Utc::now();
@@ -581,6 +681,7 @@ pub async fn get_due_cards_filtered(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE datetime(flashcards.due) <= datetime(?) AND flashcards.state = 0
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
                 "#,
@@ -622,6 +723,7 @@ pub async fn get_due_cards_filtered(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE datetime(flashcards.due) <= datetime(?) AND flashcards.state != 0
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
                 "#,
@@ -674,6 +776,7 @@ pub async fn get_due_cards_filtered(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE flashcards.text_id = ? AND datetime(flashcards.due) <= datetime(?) AND flashcards.state = 0
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
                 "#,
@@ -716,6 +819,7 @@ pub async fn get_due_cards_filtered(
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE flashcards.text_id = ? AND datetime(flashcards.due) <= datetime(?) AND flashcards.state != 0
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
                 "#,
@@ -776,6 +880,7 @@ pub async fn get_due_cards_filtered(
                 WHERE texts.folder_id IN (SELECT id FROM folder_tree)
                 AND datetime(flashcards.due) <= datetime(?)
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 AND flashcards.state = 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
@@ -826,6 +931,7 @@ pub async fn get_due_cards_filtered(
                 WHERE texts.folder_id IN (SELECT id FROM folder_tree)
                 AND datetime(flashcards.due) <= datetime(?)
                 AND (flashcards.buried_until IS NULL OR datetime(flashcards.buried_until) < datetime(?))
+                AND texts.review_suspended = 0
                 AND flashcards.state != 0
                 ORDER BY datetime(flashcards.due) ASC
                 LIMIT ?
@@ -851,7 +957,7 @@ pub async fn get_due_cards_filtered(
         }
     };
 
-    Ok(flashcards)
+    attach_source_context(pool, flashcards).await
 }
 
 #[tauri::command]
@@ -968,19 +1074,27 @@ pub async fn get_review_stats_filtered(
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<ReviewStats, String> {
     let db = db.lock().await;
-    let pool = db.pool();
-    let now = Utc::now();
+    review_stats(db.pool(), filter, Utc::now()).await
+}
 
+/// Card counts for a study scope, leaving out texts suspended from review as the queue does
+async fn review_stats(
+    pool: &sqlx::SqlitePool,
+    filter: StudyFilter,
+    now: DateTime<Utc>,
+) -> Result<ReviewStats, String> {
     let (due_count, new_count, learning_count, review_count) = match filter {
         StudyFilter::Global => {
             let row = sqlx::query!(
                 r#"
                 SELECT
-                    COUNT(CASE WHEN datetime(due) <= datetime(?) THEN 1 END) as "due_count!",
-                    COUNT(CASE WHEN state = 0 THEN 1 END) as "new_count!",
-                    COUNT(CASE WHEN state IN (1, 3) THEN 1 END) as "learning_count!",
-                    COUNT(CASE WHEN state = 2 THEN 1 END) as "review_count!"
+                    COUNT(CASE WHEN datetime(flashcards.due) <= datetime(?) THEN 1 END) as "due_count!",
+                    COUNT(CASE WHEN flashcards.state = 0 THEN 1 END) as "new_count!",
+                    COUNT(CASE WHEN flashcards.state IN (1, 3) THEN 1 END) as "learning_count!",
+                    COUNT(CASE WHEN flashcards.state = 2 THEN 1 END) as "review_count!"
                 FROM flashcards
+                INNER JOIN texts ON flashcards.text_id = texts.id
+                WHERE texts.review_suspended = 0
                 "#,
                 now
             )
@@ -993,12 +1107,13 @@ pub async fn get_review_stats_filtered(
             let row = sqlx::query!(
                 r#"
                 SELECT
-                    COUNT(CASE WHEN datetime(due) <= datetime(?) THEN 1 END) as "due_count!",
-                    COUNT(CASE WHEN state = 0 THEN 1 END) as "new_count!",
-                    COUNT(CASE WHEN state IN (1, 3) THEN 1 END) as "learning_count!",
-                    COUNT(CASE WHEN state = 2 THEN 1 END) as "review_count!"
+                    COUNT(CASE WHEN datetime(flashcards.due) <= datetime(?) THEN 1 END) as "due_count!",
+                    COUNT(CASE WHEN flashcards.state = 0 THEN 1 END) as "new_count!",
+                    COUNT(CASE WHEN flashcards.state IN (1, 3) THEN 1 END) as "learning_count!",
+                    COUNT(CASE WHEN flashcards.state = 2 THEN 1 END) as "review_count!"
                 FROM flashcards
-                WHERE text_id = ?
+                INNER JOIN texts ON flashcards.text_id = texts.id
+                WHERE flashcards.text_id = ? AND texts.review_suspended = 0
                 "#,
                 now,
                 text_id
@@ -1025,6 +1140,7 @@ pub async fn get_review_stats_filtered(
                 FROM flashcards
                 INNER JOIN texts ON flashcards.text_id = texts.id
                 WHERE texts.folder_id IN (SELECT id FROM folder_tree)
+                AND texts.review_suspended = 0
                 "#,
                 folder_id,
                 now
@@ -1302,3 +1418,197 @@ pub async fn bury_card(
 
     Ok(())
 }
+
+/// Resolve a card to its source text and UTF-16 range so the reader can open there
+#[tauri::command]
+pub async fn get_card_source_location(
+    flashcard_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<CardSourceLocation, String> {
+    let db = db.lock().await;
+
+    card_source_location(db.pool(), flashcard_id).await
+}
+
+async fn card_source_location(pool: &sqlx::SqlitePool, flashcard_id: i64) -> Result<CardSourceLocation, String> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            f.id as "flashcard_id!",
+            f.cloze_note_id,
+            f.text_id,
+            t.title as text_title,
+            cn.start_position,
+            cn.end_position
+        FROM flashcards f
+        INNER JOIN texts t ON f.text_id = t.id
+        LEFT JOIN cloze_notes cn ON f.cloze_note_id = cn.id
+        WHERE f.id = ?
+        "#,
+        flashcard_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch card source: {}", e))?
+    .ok_or_else(|| format!("Flashcard {} not found", flashcard_id))?;
+
+    Ok(CardSourceLocation {
+        flashcard_id: row.flashcard_id,
+        cloze_note_id: row.cloze_note_id,
+        text_id: row.text_id,
+        text_title: row.text_title,
+        start_position: row.start_position,
+        end_position: row.end_position,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_card(pool: &sqlx::SqlitePool, text_id: i64, note: Option<(i64, i64)>) -> i64 {
+        let cloze_note_id = match note {
+            Some((start, end)) => Some(
+                sqlx::query(
+                    "INSERT INTO cloze_notes (text_id, original_text, parsed_segments, cloze_count, start_position, end_position) \
+                     VALUES (?, 'note', '[]', 1, ?, ?)",
+                )
+                .bind(text_id)
+                .bind(start)
+                .bind(end)
+                .execute(pool)
+                .await
+                .unwrap()
+                .last_insert_rowid(),
+            ),
+            None => None,
+        };
+
+        sqlx::query(
+            "INSERT INTO flashcards (text_id, original_text, cloze_text, cloze_index, due, cloze_note_id) \
+             VALUES (?, 'note', '{{c1::note}}', 0, datetime('now'), ?)",
+        )
+        .bind(text_id)
+        .bind(cloze_note_id)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    async fn insert_text(pool: &sqlx::SqlitePool, title: &str, content: &str) -> i64 {
+        sqlx::query("INSERT INTO texts (title, content, content_length) VALUES (?, ?, ?)")
+            .bind(title)
+            .bind(content)
+            .bind(content.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn flashcard(pool: &sqlx::SqlitePool, id: i64) -> Flashcard {
+        sqlx::query_as::<_, Flashcard>(
+            "SELECT id, text_id, user_id, original_text, cloze_text, cloze_index, \
+             COALESCE(display_index, 0) as display_index, COALESCE(cloze_number, 1) as cloze_number, \
+             created_at, updated_at, cloze_note_id, due, stability, difficulty, elapsed_days, \
+             scheduled_days, reps, lapses, state, last_review, NULL as text_title \
+             FROM flashcards WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attach_source_context_for_a_batch() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+
+        // "[Rivers](https://example.org)" renders as "Rivers", so "flow" is at 7..11
+        let first = insert_text(pool, "Rivers", "[Rivers](https://example.org) flow downhill.\n\nLakes do not.").await;
+        let second = insert_text(pool, "Lakes", "Still water.").await;
+
+        let ids = [
+            insert_card(pool, first, Some((7, 11))).await,
+            insert_card(pool, first, Some((23, 28))).await,
+            insert_card(pool, second, Some((0, 5))).await,
+            insert_card(pool, second, None).await,
+        ];
+        let mut cards = Vec::new();
+        for id in ids {
+            cards.push(flashcard(pool, id).await);
+        }
+
+        let review_cards = attach_source_context(pool, cards).await.unwrap();
+        let contexts: Vec<Option<(String, String, String)>> = review_cards
+            .iter()
+            .map(|card| {
+                card.source_context
+                    .as_ref()
+                    .map(|c| (c.before.clone(), c.marked.clone(), c.after.clone()))
+            })
+            .collect();
+
+        let context = |before: &str, marked: &str, after: &str| {
+            Some((before.to_string(), marked.to_string(), after.to_string()))
+        };
+        assert_eq!(
+            contexts,
+            vec![
+                context("Rivers ", "flow", " downhill."),
+                context("", "Lakes", " do not."),
+                context("", "Still", " water."),
+                None,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_card_source_location() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+
+        let text_id = insert_text(pool, "Rivers", "Rivers flow downhill.").await;
+        let positioned = insert_card(pool, text_id, Some((7, 11))).await;
+        let unpositioned = insert_card(pool, text_id, None).await;
+
+        let location = card_source_location(pool, positioned).await.unwrap();
+        assert_eq!(location.text_id, text_id);
+        assert_eq!(location.text_title, "Rivers");
+        assert!(location.cloze_note_id.is_some());
+        assert_eq!((location.start_position, location.end_position), (Some(7), Some(11)));
+
+        let location = card_source_location(pool, unpositioned).await.unwrap();
+        assert_eq!(location.cloze_note_id, None);
+        assert_eq!((location.start_position, location.end_position), (None, None));
+
+        assert!(card_source_location(pool, 9999).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_review_stats_skip_suspended_texts() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+
+        let active = insert_text(pool, "Active", "Reviewed.").await;
+        let suspended = insert_text(pool, "Suspended", "Paused.").await;
+        insert_card(pool, active, None).await;
+        insert_card(pool, suspended, None).await;
+        sqlx::query("UPDATE texts SET review_suspended = 1 WHERE id = ?")
+            .bind(suspended)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        // Only the active text's card is in the due queue, so only it is counted
+        let global = review_stats(pool, StudyFilter::Global, Utc::now()).await.unwrap();
+        assert_eq!((global.due_count, global.new_count), (1, 1));
+
+        let paused = review_stats(pool, StudyFilter::Text { text_id: suspended }, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!((paused.due_count, paused.new_count), (0, 0));
+    }
+}
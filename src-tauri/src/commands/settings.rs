@@ -290,6 +290,33 @@ pub async fn reset_all_data(
     .await
     .map_err(|e| format!("Failed to delete paragraphs: {}", e))?;
 
+    sqlx::query!(
+        r#"
+        DELETE FROM sentences
+        "#
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to delete sentences: {}", e))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM excluded_ranges
+        "#
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to delete excluded ranges: {}", e))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM text_revisions
+        "#
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to delete text revisions: {}", e))?;
+
     let texts_result = sqlx::query!(
         r#"
         DELETE FROM texts
@@ -311,7 +338,10 @@ pub async fn reset_all_data(
     sqlx::query!(
         r#"
         DELETE FROM sqlite_sequence
-        WHERE name IN ('texts', 'folders', 'flashcards', 'cloze_notes', 'read_ranges', 'paragraphs', 'review_history')
+        WHERE name IN (
+            'texts', 'folders', 'flashcards', 'cloze_notes', 'read_ranges', 'paragraphs',
+            'sentences', 'excluded_ranges', 'text_revisions', 'review_history'
+        )
         "#
     )
     .execute(&mut *tx)
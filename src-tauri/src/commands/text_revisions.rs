@@ -0,0 +1,430 @@
+// Text revision commands
+//
+// Persistent edit history for texts: every content update records a revision as a
+// diff against the previous version. Revisions can be listed, compared, and restored.
+// Restoring is itself recorded as a new revision, so history is never lost.
+
+use crate::commands::texts::{apply_edit_with_smart_marks, dom_edit_span, dom_length, UpdateResult};
+use crate::db::Database;
+use crate::services::text_revisions::{self, DiffLine, TextDiff};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+
+/// Why a revision was recorded
+pub enum RevisionSource {
+    Edit,
+    /// Restore of the given earlier revision
+    Restore(i64),
+    /// Update of an imported article to its latest version at the source
+    Refresh,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRevision {
+    pub revision_number: i64,
+    pub edit_start: i64,
+    /// UTF-16 length of the text the edit removed
+    pub removed_length: i64,
+    /// UTF-16 length of the text the edit inserted
+    pub inserted_length: i64,
+    pub content_length: i64,
+    pub source: String,
+    pub restored_from: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiff {
+    pub from_revision: i64,
+    pub to_revision: i64,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    /// Revision recorded for the restore, None if the content was already identical
+    pub revision_number: Option<i64>,
+    pub marks: UpdateResult,
+}
+
+/// Record the change from `old_content` to `new_content` as the text's next revision
+/// Returns None without recording anything if the content didn't change
+pub(crate) async fn record_revision(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    old_content: &str,
+    new_content: &str,
+    source: RevisionSource,
+    now: DateTime<Utc>,
+) -> Result<Option<i64>, String> {
+    let diff = TextDiff::between(old_content, new_content);
+    if diff.is_empty() {
+        return Ok(None);
+    }
+
+    let revision_number: i64 = sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(revision_number), 0) + 1 as "next!: i64" FROM text_revisions WHERE text_id = ?"#,
+        text_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to get next revision number: {}", e))?;
+
+    let (source_name, restored_from) = match source {
+        RevisionSource::Edit => ("edit", None),
+        RevisionSource::Restore(revision) => ("restore", Some(revision)),
+        RevisionSource::Refresh => ("refresh", None),
+    };
+    let content_length = new_content.encode_utf16().count() as i64;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO text_revisions (
+            text_id, revision_number, edit_start, removed_text, inserted_text,
+            content_length, source, restored_from, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        text_id,
+        revision_number,
+        diff.start,
+        diff.removed,
+        diff.inserted,
+        content_length,
+        source_name,
+        restored_from,
+        now
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to record revision: {}", e))?;
+
+    Ok(Some(revision_number))
+}
+
+/// Diffs recorded after `revision_number`, newest first
+async fn diffs_after_revision(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    revision_number: i64,
+) -> Result<Vec<TextDiff>, String> {
+    let latest: i64 = sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(revision_number), 0) as "latest!: i64" FROM text_revisions WHERE text_id = ?"#,
+        text_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch latest revision: {}", e))?;
+
+    if revision_number < 0 || revision_number > latest {
+        return Err(format!("Revision {} does not exist", revision_number));
+    }
+
+    let newer = sqlx::query!(
+        r#"
+        SELECT edit_start, removed_text, inserted_text
+        FROM text_revisions
+        WHERE text_id = ? AND revision_number > ?
+        ORDER BY revision_number DESC
+        "#,
+        text_id,
+        revision_number
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch revisions: {}", e))?;
+
+    Ok(newer
+        .into_iter()
+        .map(|row| TextDiff {
+            start: row.edit_start,
+            removed: row.removed_text,
+            inserted: row.inserted_text,
+        })
+        .collect())
+}
+
+async fn current_content(conn: &mut SqliteConnection, text_id: i64) -> Result<String, String> {
+    sqlx::query_scalar!("SELECT content FROM texts WHERE id = ?", text_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to fetch text: {}", e))?
+        .ok_or_else(|| "Text not found".to_string())
+}
+
+/// Rebuild a text's content as of `revision_number` (0 = before the first recorded edit)
+async fn content_at_revision(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    revision_number: i64,
+) -> Result<String, String> {
+    let current = current_content(conn, text_id).await?;
+    let diffs = diffs_after_revision(conn, text_id, revision_number).await?;
+
+    text_revisions::rewind(&current, &diffs)
+        .map_err(|e| format!("Failed to rebuild revision {}: {}", revision_number, e))
+}
+
+/// List a text's revisions, newest first
+#[tauri::command]
+pub async fn list_text_revisions(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<TextRevision>, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT revision_number, edit_start, removed_text, inserted_text,
+               content_length, source, restored_from, created_at
+        FROM text_revisions
+        WHERE text_id = ?
+        ORDER BY revision_number DESC
+        "#,
+        text_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch revisions: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TextRevision {
+            revision_number: row.revision_number,
+            edit_start: row.edit_start,
+            removed_length: row.removed_text.encode_utf16().count() as i64,
+            inserted_length: row.inserted_text.encode_utf16().count() as i64,
+            content_length: row.content_length,
+            source: row.source,
+            restored_from: row.restored_from,
+            created_at: DateTime::from_naive_utc_and_offset(row.created_at, Utc),
+        })
+        .collect())
+}
+
+/// Get a text's full content as of a revision (0 = before the first recorded edit)
+#[tauri::command]
+pub async fn get_text_revision_content(
+    text_id: i64,
+    revision_number: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<String, String> {
+    let db = db.lock().await;
+    let mut conn = db
+        .pool()
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    content_at_revision(&mut conn, text_id, revision_number).await
+}
+
+/// Line-level diff between two revisions of a text
+#[tauri::command]
+pub async fn diff_text_revisions(
+    text_id: i64,
+    from_revision: i64,
+    to_revision: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<RevisionDiff, String> {
+    let db = db.lock().await;
+    let mut conn = db
+        .pool()
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    let from_content = content_at_revision(&mut conn, text_id, from_revision).await?;
+    let to_content = content_at_revision(&mut conn, text_id, to_revision).await?;
+
+    Ok(RevisionDiff {
+        from_revision,
+        to_revision,
+        lines: text_revisions::diff_lines(&from_content, &to_content),
+    })
+}
+
+/// Combine per-step mark results, keeping each mark's last change
+pub(crate) fn merge_update_results(steps: Vec<UpdateResult>) -> UpdateResult {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Outcome {
+        Unchanged,
+        Updated,
+        Flagged,
+        Reanchored,
+    }
+
+    let mut outcomes: Vec<(i64, Outcome)> = Vec::new();
+    for step in steps {
+        let events = step
+            .unchanged_marks
+            .into_iter()
+            .map(|id| (id, Outcome::Unchanged))
+            .chain(step.updated_marks.into_iter().map(|id| (id, Outcome::Updated)))
+            .chain(step.flagged_marks.into_iter().map(|id| (id, Outcome::Flagged)))
+            .chain(step.reanchored_marks.into_iter().map(|id| (id, Outcome::Reanchored)));
+
+        for (id, outcome) in events {
+            match outcomes.iter_mut().find(|(existing, _)| *existing == id) {
+                Some(entry) if outcome != Outcome::Unchanged => entry.1 = outcome,
+                Some(_) => {}
+                None => outcomes.push((id, outcome)),
+            }
+        }
+    }
+
+    let ids = |wanted: Outcome| -> Vec<i64> {
+        outcomes
+            .iter()
+            .filter(|(_, outcome)| *outcome == wanted)
+            .map(|(id, _)| *id)
+            .collect()
+    };
+
+    UpdateResult {
+        updated_marks: ids(Outcome::Updated),
+        flagged_marks: ids(Outcome::Flagged),
+        unchanged_marks: ids(Outcome::Unchanged),
+        reanchored_marks: ids(Outcome::Reanchored),
+    }
+}
+
+/// Restore a text to an earlier revision
+///
+/// The newer revisions are undone one at a time, each applied like a normal edit,
+/// so marks and read ranges are remapped through every step rather than one large
+/// replacement. Paragraphs are regenerated and the restore is recorded as a new revision.
+#[tauri::command]
+pub async fn restore_text_revision(
+    text_id: i64,
+    revision_number: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<RestoreResult, String> {
+    let db = db.lock().await;
+    restore_revision(db.pool(), text_id, revision_number, Utc::now()).await
+}
+
+async fn restore_revision(
+    pool: &sqlx::SqlitePool,
+    text_id: i64,
+    revision_number: i64,
+    now: DateTime<Utc>,
+) -> Result<RestoreResult, String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let current = current_content(&mut tx, text_id).await?;
+    let diffs = diffs_after_revision(&mut tx, text_id, revision_number).await?;
+
+    let mut content = current.clone();
+    let mut steps = Vec::with_capacity(diffs.len());
+    for diff in &diffs {
+        let undo = diff.invert();
+        let previous = undo
+            .apply(&content)
+            .map_err(|e| format!("Failed to rebuild revision {}: {}", revision_number, e))?;
+        // The undo's offsets are raw; marks and read ranges need the rendered span
+        let (edit_start, edit_end, _) = dom_edit_span(&content, &previous);
+
+        steps.push(
+            apply_edit_with_smart_marks(&mut tx, text_id, edit_start, edit_end, dom_length(&content), &previous, now)
+                .await?,
+        );
+        content = previous;
+    }
+
+    let new_revision = record_revision(
+        &mut tx,
+        text_id,
+        &current,
+        &content,
+        RevisionSource::Restore(revision_number),
+        now,
+    )
+    .await?;
+    let marks = merge_update_results(steps);
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(RestoreResult {
+        revision_number: new_revision,
+        marks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::excluded_ranges::fetch_excluded_ranges;
+
+    #[tokio::test]
+    async fn test_restore_shifts_marks_and_ranges_past_links_in_dom_space() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+        let original = "[River](https://x.org) one two three";
+        let edited = "[River](https://x.org) one 2 three";
+
+        let text_id = sqlx::query("INSERT INTO texts (title, content, content_length) VALUES ('Linked', ?, ?)")
+            .bind(edited)
+            .bind(edited.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        let mut conn = pool.acquire().await.unwrap();
+        record_revision(&mut conn, text_id, original, edited, RevisionSource::Edit, Utc::now())
+            .await
+            .unwrap();
+        drop(conn);
+
+        // "three" in the rendered edited text, "River one 2 three"
+        let mark_id = sqlx::query(
+            "INSERT INTO cloze_notes (text_id, original_text, parsed_segments, cloze_count, start_position, end_position) \
+             VALUES (?, 'three', '[]', 1, 12, 17)",
+        )
+        .bind(text_id)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        sqlx::query("INSERT INTO read_ranges (text_id, start_position, end_position) VALUES (?, 12, 17)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO excluded_ranges (text_id, start_position, end_position) VALUES (?, 12, 17)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let result = restore_revision(pool, text_id, 0, Utc::now()).await.unwrap();
+
+        assert_eq!(result.marks.updated_marks, vec![mark_id]);
+        let mark: (i64, i64) = sqlx::query_as("SELECT start_position, end_position FROM cloze_notes WHERE id = ?")
+            .bind(mark_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(mark, (14, 19));
+        let range: (i64, i64) = sqlx::query_as("SELECT start_position, end_position FROM read_ranges WHERE text_id = ?")
+            .bind(text_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(range, (14, 19));
+        assert_eq!(fetch_excluded_ranges(&mut pool.acquire().await.unwrap(), text_id).await.unwrap(), vec![(14, 19)]);
+    }
+}
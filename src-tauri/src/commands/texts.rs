@@ -8,14 +8,23 @@
 // These commands handle storing texts in the database and returning
 // metadata for the frontend to display.
 
+use crate::commands::excluded_ranges::{
+    apply_edit_to_excluded_ranges, fetch_excluded_ranges, inline_exclusions, save_excluded_ranges,
+};
+use crate::commands::flashcard_hub::process_content_to_dom_space;
+use crate::commands::text_revisions::{record_revision, RevisionSource};
 use crate::db::Database;
 use crate::models::paragraph::Paragraph;
 use crate::models::read_range::ReadRange;
-use crate::models::text::{CreateTextRequest, Text};
+use crate::models::text::{CreateTextRequest, Text, TEXT_STATUSES};
+use crate::services::excluded_ranges;
+use crate::services::mark_anchor::{self, DEFAULT_REANCHOR_THRESHOLD};
 use crate::services::parser::{detect_paragraphs, store_paragraphs};
 use crate::services::range_calculator::RangeCalculator;
-use chrono::Utc;
+use crate::services::sentence_segmenter::{self, segment_sentences, store_sentences};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
+use sqlx::SqliteConnection;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -26,10 +35,27 @@ pub async fn create_text(
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<Text, String> {
     let db = db.lock().await;
-    let pool = db.pool();
+    let mut conn = db
+        .pool()
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+
+    insert_text(&mut conn, request).await
+}
+
+/// Insert a new text with its paragraphs, sentences and excluded ranges, on a pooled
+/// connection or inside a caller's transaction. Shared by `create_text` and the importers.
+pub(crate) async fn insert_text(
+    conn: &mut SqliteConnection,
+    request: CreateTextRequest,
+) -> Result<Text, String> {
+    // Inline [[exclude]] tags become stored excluded ranges
+    let (content, excluded) = inline_exclusions(&request.content)
+        .unwrap_or_else(|| (request.content.clone(), Vec::new()));
 
     // Use UTF-16 code units to match JavaScript's string.length
-    let content_length = request.content.encode_utf16().count() as i64;
+    let content_length = content.encode_utf16().count() as i64;
     let now = Utc::now();
 
     let result = sqlx::query!(
@@ -37,14 +63,15 @@ pub async fn create_text(
         INSERT INTO texts (
             title, source, source_url, content, content_length,
             ingested_at, updated_at, metadata,
-            author, publication_date, publisher, access_date, doi, isbn, folder_id
+            author, publication_date, publisher, access_date, doi, isbn, language,
+            wiki_page_id, wiki_revision_id, folder_id
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         request.title,
         request.source,
         request.source_url,
-        request.content,
+        content,
         content_length,
         now,
         now,
@@ -55,18 +82,25 @@ pub async fn create_text(
         request.access_date,
         request.doi,
         request.isbn,
+        request.language,
+        request.wiki_page_id,
+        request.wiki_revision_id,
         request.folder_id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .map_err(|e| format!("Failed to insert text: {}", e))?;
 
     let text_id = result.last_insert_rowid();
 
-    let paragraphs = detect_paragraphs(&request.content);
-    store_paragraphs(pool, text_id, &paragraphs)
+    let paragraphs = detect_paragraphs(&content);
+    store_paragraphs(&mut *conn, text_id, &paragraphs)
         .await
         .map_err(|e| format!("Failed to store paragraphs: {}", e))?;
+    store_sentences(&mut *conn, text_id, &segment_sentences(&process_content_to_dom_space(&content)))
+        .await
+        .map_err(|e| format!("Failed to store sentences: {}", e))?;
+    save_excluded_ranges(&mut *conn, text_id, &excluded).await?;
 
     let text = sqlx::query_as!(
         Text,
@@ -74,34 +108,51 @@ pub async fn create_text(
         SELECT
             id as "id!", title, source, source_url, content, content_length as "content_length!",
             ingested_at as "ingested_at: _", updated_at as "updated_at: _",
-            metadata, author, publication_date, publisher, access_date, doi, isbn, folder_id
+            metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+            wiki_page_id, wiki_revision_id, folder_id,
+            parent_text_id, parent_start_position, parent_end_position, status
         FROM texts
         WHERE id = ?
         "#,
         text_id
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|e| format!("Failed to fetch created text: {}", e))?;
 
     Ok(text)
 }
 
+/// List texts, newest first, optionally only those with the given lifecycle status
 #[tauri::command]
-pub async fn list_texts(db: State<'_, Arc<Mutex<Database>>>) -> Result<Vec<Text>, String> {
+pub async fn list_texts(
+    status: Option<String>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<Vec<Text>, String> {
     let db = db.lock().await;
     let pool = db.pool();
 
+    if let Some(status) = &status {
+        if !TEXT_STATUSES.contains(&status.as_str()) {
+            return Err(format!("Invalid text status: {}", status));
+        }
+    }
+
     let texts = sqlx::query_as!(
         Text,
         r#"
         SELECT
             id as "id!", title, source, source_url, content, content_length as "content_length!",
             ingested_at as "ingested_at: _", updated_at as "updated_at: _",
-            metadata, author, publication_date, publisher, access_date, doi, isbn, folder_id
+            metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+            wiki_page_id, wiki_revision_id, folder_id,
+            parent_text_id, parent_start_position, parent_end_position, status
         FROM texts
+        WHERE ? IS NULL OR status = ?
         ORDER BY ingested_at DESC
-        "#
+        "#,
+        status,
+        status
     )
     .fetch_all(pool)
     .await
@@ -136,7 +187,14 @@ pub async fn get_texts_with_available_marks(
             t.access_date,
             t.doi,
             t.isbn,
-            t.folder_id
+            t.language,
+            t.wiki_page_id,
+            t.wiki_revision_id,
+            t.folder_id,
+            t.parent_text_id,
+            t.parent_start_position,
+            t.parent_end_position,
+            t.status
         FROM texts t
         INNER JOIN cloze_notes cn ON cn.text_id = t.id
         WHERE cn.status IN ('pending', 'skipped')
@@ -165,7 +223,9 @@ pub async fn get_text(
         SELECT
             id as "id!", title, source, source_url, content, content_length as "content_length!",
             ingested_at as "ingested_at: _", updated_at as "updated_at: _",
-            metadata, author, publication_date, publisher, access_date, doi, isbn, folder_id
+            metadata, author, publication_date, publisher, access_date, doi, isbn, language,
+            wiki_page_id, wiki_revision_id, folder_id,
+            parent_text_id, parent_start_position, parent_end_position, status
         FROM texts
         WHERE id = ?
         "#,
@@ -227,6 +287,179 @@ pub async fn delete_text(
     Ok(())
 }
 
+/// Archive a text, optionally taking its cards out of review until it's unarchived
+#[tauri::command]
+pub async fn archive_text(
+    id: i64,
+    suspend_cards: Option<bool>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<(), String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+    let suspend_cards = suspend_cards.unwrap_or(false);
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE texts
+        SET status = 'archived', review_suspended = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+        suspend_cards,
+        now,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to archive text: {}", e))?;
+
+    if result.rows_affected() == 0 {
+        return Err("Text not found".to_string());
+    }
+
+    Ok(())
+}
+
+/// Bring an archived text back, as 'reading' if it has progress and 'inbox' otherwise
+/// Its cards return to review; a finished text is detected again on its next progress check
+#[tauri::command]
+pub async fn unarchive_text(
+    id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<String, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+    let now = Utc::now();
+
+    let status = sqlx::query_scalar!(
+        r#"
+        UPDATE texts
+        SET status = CASE
+                WHEN EXISTS (SELECT 1 FROM read_ranges WHERE text_id = texts.id) THEN 'reading'
+                ELSE 'inbox'
+            END,
+            review_suspended = 0,
+            updated_at = ?
+        WHERE id = ? AND status = 'archived'
+        RETURNING status
+        "#,
+        now,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to unarchive text: {}", e))?
+    .ok_or_else(|| "Text not found or not archived".to_string())?;
+
+    Ok(status)
+}
+
+/// Shift, trim or split a text's read ranges to follow an edit of `edit_start..edit_end`
+async fn apply_edit_to_read_ranges(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    edit_start: i64,
+    edit_end: i64,
+    length_delta: i64,
+    new_content_length: i64,
+) -> Result<(), String> {
+    let ranges = sqlx::query!(
+        r#"
+        SELECT id as "id!", start_position, end_position
+        FROM read_ranges
+        WHERE text_id = ?
+        "#,
+        text_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
+
+    for range in ranges {
+        let pieces: Vec<(i64, i64)> = RangeCalculator::apply_edit_to_range(
+            range.start_position,
+            range.end_position,
+            edit_start,
+            edit_end,
+            length_delta,
+        )
+        .into_iter()
+        .map(|(start, end)| (start.max(0), end.min(new_content_length)))
+        .filter(|(start, end)| start < end)
+        .collect();
+
+        if pieces == [(range.start_position, range.end_position)] {
+            continue;
+        }
+
+        let Some(&(first_start, first_end)) = pieces.first() else {
+            sqlx::query!("DELETE FROM read_ranges WHERE id = ?", range.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| format!("Failed to delete read range {}: {}", range.id, e))?;
+            continue;
+        };
+
+        sqlx::query!(
+            "UPDATE read_ranges SET start_position = ?, end_position = ? WHERE id = ?",
+            first_start,
+            first_end,
+            range.id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to update read range {}: {}", range.id, e))?;
+
+        // The second half of a split range keeps the original's session and timestamp,
+        // but not its counts, so session statistics aren't counted twice
+        for &(start, end) in &pieces[1..] {
+            sqlx::query!(
+                r#"
+                INSERT INTO read_ranges (text_id, user_id, start_position, end_position, marked_at, session_id, is_auto_completed)
+                SELECT text_id, user_id, ?, ?, marked_at, session_id, is_auto_completed
+                FROM read_ranges
+                WHERE id = ?
+                "#,
+                start,
+                end,
+                range.id
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to split read range {}: {}", range.id, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace a text's stored paragraphs and sentences with ones detected from its new content
+pub(crate) async fn regenerate_paragraphs(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    content: &str,
+) -> Result<(), String> {
+    sqlx::query!("DELETE FROM paragraphs WHERE text_id = ?", text_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to delete paragraphs: {}", e))?;
+
+    sqlx::query!("DELETE FROM sentences WHERE text_id = ?", text_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to delete sentences: {}", e))?;
+
+    let paragraphs = detect_paragraphs(content);
+    store_paragraphs(&mut *conn, text_id, &paragraphs)
+        .await
+        .map_err(|e| format!("Failed to store paragraphs: {}", e))?;
+
+    // Sentence positions are DOM space, like the read and excluded ranges they're compared with
+    store_sentences(&mut *conn, text_id, &segment_sentences(&process_content_to_dom_space(content)))
+        .await
+        .map_err(|e| format!("Failed to store sentences: {}", e))
+}
+
 #[tauri::command]
 pub async fn update_text_content(
     text_id: i64,
@@ -234,11 +467,49 @@ pub async fn update_text_content(
     db: State<'_, Arc<Mutex<Database>>>,
 ) -> Result<(), String> {
     let db = db.lock().await;
-    let pool = db.pool();
-    let now = Utc::now();
+    replace_text_content(db.pool(), text_id, &new_content, Utc::now()).await
+}
 
+/// Rendered (DOM space) UTF-16 length of `content`
+pub(crate) fn dom_length(content: &str) -> i64 {
+    process_content_to_dom_space(content).encode_utf16().count() as i64
+}
+
+/// Where `old_content` and `new_content` differ, as DOM-space
+/// (edit_start, old_edit_end, new_edit_end)
+///
+/// Marks and read ranges are stored against the rendered text, so raw offsets
+/// would drift by the length of any link markup before the edit.
+pub(crate) fn dom_edit_span(old_content: &str, new_content: &str) -> (i64, i64, i64) {
+    RangeCalculator::edit_span(
+        &process_content_to_dom_space(old_content),
+        &process_content_to_dom_space(new_content),
+    )
+}
+
+async fn replace_text_content(
+    pool: &sqlx::SqlitePool,
+    text_id: i64,
+    new_content: &str,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
     let new_length = new_content.encode_utf16().count() as i64;
 
+    let old_content: String = sqlx::query_scalar!("SELECT content FROM texts WHERE id = ?", text_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch original text: {}", e))?;
+
+    let (edit_start, old_edit_end, new_edit_end) = dom_edit_span(&old_content, new_content);
+    let new_dom_length = dom_length(new_content);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    record_revision(&mut tx, text_id, &old_content, new_content, RevisionSource::Edit, now).await?;
+
     sqlx::query!(
         "UPDATE texts SET content = ?, content_length = ?, updated_at = ? WHERE id = ?",
         new_content,
@@ -246,10 +517,30 @@ pub async fn update_text_content(
         now,
         text_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await
     .map_err(|e| format!("Failed to update text content: {}", e))?;
 
+    apply_edit_to_read_ranges(
+        &mut tx,
+        text_id,
+        edit_start,
+        old_edit_end,
+        new_edit_end - old_edit_end,
+        new_dom_length,
+    )
+    .await?;
+    apply_edit_to_excluded_ranges(
+        &mut tx,
+        text_id,
+        edit_start,
+        old_edit_end,
+        new_edit_end - old_edit_end,
+        new_dom_length,
+    )
+    .await?;
+    regenerate_paragraphs(&mut tx, text_id, new_content).await?;
+
     sqlx::query!(
         r#"
         UPDATE cloze_notes
@@ -262,24 +553,31 @@ pub async fn update_text_content(
         now,
         text_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await
     .map_err(|e| format!("Failed to update marks: {}", e))?;
 
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
     Ok(())
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateResult {
     pub updated_marks: Vec<i64>,
     pub flagged_marks: Vec<i64>,
     pub unchanged_marks: Vec<i64>,
+    /// Marks the edit overlapped that were found again and relocated
+    pub reanchored_marks: Vec<i64>,
 }
 
 #[derive(sqlx::FromRow)]
 struct ClozeNoteMark {
     id: i64,
+    original_text: String,
     start_position: Option<i64>,
     end_position: Option<i64>,
 }
@@ -301,70 +599,95 @@ pub async fn update_text_with_smart_marks(
     }
 
     let original_text = sqlx::query!(
-        "SELECT content_length FROM texts WHERE id = ?",
+        "SELECT content, content_length FROM texts WHERE id = ?",
         text_id
     )
     .fetch_one(pool)
     .await
     .map_err(|e| format!("Failed to fetch original text: {}", e))?;
 
-    let original_content_length = original_text.content_length;
+    let original_dom_length = dom_length(&original_text.content);
 
-    if edit_end > original_content_length {
-        return Err(format!("Invalid edit_end position: {} exceeds original content length: {}", edit_end, original_content_length));
+    if edit_end > original_dom_length {
+        return Err(format!("Invalid edit_end position: {} exceeds original content length: {}", edit_end, original_dom_length));
     }
 
-    let new_content_length = new_content.encode_utf16().count() as i64;
-    let length_delta = new_content_length - original_content_length;
-
     let mut tx = pool
         .begin()
         .await
         .map_err(|e| format!("Failed to start transaction: {}", e))?;
 
+    record_revision(&mut tx, text_id, &original_text.content, &new_content, RevisionSource::Edit, now).await?;
+
+    let result = apply_edit_with_smart_marks(
+        &mut tx,
+        text_id,
+        edit_start,
+        edit_end,
+        original_dom_length,
+        &new_content,
+        now,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(result)
+}
+
+/// Apply an edit of `edit_start..edit_end` inside the caller's transaction
+///
+/// Edit positions and `original_dom_length` are in DOM space, like the marks and
+/// ranges they're applied to. Marks before the edit are kept, marks after it are
+/// shifted, and marks the edit overlaps are re-anchored or flagged for review. Read
+/// ranges follow the same shift/trim/split rules, excluded ranges move with the edit,
+/// and paragraphs are regenerated from the new content.
+pub(crate) async fn apply_edit_with_smart_marks(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    edit_start: i64,
+    edit_end: i64,
+    original_dom_length: i64,
+    new_content: &str,
+    now: DateTime<Utc>,
+) -> Result<UpdateResult, String> {
+    // Marks are stored in DOM space, so fuzzy re-anchoring searches the rendered content
+    let dom_content = process_content_to_dom_space(new_content);
+    let new_dom_length = dom_content.encode_utf16().count() as i64;
+    let length_delta = new_dom_length - original_dom_length;
+
     let marks = sqlx::query_as::<_, ClozeNoteMark>(
         r#"
-        SELECT id, start_position, end_position
+        SELECT id, original_text, start_position, end_position
         FROM cloze_notes
         WHERE text_id = ?
           AND status NOT IN ('buried', 'converted')
         "#
     )
     .bind(text_id)
-    .fetch_all(&mut *tx)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| format!("Failed to fetch marks: {}", e))?;
 
     let mut updated_marks = Vec::new();
     let mut flagged_marks = Vec::new();
     let mut unchanged_marks = Vec::new();
+    let mut reanchored_marks = Vec::new();
 
     for mark in marks {
-        match (mark.start_position, mark.end_position) {
+        let flag_note = match (mark.start_position, mark.end_position) {
             (Some(start_pos), Some(end_pos)) => {
                 if end_pos <= edit_start {
                     unchanged_marks.push(mark.id);
+                    None
                 } else if start_pos >= edit_end {
                     let new_start = start_pos + length_delta;
                     let new_end = end_pos + length_delta;
 
-                    if new_start < 0 || new_end < 0 || new_end > new_content_length {
-                        sqlx::query!(
-                            r#"
-                            UPDATE cloze_notes
-                            SET status = 'needs_review',
-                                notes = 'Text edited: mark position became invalid',
-                                updated_at = ?
-                            WHERE id = ?
-                            "#,
-                            now,
-                            mark.id
-                        )
-                        .execute(&mut *tx)
-                        .await
-                        .map_err(|e| format!("Failed to flag mark {}: {}", mark.id, e))?;
-
-                        flagged_marks.push(mark.id);
+                    if new_start < 0 || new_end < 0 || new_end > new_dom_length {
+                        Some("Text edited: mark position became invalid")
                     } else {
                         sqlx::query!(
                             r#"
@@ -379,52 +702,78 @@ pub async fn update_text_with_smart_marks(
                             now,
                             mark.id
                         )
-                        .execute(&mut *tx)
+                        .execute(&mut *conn)
                         .await
                         .map_err(|e| format!("Failed to update mark {}: {}", mark.id, e))?;
 
                         updated_marks.push(mark.id);
+                        None
                     }
                 } else {
-                    sqlx::query!(
-                        r#"
-                        UPDATE cloze_notes
-                        SET status = 'needs_review',
-                            notes = 'Text edited in marked region',
-                            updated_at = ?
-                        WHERE id = ?
-                        "#,
-                        now,
-                        mark.id
-                    )
-                    .execute(&mut *tx)
-                    .await
-                    .map_err(|e| format!("Failed to flag overlapping mark {}: {}", mark.id, e))?;
-
-                    flagged_marks.push(mark.id);
+                    Some("Text edited in marked region")
                 }
             }
-            _ => {
-                sqlx::query!(
-                    r#"
-                    UPDATE cloze_notes
-                    SET status = 'needs_review',
-                        notes = 'Text was edited - please verify mark',
-                        updated_at = ?
-                    WHERE id = ?
-                    "#,
-                    now,
-                    mark.id
-                )
-                .execute(&mut *tx)
-                .await
-                .map_err(|e| format!("Failed to flag mark {} with missing positions: {}", mark.id, e))?;
-
-                flagged_marks.push(mark.id);
-            }
+            _ => Some("Text was edited - please verify mark"),
+        };
+
+        let Some(flag_note) = flag_note else {
+            continue;
+        };
+
+        // Before flagging, try to find the marked text again near where it was
+        let anchor = mark_anchor::reanchor(
+            &dom_content,
+            &mark.original_text,
+            mark.start_position,
+            mark.end_position,
+            DEFAULT_REANCHOR_THRESHOLD,
+        );
+
+        if let Some(anchor) = anchor {
+            sqlx::query!(
+                r#"
+                UPDATE cloze_notes
+                SET start_position = ?,
+                    end_position = ?,
+                    original_text = ?,
+                    status = CASE WHEN status = 'needs_review' THEN 'pending' ELSE status END,
+                    notes = CASE WHEN status = 'needs_review' THEN NULL ELSE notes END,
+                    updated_at = ?
+                WHERE id = ?
+                "#,
+                anchor.start,
+                anchor.end,
+                anchor.matched_text,
+                now,
+                mark.id
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to re-anchor mark {}: {}", mark.id, e))?;
+
+            reanchored_marks.push(mark.id);
+        } else {
+            sqlx::query!(
+                r#"
+                UPDATE cloze_notes
+                SET status = 'needs_review',
+                    notes = ?,
+                    updated_at = ?
+                WHERE id = ?
+                "#,
+                flag_note,
+                now,
+                mark.id
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to flag mark {}: {}", mark.id, e))?;
+
+            flagged_marks.push(mark.id);
         }
     }
 
+    let content_length = new_content.encode_utf16().count() as i64;
     sqlx::query!(
         r#"
         UPDATE texts
@@ -434,22 +783,31 @@ pub async fn update_text_with_smart_marks(
         WHERE id = ?
         "#,
         new_content,
-        new_content_length,
+        content_length,
         now,
         text_id
     )
-    .execute(&mut *tx)
+    .execute(&mut *conn)
     .await
     .map_err(|e| format!("Failed to update text content: {}", e))?;
 
-    tx.commit()
-        .await
-        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+    apply_edit_to_read_ranges(
+        &mut *conn,
+        text_id,
+        edit_start,
+        edit_end,
+        length_delta,
+        new_dom_length,
+    )
+    .await?;
+    apply_edit_to_excluded_ranges(&mut *conn, text_id, edit_start, edit_end, length_delta, new_dom_length).await?;
+    regenerate_paragraphs(&mut *conn, text_id, new_content).await?;
 
     Ok(UpdateResult {
         updated_marks,
         flagged_marks,
         unchanged_marks,
+        reanchored_marks,
     })
 }
 
@@ -633,7 +991,16 @@ pub async fn get_smart_excerpt(
         .max()
         .unwrap_or(0);
 
-    let unread_ranges = RangeCalculator::get_unread_ranges(total_length, read_ranges.clone());
+    // Excluded parts never count as unread
+    let excluded = fetch_excluded_ranges(
+        &mut *pool.acquire().await.map_err(|e| format!("Failed to acquire connection: {}", e))?,
+        text_id,
+    )
+    .await?;
+    let unread_ranges = excluded.iter().fold(
+        RangeCalculator::get_unread_ranges(total_length, read_ranges.clone()),
+        |unread, &(start, end)| excluded_ranges::remove_range(&unread, start, end),
+    );
 
     let (excerpt_start, excerpt_type) = if !unread_ranges.is_empty() {
         let paragraphs = sqlx::query_as!(
@@ -704,8 +1071,7 @@ pub async fn get_smart_excerpt(
 
         let excerpt_slice: Vec<u16> = utf16_units[search_start..excerpt_end_usize].to_vec();
         if let Ok(excerpt_str) = String::from_utf16(&excerpt_slice) {
-            if let Some(period_pos) = excerpt_str.rfind(". ") {
-                let utf16_offset = excerpt_str[..period_pos + 1].encode_utf16().count();
+            if let Some(utf16_offset) = sentence_segmenter::last_sentence_end(&excerpt_str) {
                 excerpt_end_usize = search_start + utf16_offset;
             }
         }
@@ -730,3 +1096,63 @@ pub async fn get_smart_excerpt(
         excerpt_type: excerpt_type.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "River one two three" once rendered; the link markup adds 17 raw characters
+    const LINKED: &str = "[River](https://x.org) one two three";
+
+    async fn insert_linked_text(pool: &sqlx::SqlitePool) -> i64 {
+        sqlx::query("INSERT INTO texts (title, content, content_length) VALUES ('Linked', ?, ?)")
+            .bind(LINKED)
+            .bind(LINKED.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    async fn read_ranges(pool: &sqlx::SqlitePool, text_id: i64) -> Vec<(i64, i64)> {
+        sqlx::query_as("SELECT start_position, end_position FROM read_ranges WHERE text_id = ? ORDER BY start_position")
+            .bind(text_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dom_edit_span_ignores_link_markup() {
+        let edited = "[River](https://x.org) one 2 three";
+
+        assert_eq!(dom_edit_span(LINKED, edited), (10, 13, 11));
+        assert_eq!(dom_length(edited), 17);
+    }
+
+    #[tokio::test]
+    async fn test_update_text_content_shifts_ranges_in_dom_space() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+        let text_id = insert_linked_text(pool).await;
+
+        // "three" in DOM space
+        sqlx::query("INSERT INTO read_ranges (text_id, start_position, end_position) VALUES (?, 14, 19)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO excluded_ranges (text_id, start_position, end_position) VALUES (?, 14, 19)")
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        replace_text_content(pool, text_id, "[River](https://x.org) one 2 three", Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(read_ranges(pool, text_id).await, vec![(12, 17)]);
+        assert_eq!(fetch_excluded_ranges(&mut pool.acquire().await.unwrap(), text_id).await.unwrap(), vec![(12, 17)]);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::services::http_client::HttpClient;
+use crate::services::web_article;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebArticleData {
+    pub title: String,
+    pub content: String,
+    /// The page's canonical URL, or the fetched URL when it declares none
+    pub url: String,
+    pub author: Option<String>,
+    pub publication_date: Option<String>,
+    pub publisher: Option<String>,
+    pub access_date: String,
+}
+
+/// Fetch any web page and extract its main article, for texts not from Wikipedia
+#[tauri::command]
+pub async fn fetch_web_article(url: String) -> Result<WebArticleData, String> {
+    let client = HttpClient::new().map_err(|e| e.to_string())?;
+    let article = web_article::fetch_web_article(&client, &url)
+        .await
+        .map_err(|e| format!("Failed to import article: {}", e))?;
+
+    Ok(WebArticleData {
+        title: article.title,
+        content: article.content,
+        url: article.canonical_url,
+        author: article.author,
+        publication_date: article.publication_date,
+        publisher: article.site_name,
+        access_date: Utc::now().format("%Y-%m-%d").to_string(),
+    })
+}
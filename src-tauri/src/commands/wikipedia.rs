@@ -1,17 +1,76 @@
+use crate::commands::text_revisions::{merge_update_results, record_revision, RevisionSource};
+use crate::commands::texts::{apply_edit_with_smart_marks, dom_edit_span, dom_length, UpdateResult};
+use crate::db::Database;
+use crate::services::http_client::HttpClient;
+use crate::services::text_revisions::{diff_paragraphs, DiffLine, DiffOp};
 use crate::services::wikipedia;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::Mutex;
+
+/// Setting listing the api.php URLs of third-party MediaWiki sites, one per line
+const MEDIAWIKI_HOSTS_SETTING: &str = "mediawikiHosts";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WikipediaArticleData {
     pub title: String,
     pub content: String,
     pub url: String,
+    pub language: Option<String>,
+    pub page_id: i64,
+    pub revision_id: Option<i64>,
     pub timestamp: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikipediaRefreshPreview {
+    pub current_revision_id: Option<i64>,
+    pub latest_revision_id: Option<i64>,
+    /// Paragraphs of the stored text and the latest revision, as kept, removed or added
+    pub paragraphs: Vec<DiffLine>,
+    pub has_changes: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikipediaRefreshResult {
+    pub revision_id: Option<i64>,
+    /// Text revision recorded for the refresh, None if the content was already identical
+    pub revision_number: Option<i64>,
+    pub marks: UpdateResult,
+    /// Read ranges that lost part of their span because the text under it changed
+    pub flagged_read_ranges: Vec<i64>,
+}
+
+async fn configured_api_urls(pool: &Pool<Sqlite>) -> Result<Vec<String>, String> {
+    let hosts = sqlx::query_scalar!("SELECT value FROM settings WHERE key = ?", MEDIAWIKI_HOSTS_SETTING)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch settings: {}", e))?;
+
+    Ok(hosts
+        .map(|hosts| hosts.lines().map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
 #[tauri::command]
-pub async fn fetch_wikipedia_article(url: String) -> Result<WikipediaArticleData, String> {
-    let article = wikipedia::fetch_wikipedia_article(&url)
+pub async fn fetch_wikipedia_article(
+    url: String,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<WikipediaArticleData, String> {
+    let extra_api_urls = {
+        let db = db.lock().await;
+        configured_api_urls(db.pool()).await?
+    };
+
+    let client = HttpClient::new().map_err(|e| e.to_string())?;
+    let article = wikipedia::fetch_wikipedia_article(&client, &url, &extra_api_urls)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -19,6 +78,263 @@ pub async fn fetch_wikipedia_article(url: String) -> Result<WikipediaArticleData
         title: article.title,
         content: article.extract,
         url: article.url,
+        language: article.language,
+        page_id: article.page_id,
+        revision_id: article.revision_id,
         timestamp: article.timestamp.to_rfc3339(),
     })
 }
+
+/// An imported article as stored, with what's needed to fetch it again
+struct StoredArticle {
+    content: String,
+    url: String,
+    page_id: Option<i64>,
+    revision_id: Option<i64>,
+    extra_api_urls: Vec<String>,
+}
+
+impl StoredArticle {
+    /// Fetch the latest revision, by page id so a renamed article is still found; texts
+    /// imported before page ids were stored fall back to the title in their URL
+    async fn fetch_latest(&self, client: &HttpClient) -> Result<wikipedia::WikipediaArticle, String> {
+        match self.page_id {
+            Some(page_id) => {
+                wikipedia::fetch_wikipedia_page(client, &self.url, &self.extra_api_urls, page_id).await
+            }
+            None => wikipedia::fetch_wikipedia_article(client, &self.url, &self.extra_api_urls).await,
+        }
+        .map_err(|e| format!("Failed to fetch article: {}", e))
+    }
+}
+
+async fn stored_article(db: &State<'_, Arc<Mutex<Database>>>, text_id: i64) -> Result<StoredArticle, String> {
+    let db = db.lock().await;
+    let pool = db.pool();
+
+    let text = sqlx::query!(
+        "SELECT content, source_url, wiki_page_id, wiki_revision_id FROM texts WHERE id = ?",
+        text_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch text: {}", e))?
+    .ok_or_else(|| "Text not found".to_string())?;
+
+    let url = text
+        .source_url
+        .ok_or_else(|| "Text has no source URL to refresh from".to_string())?;
+
+    Ok(StoredArticle {
+        content: text.content,
+        url,
+        page_id: text.wiki_page_id,
+        revision_id: text.wiki_revision_id,
+        extra_api_urls: configured_api_urls(pool).await?,
+    })
+}
+
+/// Fetch the latest revision of an imported article and compare it to the stored text,
+/// paragraph by paragraph, without changing anything
+#[tauri::command]
+pub async fn preview_wikipedia_refresh(
+    text_id: i64,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<WikipediaRefreshPreview, String> {
+    let stored = stored_article(&db, text_id).await?;
+
+    let client = HttpClient::new().map_err(|e| e.to_string())?;
+    let article = stored.fetch_latest(&client).await?;
+
+    let paragraphs = diff_paragraphs(&stored.content, &article.extract).paragraphs;
+    let has_changes = paragraphs.iter().any(|line| line.op != DiffOp::Equal);
+
+    Ok(WikipediaRefreshPreview {
+        current_revision_id: stored.revision_id,
+        latest_revision_id: article.revision_id,
+        paragraphs,
+        has_changes,
+    })
+}
+
+async fn read_range_lengths(conn: &mut SqliteConnection, text_id: i64) -> Result<HashMap<i64, i64>, String> {
+    let ranges = sqlx::query!(
+        r#"SELECT id as "id!", end_position - start_position as "length!: i64" FROM read_ranges WHERE text_id = ?"#,
+        text_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to fetch read ranges: {}", e))?;
+
+    Ok(ranges.into_iter().map(|range| (range.id, range.length)).collect())
+}
+
+/// Replace an imported article's text with a newer revision: `revision_id` when given
+/// (the one previewed), otherwise the latest
+///
+/// Each changed stretch between unchanged paragraphs is applied as its own edit, last
+/// to first, so marks and read ranges in untouched paragraphs are shifted rather than
+/// lost. Marks inside changed text are re-anchored or flagged for review, read ranges
+/// over changed text are trimmed, and excluded ranges move with the edits. The refresh
+/// is recorded as a text revision.
+#[tauri::command]
+pub async fn refresh_wikipedia_article(
+    text_id: i64,
+    revision_id: Option<i64>,
+    db: State<'_, Arc<Mutex<Database>>>,
+) -> Result<WikipediaRefreshResult, String> {
+    let stored = stored_article(&db, text_id).await?;
+
+    let client = HttpClient::new().map_err(|e| e.to_string())?;
+    let article = match revision_id {
+        Some(revision_id) => {
+            wikipedia::fetch_wikipedia_revision(&client, &stored.url, &stored.extra_api_urls, revision_id)
+                .await
+                .map_err(|e| format!("Failed to fetch article: {}", e))?
+        }
+        None => stored.fetch_latest(&client).await?,
+    };
+
+    let db = db.lock().await;
+    apply_article_revision(db.pool(), text_id, &article, Utc::now()).await
+}
+
+/// Replace a text with a fetched revision of its article, as `refresh_wikipedia_article`
+async fn apply_article_revision(
+    pool: &Pool<Sqlite>,
+    text_id: i64,
+    article: &wikipedia::WikipediaArticle,
+    now: DateTime<Utc>,
+) -> Result<WikipediaRefreshResult, String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    // The text may have been edited while the article was being fetched
+    let current: String = sqlx::query_scalar!("SELECT content FROM texts WHERE id = ?", text_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to fetch text: {}", e))?;
+    let read_ranges_before = read_range_lengths(&mut tx, text_id).await?;
+
+    let diff = diff_paragraphs(&current, &article.extract);
+    let mut content = current.clone();
+    let mut steps = Vec::with_capacity(diff.edits.len());
+    for edit in diff.edits.iter().rev() {
+        let next = edit
+            .apply(&content)
+            .map_err(|e| format!("Failed to apply refreshed article: {}", e))?;
+        // The diff's offsets are raw; marks and ranges need the rendered span
+        let (edit_start, edit_end, _) = dom_edit_span(&content, &next);
+
+        steps.push(
+            apply_edit_with_smart_marks(&mut tx, text_id, edit_start, edit_end, dom_length(&content), &next, now)
+                .await?,
+        );
+        content = next;
+    }
+
+    let revision_number = record_revision(&mut tx, text_id, &current, &content, RevisionSource::Refresh, now).await?;
+
+    sqlx::query!(
+        "UPDATE texts SET wiki_page_id = ?, wiki_revision_id = ?, updated_at = ? WHERE id = ?",
+        article.page_id,
+        article.revision_id,
+        now,
+        text_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update article revision: {}", e))?;
+
+    let read_ranges_after = read_range_lengths(&mut tx, text_id).await?;
+    let mut flagged_read_ranges: Vec<i64> = read_ranges_before
+        .into_iter()
+        .filter(|(id, length)| read_ranges_after.get(id).is_none_or(|after| after < length))
+        .map(|(id, _)| id)
+        .collect();
+    flagged_read_ranges.sort_unstable();
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(WikipediaRefreshResult {
+        revision_id: article.revision_id,
+        revision_number,
+        marks: merge_update_results(steps),
+        flagged_read_ranges,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::excluded_ranges::fetch_excluded_ranges;
+
+    #[tokio::test]
+    async fn test_refresh_moves_marks_and_ranges_past_links_in_dom_space() {
+        let db = Database::in_memory().await.unwrap();
+        let pool = db.pool();
+        let current = "[Vell](https://en.wikipedia.org/wiki/Vell) is a river.\n\nIt flows north.\n\nIt joins the Danube.";
+
+        let text_id = sqlx::query("INSERT INTO texts (title, content, content_length) VALUES ('Vell', ?, ?)")
+            .bind(current)
+            .bind(current.encode_utf16().count() as i64)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        // Rendered, the last paragraph is 35..55 and "Danube" 48..54
+        let mark_id = sqlx::query(
+            "INSERT INTO cloze_notes (text_id, original_text, parsed_segments, cloze_count, start_position, end_position) \
+             VALUES (?, 'Danube', '[]', 1, 48, 54)",
+        )
+        .bind(text_id)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        for table in ["read_ranges", "excluded_ranges"] {
+            sqlx::query(&format!(
+                "INSERT INTO {} (text_id, start_position, end_position) VALUES (?, 35, 55)",
+                table
+            ))
+            .bind(text_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        let article = wikipedia::WikipediaArticle {
+            title: "Vell".to_string(),
+            extract: "[Vell](https://en.wikipedia.org/wiki/Vell) is a river.\n\n\
+                      It flows north through three countries.\n\nIt joins the Danube."
+                .to_string(),
+            url: "https://en.wikipedia.org/wiki/Vell".to_string(),
+            language: None,
+            page_id: 1,
+            revision_id: Some(2),
+            timestamp: Utc::now(),
+        };
+        let result = apply_article_revision(pool, text_id, &article, Utc::now()).await.unwrap();
+
+        // The second paragraph grew by 24 characters
+        assert_eq!(result.marks.updated_marks, vec![mark_id]);
+        assert!(result.flagged_read_ranges.is_empty());
+        let mark: (i64, i64) = sqlx::query_as("SELECT start_position, end_position FROM cloze_notes WHERE id = ?")
+            .bind(mark_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(mark, (72, 78));
+        let read: (i64, i64) = sqlx::query_as("SELECT start_position, end_position FROM read_ranges WHERE text_id = ?")
+            .bind(text_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(read, (59, 79));
+        assert_eq!(fetch_excluded_ranges(&mut pool.acquire().await.unwrap(), text_id).await.unwrap(), vec![(59, 79)]);
+    }
+}
@@ -31,6 +31,24 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// Fresh migrated database in memory, for tests
+    #[cfg(test)]
+    pub async fn in_memory() -> Result<Self> {
+        // Every connection to ":memory:" is its own database, so keep just one
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .context("Failed to open in-memory database")?;
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .context("Failed to run migrations")?;
+
+        Ok(Self { pool })
+    }
+
     pub fn pool(&self) -> &Pool<Sqlite> {
         &self.pool
     }
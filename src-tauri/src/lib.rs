@@ -19,6 +19,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_os::init())
+        .manage(Mutex::new(services::cloze_suggester::LibraryWordIndex::default()))
         .setup(|app| {
             let app_data_dir = app
                 .path()
@@ -36,6 +37,14 @@ pub fn run() {
                     .await
                     .expect("Failed to initialize database");
 
+                // Texts that can't be converted keep their tags, which still render;
+                // that's no reason to keep the app from starting
+                if let Err(e) =
+                    commands::excluded_ranges::migrate_inline_exclude_tags(database.pool()).await
+                {
+                    eprintln!("Failed to convert inline exclude tags: {}", e);
+                }
+
                 app.manage(Arc::new(Mutex::new(database)));
             });
 
@@ -49,8 +58,23 @@ pub fn run() {
             commands::texts::get_text,
             commands::texts::rename_text,
             commands::texts::delete_text,
+            commands::texts::archive_text,
+            commands::texts::unarchive_text,
             commands::texts::update_text_content,
             commands::texts::update_text_with_smart_marks,
+            commands::text_revisions::list_text_revisions,
+            commands::text_revisions::get_text_revision_content,
+            commands::text_revisions::diff_text_revisions,
+            commands::text_revisions::restore_text_revision,
+            commands::extracts::create_extract,
+            commands::extracts::get_extract_tree,
+            commands::extracts::split_text_by_headers,
+            commands::reading_queue::add_to_reading_queue,
+            commands::reading_queue::remove_from_reading_queue,
+            commands::reading_queue::set_reading_priority,
+            commands::reading_queue::postpone_reading_item,
+            commands::reading_queue::get_reading_queue,
+            commands::reading_queue::get_next_reading_item,
             commands::texts::delete_marks,
             commands::texts::delete_read_ranges,
             commands::texts::get_smart_excerpt,
@@ -61,20 +85,35 @@ pub fn run() {
             commands::reading::get_paragraphs,
             commands::reading::get_next_unread_paragraph,
             commands::reading::get_previous_paragraph,
+            commands::reading::get_sentences,
+            commands::reading::get_next_unread_sentence,
+            commands::reading::get_previous_sentence,
+            commands::excluded_ranges::get_excluded_ranges,
+            commands::excluded_ranges::exclude_range,
+            commands::excluded_ranges::include_range,
             commands::reading::get_most_recently_read_text,
             commands::reading::clear_read_progress,
             commands::reading::start_reading_session,
             commands::reading::end_reading_session,
             commands::reading::get_countable_length,
+            commands::reading::get_text_outline,
             commands::flashcards::create_flashcard_from_cloze,
             commands::flashcards::get_flashcards_by_text,
             commands::flashcards::delete_flashcard,
+            commands::flashcards::edit_cloze_note,
             commands::flashcards::get_flashcard_preview,
+            commands::flashcards::validate_cloze_text,
+            commands::flashcards::get_duplicate_clusters,
             commands::flashcards::create_mark,
             commands::flashcards::get_marks_for_text,
             commands::flashcard_hub::get_hub_marks,
+            commands::flashcard_hub::suggest_clozes_for_mark,
             commands::flashcard_hub::skip_mark,
+            commands::flashcard_hub::set_mark_priority,
             commands::flashcard_hub::delete_mark,
+            commands::flashcard_hub::get_marks_needing_review,
+            commands::flashcard_hub::accept_mark_anchor,
+            commands::flashcard_hub::set_mark_range,
             commands::flashcard_hub::get_hub_stats,
             commands::flashcard_hub::create_card_from_mark,
             commands::review::get_due_cards,
@@ -88,6 +127,7 @@ pub fn run() {
             commands::review::get_study_limits,
             commands::review::update_study_limits,
             commands::review::bury_card,
+            commands::review::get_card_source_location,
             commands::folder::create_folder,
             commands::folder::get_folder_tree,
             commands::folder::rename_folder,
@@ -97,6 +137,12 @@ pub fn run() {
             commands::folder::get_texts_in_folder,
             commands::folder::calculate_folder_progress,
             commands::wikipedia::fetch_wikipedia_article,
+            commands::wikipedia::preview_wikipedia_refresh,
+            commands::wikipedia::refresh_wikipedia_article,
+            commands::web_article::fetch_web_article,
+            commands::import::import_files,
+            commands::import::import_epub,
+            commands::import::import_pdf,
             commands::settings::get_settings,
             commands::settings::update_setting,
             commands::settings::get_database_size,
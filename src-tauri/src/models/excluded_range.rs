@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedRange {
+    pub id: i64,
+    pub text_id: i64,
+    pub start_position: i64,
+    pub end_position: i64,
+}
@@ -2,7 +2,9 @@ pub mod text;
 pub mod flashcard;
 pub mod progress;
 pub mod read_range;
+pub mod excluded_range;
 pub mod paragraph;
+pub mod sentence;
 pub mod cloze_note;
 pub mod folder;
 pub mod study_filter;
@@ -12,6 +12,11 @@
 // - ingested_at: Timestamp when text was added
 // - updated_at: Last modification timestamp
 // - metadata: Optional JSON metadata
+// - language: Language code of the content (e.g. "de"), when known
+// - wiki_page_id/wiki_revision_id: MediaWiki page and revision imported, for refreshing
+// - parent_text_id: Text this one was extracted from, if any
+// - parent_start_position/parent_end_position: UTF-16 range it was copied from
+// - status: Lifecycle state (inbox, reading, finished, archived)
 //
 
 use chrono::{DateTime, Utc};
@@ -36,6 +41,9 @@ pub struct Text {
     pub access_date: Option<String>,
     pub doi: Option<String>,
     pub isbn: Option<String>,
+    pub language: Option<String>,
+    pub wiki_page_id: Option<i64>,
+    pub wiki_revision_id: Option<i64>,
 
     /// Foreign key reference to folders.id (TEXT/UUID format)
     ///
@@ -48,8 +56,18 @@ pub struct Text {
     ///
     /// See migration 20251015000002_fix_texts_folder_id.sql for schema details.
     pub folder_id: Option<String>,
+
+    /// Text this one was extracted from (incremental-reading extract)
+    pub parent_text_id: Option<i64>,
+    pub parent_start_position: Option<i64>,
+    pub parent_end_position: Option<i64>,
+
+    pub status: String, // 'inbox', 'reading', 'finished', 'archived'
 }
 
+/// Valid values of `texts.status`
+pub const TEXT_STATUSES: [&str; 4] = ["inbox", "reading", "finished", "archived"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTextRequest {
@@ -64,5 +82,8 @@ pub struct CreateTextRequest {
     pub access_date: Option<String>,
     pub doi: Option<String>,
     pub isbn: Option<String>,
+    pub language: Option<String>,
+    pub wiki_page_id: Option<i64>,
+    pub wiki_revision_id: Option<i64>,
     pub folder_id: Option<String>,
 }
@@ -22,35 +22,145 @@ pub struct ParsedCloze {
     pub segments: Vec<ClozeSegment>,
 }
 
-#[derive(Debug, Clone)]
+/// Parse failure with its location in UTF-16 code units (matching JavaScript's string indices)
+#[derive(Debug, Clone, PartialEq)]
 pub enum ClozeParseError {
-    EmptyCloze,
-    InvalidClozeNumber(String),
-    UnmatchedBraces,
-    NestedClozes,
+    EmptyCloze { start: usize, end: usize },
+    InvalidClozeNumber { number: String, start: usize, end: usize },
+    UnmatchedOpenBraces { start: usize, end: usize },
+    UnmatchedCloseBraces { start: usize, end: usize },
+    NestedClozes { start: usize, end: usize },
+}
+
+impl ClozeParseError {
+    pub fn range(&self) -> (usize, usize) {
+        match self {
+            ClozeParseError::EmptyCloze { start, end }
+            | ClozeParseError::InvalidClozeNumber { start, end, .. }
+            | ClozeParseError::UnmatchedOpenBraces { start, end }
+            | ClozeParseError::UnmatchedCloseBraces { start, end }
+            | ClozeParseError::NestedClozes { start, end } => (*start, *end),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ClozeParseError::EmptyCloze { .. } => "empty-cloze",
+            ClozeParseError::InvalidClozeNumber { .. } => "invalid-cloze-number",
+            ClozeParseError::UnmatchedOpenBraces { .. } => "unmatched-open-braces",
+            ClozeParseError::UnmatchedCloseBraces { .. } => "unmatched-close-braces",
+            ClozeParseError::NestedClozes { .. } => "nested-clozes",
+        }
+    }
+
+    pub fn suggestion(&self) -> String {
+        match self {
+            ClozeParseError::EmptyCloze { .. } => {
+                "Add the text to hide between '::' and '}}'".to_string()
+            }
+            ClozeParseError::InvalidClozeNumber { .. } => {
+                "Number clozes starting from c1".to_string()
+            }
+            ClozeParseError::UnmatchedOpenBraces { .. } => {
+                "Close the cloze with '}}'".to_string()
+            }
+            ClozeParseError::UnmatchedCloseBraces { .. } => {
+                "Remove the extra '}}' or open a cloze with '{{c1::'".to_string()
+            }
+            ClozeParseError::NestedClozes { .. } => {
+                "Close the outer cloze before starting a new one".to_string()
+            }
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> ClozeDiagnostic {
+        let (start, end) = self.range();
+        ClozeDiagnostic {
+            code: self.code(),
+            severity: DiagnosticSeverity::Error,
+            message: self.to_string(),
+            start,
+            end,
+            suggestion: Some(self.suggestion()),
+        }
+    }
 }
 
 impl std::fmt::Display for ClozeParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ClozeParseError::EmptyCloze => write!(f, "Cloze deletion contains empty text"),
-            ClozeParseError::InvalidClozeNumber(num) => {
-                write!(f, "Invalid cloze number: {}", num)
+            ClozeParseError::EmptyCloze { start, .. } => {
+                write!(f, "Cloze deletion at position {} contains empty text", start)
+            }
+            ClozeParseError::InvalidClozeNumber { number, start, .. } => {
+                write!(f, "Invalid cloze number at position {}: {}", start, number)
+            }
+            ClozeParseError::UnmatchedOpenBraces { start, .. } => {
+                write!(f, "Unmatched '{{{{' at position {}", start)
+            }
+            ClozeParseError::UnmatchedCloseBraces { start, .. } => {
+                write!(f, "Unmatched '}}}}' at position {}", start)
+            }
+            ClozeParseError::NestedClozes { start, .. } => {
+                write!(f, "Nested cloze deletion at position {} is not supported", start)
             }
-            ClozeParseError::UnmatchedBraces => write!(f, "Unmatched braces in cloze syntax"),
-            ClozeParseError::NestedClozes => write!(f, "Nested cloze deletions are not supported"),
         }
     }
 }
 
 impl std::error::Error for ClozeParseError {}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A lint result for cloze text, positioned in UTF-16 code units for the editor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClozeDiagnostic {
+    pub code: &'static str,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+    pub suggestion: Option<String>,
+}
+
+/// Brace structure of a text: balanced top-level `{{...}}` spans plus any structural errors
+/// Spans are byte offsets, errors carry UTF-16 offsets
+struct BraceScan {
+    spans: Vec<(usize, usize)>,
+    unmatched: Vec<ClozeParseError>,
+    nested: Vec<ClozeParseError>,
+}
+
+// Convert byte offset to UTF-16 code unit offset to match JavaScript's string indices
+fn utf16_offset(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset].encode_utf16().count()
+}
+
+/// Cloze numbers of an edited note compared against the cards that already exist for it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClozeNumberDiff {
+    pub kept: Vec<u32>,
+    pub removed: Vec<u32>,
+    pub added: Vec<u32>,
+}
+
 pub struct ClozeParser;
 
 impl ClozeParser {
     pub fn parse(text: &str) -> Result<ParsedCloze> {
-        Self::validate_braces(text)?;
-        Self::validate_no_nesting(text)?;
+        let scan = Self::scan_braces(text);
+        if let Some(error) = scan.unmatched.into_iter().next() {
+            return Err(anyhow!(error));
+        }
+        if let Some(error) = scan.nested.into_iter().next() {
+            return Err(anyhow!(error));
+        }
 
         let mut segments = Vec::new();
 
@@ -60,18 +170,22 @@ impl ClozeParser {
             let cloze_text = cap.get(2).unwrap().as_str();
             let hint = cap.get(3).map(|m| m.as_str().to_string());
 
-            let cloze_number = cloze_num_str
-                .parse::<u32>()
-                .map_err(|_| ClozeParseError::InvalidClozeNumber(cloze_num_str.to_string()))?;
-
-            if cloze_number == 0 {
-                return Err(anyhow!(ClozeParseError::InvalidClozeNumber(
-                    cloze_num_str.to_string()
-                )));
-            }
+            let start = utf16_offset(text, full_match.start());
+            let end = utf16_offset(text, full_match.end());
+
+            let cloze_number = match cloze_num_str.parse::<u32>() {
+                Ok(number) if number > 0 => number,
+                _ => {
+                    return Err(anyhow!(ClozeParseError::InvalidClozeNumber {
+                        number: cloze_num_str.to_string(),
+                        start,
+                        end,
+                    }));
+                }
+            };
 
             if cloze_text.trim().is_empty() {
-                return Err(anyhow!(ClozeParseError::EmptyCloze));
+                return Err(anyhow!(ClozeParseError::EmptyCloze { start, end }));
             }
 
             segments.push(ClozeSegment {
@@ -89,6 +203,195 @@ impl ClozeParser {
         })
     }
 
+    /// Parse cloze text that cards will be created from
+    ///
+    /// Fails like `parse`, and also when there is nothing to make a card of, with the
+    /// problem located for the editor.
+    pub fn parse_for_cards(text: &str) -> std::result::Result<ParsedCloze, ClozeDiagnostic> {
+        let parsed = Self::parse(text).map_err(|e| match e.downcast_ref::<ClozeParseError>() {
+            Some(error) => error.to_diagnostic(),
+            None => ClozeDiagnostic {
+                code: "invalid-cloze",
+                severity: DiagnosticSeverity::Error,
+                message: e.to_string(),
+                start: 0,
+                end: text.encode_utf16().count(),
+                suggestion: None,
+            },
+        })?;
+
+        if parsed.segments.is_empty() {
+            return Err(Self::no_clozes(text));
+        }
+        Ok(parsed)
+    }
+
+    fn no_clozes(text: &str) -> ClozeDiagnostic {
+        ClozeDiagnostic {
+            code: "no-clozes",
+            severity: DiagnosticSeverity::Error,
+            message: "No cloze deletions found in text".to_string(),
+            start: 0,
+            end: text.encode_utf16().count(),
+            suggestion: Some("Wrap the text to hide in {{c1::...}}".to_string()),
+        }
+    }
+
+    /// Lint cloze text without creating anything
+    ///
+    /// Returns every structural error (the first of which `parse` would fail on)
+    /// followed by warnings for text that parses but is probably not what was meant.
+    pub fn diagnose(text: &str) -> Vec<ClozeDiagnostic> {
+        let scan = Self::scan_braces(text);
+        let mut diagnostics: Vec<ClozeDiagnostic> = scan
+            .unmatched
+            .iter()
+            .chain(scan.nested.iter())
+            .map(|e| e.to_diagnostic())
+            .collect();
+
+        // Clozes inside broken brace structure can't be checked reliably
+        if !diagnostics.is_empty() {
+            return diagnostics;
+        }
+
+        let mut numbered: Vec<(u32, usize, usize)> = Vec::new();
+        let mut matched_spans = Vec::new();
+        let mut clozed_bytes = 0;
+
+        for cap in CLOZE_REGEX.captures_iter(text) {
+            let full_match = cap.get(0).unwrap();
+            let cloze_num_str = cap.get(1).unwrap().as_str();
+            let cloze_text = cap.get(2).unwrap().as_str();
+            let start = utf16_offset(text, full_match.start());
+            let end = utf16_offset(text, full_match.end());
+
+            matched_spans.push((full_match.start(), full_match.end()));
+            clozed_bytes += full_match.len();
+
+            match cloze_num_str.parse::<u32>() {
+                Ok(number) if number > 0 => numbered.push((number, start, end)),
+                _ => diagnostics.push(
+                    ClozeParseError::InvalidClozeNumber {
+                        number: cloze_num_str.to_string(),
+                        start,
+                        end,
+                    }
+                    .to_diagnostic(),
+                ),
+            }
+
+            if cloze_text.trim().is_empty() {
+                diagnostics.push(ClozeParseError::EmptyCloze { start, end }.to_diagnostic());
+            }
+
+            // An empty hint ("{{c1::Paris::}}") is swallowed into the answer by the regex
+            let hint_is_empty = cap.get(3).is_some_and(|h| h.as_str().trim().is_empty())
+                || cloze_text.ends_with("::");
+            if hint_is_empty {
+                diagnostics.push(ClozeDiagnostic {
+                    code: "empty-hint",
+                    severity: DiagnosticSeverity::Warning,
+                    message: format!("Cloze at position {} has an empty hint", start),
+                    start,
+                    end,
+                    suggestion: Some("Remove the trailing '::' or write a hint after it".to_string()),
+                });
+            }
+        }
+
+        for (span_start, span_end) in &scan.spans {
+            if !matched_spans.contains(&(*span_start, *span_end)) {
+                let start = utf16_offset(text, *span_start);
+                diagnostics.push(ClozeDiagnostic {
+                    code: "malformed-cloze",
+                    severity: DiagnosticSeverity::Warning,
+                    message: format!(
+                        "Braces at position {} are not a cloze and will be shown as plain text",
+                        start
+                    ),
+                    start,
+                    end: utf16_offset(text, *span_end),
+                    suggestion: Some("Write clozes as {{c1::answer}} or {{c1::answer::hint}}".to_string()),
+                });
+            }
+        }
+
+        if numbered.is_empty() {
+            if diagnostics.is_empty() {
+                diagnostics.push(Self::no_clozes(text));
+            }
+            return diagnostics;
+        }
+
+        diagnostics.extend(Self::numbering_gaps(&numbered));
+
+        // Everything outside the clozes is whitespace or punctuation - nothing left to cue recall
+        let has_context = text.len() > clozed_bytes && {
+            let mut outside = String::new();
+            let mut last = 0;
+            for (span_start, span_end) in &matched_spans {
+                outside.push_str(&text[last..*span_start]);
+                last = *span_end;
+            }
+            outside.push_str(&text[last..]);
+            outside.chars().any(|c| c.is_alphanumeric())
+        };
+        if !has_context {
+            let start = numbered.iter().map(|(_, s, _)| *s).min().unwrap_or(0);
+            let end = numbered.iter().map(|(_, _, e)| *e).max().unwrap_or(0);
+            diagnostics.push(ClozeDiagnostic {
+                code: "cloze-spans-whole-text",
+                severity: DiagnosticSeverity::Warning,
+                message: "Cloze covers the whole text, leaving no context to recall it from".to_string(),
+                start,
+                end,
+                suggestion: Some("Hide only the key term and keep the rest as context".to_string()),
+            });
+        }
+
+        diagnostics
+    }
+
+    fn numbering_gaps(numbered: &[(u32, usize, usize)]) -> Vec<ClozeDiagnostic> {
+        let mut numbers: Vec<u32> = numbered.iter().map(|(n, _, _)| *n).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+
+        let mut diagnostics = Vec::new();
+        let mut expected = 1;
+
+        for number in numbers {
+            if number > expected {
+                // Point at the first occurrence of the number that follows the gap
+                let (_, start, end) = numbered
+                    .iter()
+                    .filter(|(n, _, _)| *n == number)
+                    .min_by_key(|(_, s, _)| *s)
+                    .copied()
+                    .unwrap_or((number, 0, 0));
+
+                let missing = if number - expected == 1 {
+                    format!("c{}", expected)
+                } else {
+                    format!("c{} to c{}", expected, number - 1)
+                };
+
+                diagnostics.push(ClozeDiagnostic {
+                    code: "cloze-number-gap",
+                    severity: DiagnosticSeverity::Warning,
+                    message: format!("Cloze numbering skips {}", missing),
+                    start,
+                    end,
+                    suggestion: Some(format!("Renumber c{} to c{}", number, expected)),
+                });
+            }
+            expected = number + 1;
+        }
+
+        diagnostics
+    }
+
     pub fn extract_cloze_numbers(text: &str) -> Result<Vec<u32>> {
         let parsed = Self::parse(text)?;
         let mut numbers: Vec<u32> = parsed
@@ -101,6 +404,24 @@ impl ClozeParser {
         Ok(numbers)
     }
 
+    /// Compare the cloze numbers of existing cards with those of the edited text.
+    /// Both inputs may be unsorted or contain duplicates; outputs are sorted and unique.
+    pub fn diff_cloze_numbers(existing: &[u32], updated: &[u32]) -> ClozeNumberDiff {
+        let mut existing: Vec<u32> = existing.to_vec();
+        existing.sort_unstable();
+        existing.dedup();
+
+        let mut updated: Vec<u32> = updated.to_vec();
+        updated.sort_unstable();
+        updated.dedup();
+
+        ClozeNumberDiff {
+            kept: existing.iter().copied().filter(|n| updated.contains(n)).collect(),
+            removed: existing.iter().copied().filter(|n| !updated.contains(n)).collect(),
+            added: updated.iter().copied().filter(|n| !existing.contains(n)).collect(),
+        }
+    }
+
     pub fn has_cloze_deletions(text: &str) -> bool {
         CLOZE_REGEX.is_match(text)
     }
@@ -112,19 +433,36 @@ impl ClozeParser {
         }
     }
 
-    fn validate_braces(text: &str) -> Result<()> {
-        let mut depth = 0;
+    fn scan_braces(text: &str) -> BraceScan {
+        let bytes = text.as_bytes();
+        let mut open_stack: Vec<usize> = Vec::new();
+        let mut scan = BraceScan {
+            spans: Vec::new(),
+            unmatched: Vec::new(),
+            nested: Vec::new(),
+        };
         let mut i = 0;
-        let chars: Vec<char> = text.chars().collect();
 
-        while i < chars.len() {
-            if i + 1 < chars.len() && chars[i] == '{' && chars[i + 1] == '{' {
-                depth += 1;
+        // Braces are ASCII, so scanning bytes never splits a multi-byte character
+        while i + 1 < bytes.len() {
+            if bytes[i] == b'{' && bytes[i + 1] == b'{' {
+                if !open_stack.is_empty() {
+                    let start = utf16_offset(text, i);
+                    scan.nested.push(ClozeParseError::NestedClozes { start, end: start + 2 });
+                }
+                open_stack.push(i);
                 i += 2;
-            } else if i + 1 < chars.len() && chars[i] == '}' && chars[i + 1] == '}' {
-                depth -= 1;
-                if depth < 0 {
-                    return Err(anyhow!(ClozeParseError::UnmatchedBraces));
+            } else if bytes[i] == b'}' && bytes[i + 1] == b'}' {
+                match open_stack.pop() {
+                    Some(open) => {
+                        if open_stack.is_empty() {
+                            scan.spans.push((open, i + 2));
+                        }
+                    }
+                    None => {
+                        let start = utf16_offset(text, i);
+                        scan.unmatched.push(ClozeParseError::UnmatchedCloseBraces { start, end: start + 2 });
+                    }
                 }
                 i += 2;
             } else {
@@ -132,34 +470,13 @@ impl ClozeParser {
             }
         }
 
-        if depth != 0 {
-            return Err(anyhow!(ClozeParseError::UnmatchedBraces));
+        for open in open_stack {
+            let start = utf16_offset(text, open);
+            scan.unmatched.push(ClozeParseError::UnmatchedOpenBraces { start, end: start + 2 });
         }
+        scan.unmatched.sort_by_key(|e| e.range().0);
 
-        Ok(())
-    }
-
-    fn validate_no_nesting(text: &str) -> Result<()> {
-        let mut depth = 0;
-        let mut i = 0;
-        let chars: Vec<char> = text.chars().collect();
-
-        while i < chars.len() {
-            if i + 1 < chars.len() && chars[i] == '{' && chars[i + 1] == '{' {
-                depth += 1;
-                if depth > 1 {
-                    return Err(anyhow!(ClozeParseError::NestedClozes));
-                }
-                i += 2;
-            } else if i + 1 < chars.len() && chars[i] == '}' && chars[i + 1] == '}' {
-                depth -= 1;
-                i += 2;
-            } else {
-                i += 1;
-            }
-        }
-
-        Ok(())
+        scan
     }
 }
 
@@ -262,4 +579,125 @@ mod tests {
         let text = "{{c1::Paris}} is in {{c2::France}} and {{c1::Europe}}.";
         assert_eq!(ClozeParser::count_cloze_deletions(text), 2);
     }
+
+    #[test]
+    fn test_diff_cloze_numbers() {
+        let diff = ClozeParser::diff_cloze_numbers(&[1, 2, 3], &[3, 1, 4]);
+
+        assert_eq!(diff.kept, vec![1, 3]);
+        assert_eq!(diff.removed, vec![2]);
+        assert_eq!(diff.added, vec![4]);
+    }
+
+    #[test]
+    fn test_diff_cloze_numbers_unchanged() {
+        let diff = ClozeParser::diff_cloze_numbers(&[2, 1, 1], &[1, 2]);
+
+        assert_eq!(diff.kept, vec![1, 2]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.added.is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_location_utf16() {
+        let text = "👋 {{c1::Paris}";
+        let error = ClozeParser::parse(text).unwrap_err();
+        let error = error.downcast_ref::<ClozeParseError>().unwrap();
+
+        // 👋 is 2 UTF-16 units, followed by a space
+        assert_eq!(error, &ClozeParseError::UnmatchedOpenBraces { start: 3, end: 5 });
+    }
+
+    #[test]
+    fn test_diagnose_valid_text() {
+        let text = "{{c1::Paris}} is the capital of {{c2::France::country}}.";
+        assert!(ClozeParser::diagnose(text).is_empty());
+    }
+
+    #[test]
+    fn test_diagnose_unmatched_close() {
+        let text = "Paris}} is a city.";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "unmatched-close-braces");
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!((diagnostics[0].start, diagnostics[0].end), (5, 7));
+        assert!(diagnostics[0].suggestion.is_some());
+    }
+
+    #[test]
+    fn test_diagnose_nested() {
+        let text = "{{c1::The {{c2::nested}} text}}";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "nested-clozes");
+        assert_eq!(diagnostics[0].start, 10);
+    }
+
+    #[test]
+    fn test_diagnose_numbering_gap() {
+        let text = "{{c1::Paris}} is in {{c3::France}}.";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "cloze-number-gap");
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[0].start, 20);
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("Renumber c3 to c2"));
+    }
+
+    #[test]
+    fn test_diagnose_empty_hint() {
+        let text = "The capital is {{c1::Paris::}}.";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "empty-hint");
+    }
+
+    #[test]
+    fn test_diagnose_whole_text_cloze() {
+        let text = "{{c1::Paris is the capital of France}}.";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "cloze-spans-whole-text");
+    }
+
+    #[test]
+    fn test_diagnose_malformed_cloze() {
+        let text = "The capital is {{c1:Paris}} and {{c1::Lyon}}.";
+        let diagnostics = ClozeParser::diagnose(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "malformed-cloze");
+        assert_eq!((diagnostics[0].start, diagnostics[0].end), (15, 27));
+    }
+
+    #[test]
+    fn test_diagnose_no_clozes() {
+        let diagnostics = ClozeParser::diagnose("plain text");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "no-clozes");
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+    }
+
+    #[test]
+    fn test_parse_for_cards_locates_errors() {
+        let diagnostic = ClozeParser::parse_for_cards("👋 {{c1::Paris}").unwrap_err();
+        assert_eq!(diagnostic.code, "unmatched-open-braces");
+        assert_eq!((diagnostic.start, diagnostic.end), (3, 5));
+
+        let diagnostic = ClozeParser::parse_for_cards("{{c0::Paris}} is a city").unwrap_err();
+        assert_eq!(diagnostic.code, "invalid-cloze-number");
+
+        let diagnostic = ClozeParser::parse_for_cards("plain text").unwrap_err();
+        assert_eq!(diagnostic.code, "no-clozes");
+
+        let parsed = ClozeParser::parse_for_cards("{{c1::Paris}} is a city").unwrap();
+        assert_eq!(parsed.segments.len(), 1);
+    }
 }
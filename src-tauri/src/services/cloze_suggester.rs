@@ -0,0 +1,444 @@
+// Cloze suggestion service
+//
+// Proposes cloze deletion candidates for a marked passage using offline heuristics.
+//
+// Candidate kinds, from strongest to weakest signal:
+// - Terms being defined ("X is a ...", "X refers to ...")
+// - Full dates ("4 July 1776", "July 4, 1776", "1776-07-04")
+// - Years ("1066", "1990s", "44 BC")
+// - Capitalised multi-word proper nouns ("Battle of Hastings")
+// - Numbers and percentages
+// - Words that occur rarely in the rest of the library
+//
+// All positions are UTF-16 code units relative to the marked text, matching
+// JavaScript's string indices so the hub can apply a suggestion directly.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+const MONTHS: &str = "January|February|March|April|May|June|July|August|September|October|November|December";
+
+static DEFINED_TERM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:^|[.;:!?]\s+|,\s+)(?:(?:[Tt]he|[Aa]n?)\s+)?([\p{L}\p{N}][\p{L}\p{N}'-]*(?:\s+[\p{L}\p{N}][\p{L}\p{N}'-]*){0,3}?)\s+(?:(?:is|are|was|were)\s+(?:a|an|the|any)\b|refers?\s+to\b|is\s+defined\s+as\b)",
+    )
+    .unwrap()
+});
+
+static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"\b(?:\d{{1,2}}\s+(?:{m})\s+\d{{3,4}}|(?:{m})\s+\d{{1,2}},?\s+\d{{3,4}}|(?:{m})\s+\d{{3,4}}|\d{{4}}-\d{{2}}-\d{{2}})\b",
+        m = MONTHS
+    ))
+    .unwrap()
+});
+
+static YEAR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:\d{1,4}\s*(?:BC|BCE|AD|CE)|(?:1\d{3}|20\d{2})s?)\b").unwrap()
+});
+
+static NUMBER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\d{1,3}(?:,\d{3})+(?:\.\d+)?%?|\b\d+(?:\.\d+)?%?").unwrap()
+});
+
+static PROPER_NOUN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\p{Lu}[\p{L}'-]+(?:\s+(?:(?:of|the|de|da|von|van|der|du|la|le|and)\s+)*\p{Lu}[\p{L}'-]+)+")
+        .unwrap()
+});
+
+static WORD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\p{L}[\p{L}\p{M}'-]*").unwrap());
+
+/// Capitalised only because they start a sentence - never the start of a proper noun
+const LEADING_STOPWORDS: &[&str] = &[
+    "The", "A", "An", "In", "On", "At", "By", "For", "From", "After", "Before", "During",
+    "When", "While", "This", "That", "These", "Those", "His", "Her", "Its", "Their", "Our",
+];
+
+const COMMON_WORDS: &[&str] = &[
+    "about", "after", "against", "although", "another", "around", "because", "before",
+    "between", "during", "either", "however", "including", "itself", "neither", "other",
+    "others", "should", "something", "through", "therefore", "together", "towards", "whether",
+    "within", "without", "would", "people", "number", "became", "become", "called", "several",
+];
+
+/// Rare words must be at least this long to be worth hiding
+const MIN_RARE_WORD_LENGTH: usize = 6;
+
+/// Words seen at most this many times in the rest of the library count as rare
+const RARE_WORD_MAX_OCCURRENCES: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionKind {
+    DefinedTerm,
+    Date,
+    Year,
+    ProperNoun,
+    Number,
+    RareWord,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClozeSuggestion {
+    pub kind: SuggestionKind,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+    /// The marked text with this candidate wrapped as {{c1::...}}
+    pub cloze_text: String,
+}
+
+/// Candidate span in byte offsets, converted to UTF-16 once ranked
+struct Candidate {
+    kind: SuggestionKind,
+    start: usize,
+    end: usize,
+    score: f64,
+}
+
+pub struct ClozeSuggester;
+
+impl ClozeSuggester {
+    /// Rank cloze candidates in a marked passage
+    ///
+    /// `library_word_counts` holds occurrences of the mark's words in the rest of the library
+    /// (see `candidate_words`); rare-word suggestions are skipped when `library_text_count`
+    /// is zero since every word would look rare.
+    pub fn suggest(
+        marked_text: &str,
+        library_word_counts: &HashMap<String, usize>,
+        library_text_count: usize,
+        limit: usize,
+    ) -> Vec<ClozeSuggestion> {
+        let mut candidates = Vec::new();
+
+        for cap in DEFINED_TERM_REGEX.captures_iter(marked_text) {
+            if let Some(term) = cap.get(1) {
+                candidates.push(Candidate {
+                    kind: SuggestionKind::DefinedTerm,
+                    start: term.start(),
+                    end: term.end(),
+                    score: 0.95,
+                });
+            }
+        }
+
+        for m in DATE_REGEX.find_iter(marked_text) {
+            candidates.push(Candidate {
+                kind: SuggestionKind::Date,
+                start: m.start(),
+                end: m.end(),
+                score: 0.9,
+            });
+        }
+
+        for m in YEAR_REGEX.find_iter(marked_text) {
+            candidates.push(Candidate {
+                kind: SuggestionKind::Year,
+                start: m.start(),
+                end: m.end(),
+                score: 0.8,
+            });
+        }
+
+        for m in PROPER_NOUN_REGEX.find_iter(marked_text) {
+            let (start, phrase) = strip_leading_stopword(m.start(), m.as_str());
+            let word_count = phrase.split_whitespace().count();
+            if word_count < 2 {
+                continue;
+            }
+            candidates.push(Candidate {
+                kind: SuggestionKind::ProperNoun,
+                start,
+                end: m.end(),
+                score: (0.7 + 0.05 * word_count as f64).min(0.9),
+            });
+        }
+
+        for m in NUMBER_REGEX.find_iter(marked_text) {
+            candidates.push(Candidate {
+                kind: SuggestionKind::Number,
+                start: m.start(),
+                end: m.end(),
+                score: 0.6,
+            });
+        }
+
+        if library_text_count > 0 {
+            let mut seen = HashSet::new();
+            for m in WORD_REGEX.find_iter(marked_text) {
+                let word = m.as_str().to_lowercase();
+                if !is_rare_word_candidate(&word) || !seen.insert(word.clone()) {
+                    continue;
+                }
+                let occurrences = library_word_counts.get(&word).copied().unwrap_or(0);
+                if occurrences <= RARE_WORD_MAX_OCCURRENCES {
+                    let rarity = 1.0 - occurrences as f64 / (RARE_WORD_MAX_OCCURRENCES + 1) as f64;
+                    candidates.push(Candidate {
+                        kind: SuggestionKind::RareWord,
+                        start: m.start(),
+                        end: m.end(),
+                        score: 0.4 + 0.15 * rarity,
+                    });
+                }
+            }
+        }
+
+        // Strongest candidates claim their span first; weaker overlapping ones are dropped
+        candidates.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.start.cmp(&b.start))
+        });
+
+        let mut selected: Vec<Candidate> = Vec::new();
+        for candidate in candidates {
+            let overlaps = selected
+                .iter()
+                .any(|s| candidate.start < s.end && s.start < candidate.end);
+            if !overlaps && candidate.end > candidate.start {
+                selected.push(candidate);
+            }
+            if selected.len() >= limit {
+                break;
+            }
+        }
+
+        selected
+            .into_iter()
+            .map(|c| {
+                let text = marked_text[c.start..c.end].to_string();
+                ClozeSuggestion {
+                    kind: c.kind,
+                    start: marked_text[..c.start].encode_utf16().count(),
+                    end: marked_text[..c.end].encode_utf16().count(),
+                    score: c.score,
+                    cloze_text: format!(
+                        "{}{{{{c1::{}}}}}{}",
+                        &marked_text[..c.start],
+                        text,
+                        &marked_text[c.end..]
+                    ),
+                    text,
+                }
+            })
+            .collect()
+    }
+
+    /// Lowercased words of a passage that could become rare-word suggestions
+    pub fn candidate_words(marked_text: &str) -> HashSet<String> {
+        WORD_REGEX
+            .find_iter(marked_text)
+            .map(|m| m.as_str().to_lowercase())
+            .filter(|w| is_rare_word_candidate(w))
+            .collect()
+    }
+
+}
+
+/// Counts of the rare-word candidates in each library text, kept for the session so a
+/// suggestion only rescans texts that changed since the last one
+#[derive(Default)]
+pub struct LibraryWordIndex {
+    /// Text id -> (the text's `updated_at` when counted, word counts)
+    texts: HashMap<i64, (DateTime<Utc>, HashMap<String, usize>)>,
+}
+
+impl LibraryWordIndex {
+    /// Forget texts missing from `current` (id, updated_at) pairs and return the ids of
+    /// texts that are new or changed since they were counted, to be passed to `insert`
+    pub fn stale_texts(&mut self, current: &[(i64, DateTime<Utc>)]) -> Vec<i64> {
+        let ids: HashSet<i64> = current.iter().map(|&(id, _)| id).collect();
+        self.texts.retain(|id, _| ids.contains(id));
+
+        current
+            .iter()
+            .filter(|(id, updated_at)| self.texts.get(id).is_none_or(|(counted, _)| counted != updated_at))
+            .map(|&(id, _)| id)
+            .collect()
+    }
+
+    /// Count the candidate words of a text's content
+    pub fn insert(&mut self, text_id: i64, updated_at: DateTime<Utc>, content: &str) {
+        let mut counts = HashMap::new();
+        for m in WORD_REGEX.find_iter(content) {
+            let word = m.as_str().to_lowercase();
+            if is_rare_word_candidate(&word) {
+                *counts.entry(word).or_insert(0) += 1;
+            }
+        }
+        self.texts.insert(text_id, (updated_at, counts));
+    }
+
+    /// Occurrences of `words` across every text except `exclude_text_id`, with the
+    /// number of texts counted
+    pub fn counts(&self, words: &HashSet<String>, exclude_text_id: i64) -> (HashMap<String, usize>, usize) {
+        let mut counts = HashMap::new();
+        let mut text_count = 0;
+        for (&text_id, (_, text_counts)) in &self.texts {
+            if text_id == exclude_text_id {
+                continue;
+            }
+            text_count += 1;
+            for word in words {
+                if let Some(count) = text_counts.get(word) {
+                    *counts.entry(word.clone()).or_insert(0) += count;
+                }
+            }
+        }
+        (counts, text_count)
+    }
+}
+
+fn is_rare_word_candidate(word: &str) -> bool {
+    word.chars().count() >= MIN_RARE_WORD_LENGTH && !COMMON_WORDS.contains(&word)
+}
+
+/// Drop a sentence-initial stopword ("The Roman Empire" -> "Roman Empire")
+/// Returns the new byte start and the remaining phrase
+fn strip_leading_stopword(start: usize, phrase: &str) -> (usize, &str) {
+    let first_word = phrase.split_whitespace().next().unwrap_or("");
+
+    if LEADING_STOPWORDS.contains(&first_word) {
+        let rest = phrase[first_word.len()..].trim_start();
+        return (start + phrase.len() - rest.len(), rest);
+    }
+
+    (start, phrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(text: &str) -> Vec<ClozeSuggestion> {
+        ClozeSuggester::suggest(text, &HashMap::new(), 0, 10)
+    }
+
+    #[test]
+    fn test_suggests_defined_term_first() {
+        let suggestions = suggest("Photosynthesis is a process used by plants.");
+
+        assert_eq!(suggestions[0].kind, SuggestionKind::DefinedTerm);
+        assert_eq!(suggestions[0].text, "Photosynthesis");
+        assert_eq!(suggestions[0].cloze_text, "{{c1::Photosynthesis}} is a process used by plants.");
+    }
+
+    #[test]
+    fn test_defined_term_refers_to() {
+        let suggestions = suggest("In biology, mitosis refers to cell division.");
+
+        let term = suggestions
+            .iter()
+            .find(|s| s.kind == SuggestionKind::DefinedTerm)
+            .unwrap();
+        assert_eq!(term.text, "mitosis");
+    }
+
+    #[test]
+    fn test_date_beats_year_and_number() {
+        let suggestions = suggest("The declaration was signed on 4 July 1776 in Philadelphia.");
+
+        let date = suggestions.iter().find(|s| s.kind == SuggestionKind::Date).unwrap();
+        assert_eq!(date.text, "4 July 1776");
+        assert!(!suggestions.iter().any(|s| s.kind == SuggestionKind::Year));
+        assert!(!suggestions.iter().any(|s| s.kind == SuggestionKind::Number));
+    }
+
+    #[test]
+    fn test_year_and_number() {
+        let suggestions = suggest("By 1990 about 45% of households owned one.");
+
+        assert!(suggestions.iter().any(|s| s.kind == SuggestionKind::Year && s.text == "1990"));
+        assert!(suggestions.iter().any(|s| s.kind == SuggestionKind::Number && s.text == "45%"));
+    }
+
+    #[test]
+    fn test_proper_noun_strips_leading_article() {
+        let suggestions = suggest("The Roman Empire fell after the Battle of Adrianople.");
+
+        let nouns: Vec<&str> = suggestions
+            .iter()
+            .filter(|s| s.kind == SuggestionKind::ProperNoun)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert!(nouns.contains(&"Roman Empire"));
+        assert!(nouns.contains(&"Battle of Adrianople"));
+    }
+
+    #[test]
+    fn test_rare_words_use_library_counts() {
+        let text = "Tardigrades survive extreme dehydration.";
+        let words = ClozeSuggester::candidate_words(text);
+        let now = Utc::now();
+        let mut index = LibraryWordIndex::default();
+        assert_eq!(index.stale_texts(&[(1, now), (2, now), (3, now)]), vec![1, 2, 3]);
+        index.insert(1, now, "Extreme weather. Extreme sports. Extreme heat.");
+        index.insert(2, now, "They survive.");
+        index.insert(3, now, "Tardigrades are the text being marked.");
+        let (counts, text_count) = index.counts(&words, 3);
+
+        let suggestions = ClozeSuggester::suggest(text, &counts, text_count, 10);
+        let rare: Vec<&str> = suggestions
+            .iter()
+            .filter(|s| s.kind == SuggestionKind::RareWord)
+            .map(|s| s.text.as_str())
+            .collect();
+
+        assert!(rare.contains(&"Tardigrades"));
+        assert!(rare.contains(&"dehydration"));
+        assert!(!rare.contains(&"extreme"));
+    }
+
+    #[test]
+    fn test_library_index_recounts_only_changed_texts() {
+        let before = Utc::now();
+        let after = before + chrono::Duration::seconds(1);
+        let mut index = LibraryWordIndex::default();
+        index.stale_texts(&[(1, before), (2, before)]);
+        index.insert(1, before, "Tardigrades everywhere.");
+        index.insert(2, before, "Dehydration here.");
+
+        // Text 1 was edited and text 2 deleted since they were counted
+        assert_eq!(index.stale_texts(&[(1, after), (3, before)]), vec![1, 3]);
+        let words = ClozeSuggester::candidate_words("tardigrades dehydration");
+        let (counts, text_count) = index.counts(&words, 0);
+        assert_eq!(text_count, 1);
+        assert_eq!(counts.get("tardigrades"), Some(&1));
+        assert_eq!(counts.get("dehydration"), None);
+    }
+
+    #[test]
+    fn test_no_rare_words_without_library() {
+        let suggestions = suggest("Tardigrades survive extreme dehydration.");
+        assert!(!suggestions.iter().any(|s| s.kind == SuggestionKind::RareWord));
+    }
+
+    #[test]
+    fn test_positions_are_utf16() {
+        let suggestions = suggest("👋 By 1990 it ended.");
+        let year = suggestions.iter().find(|s| s.kind == SuggestionKind::Year).unwrap();
+
+        // 👋 is 2 UTF-16 units
+        assert_eq!((year.start, year.end), (6, 10));
+    }
+
+    #[test]
+    fn test_limit_and_ranking() {
+        let suggestions = ClozeSuggester::suggest(
+            "Photosynthesis is a process. In 1779 Jan Ingenhousz showed 3 results.",
+            &HashMap::new(),
+            0,
+            2,
+        );
+
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggestions[0].score >= suggestions[1].score);
+    }
+}
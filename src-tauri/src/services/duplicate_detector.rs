@@ -0,0 +1,313 @@
+// Duplicate detection service
+//
+// Finds near-duplicate cloze notes, e.g. the same fact marked in two different articles.
+//
+// Notes are compared on their text with cloze syntax stripped, normalized to lowercase
+// alphanumerics, using the Jaccard similarity of character trigrams. Trigrams tolerate
+// small wording and punctuation differences that exact matching would miss.
+
+use crate::services::cloze_renderer::ClozeRenderer;
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+
+/// Similarity at or above which two notes are reported as likely duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.7;
+
+const NGRAM_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMatch {
+    pub cloze_note_id: i64,
+    pub text_id: i64,
+    pub text_title: String,
+    pub note_text: String,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCluster {
+    pub notes: Vec<DuplicateMatch>,
+    /// Highest pairwise similarity inside the cluster
+    pub max_similarity: f64,
+}
+
+/// A note prepared for comparison
+pub struct NoteFingerprint {
+    pub cloze_note_id: i64,
+    pub text_id: i64,
+    pub text_title: String,
+    pub note_text: String,
+    grams: HashSet<String>,
+}
+
+impl NoteFingerprint {
+    pub fn new(cloze_note_id: i64, text_id: i64, text_title: String, cloze_text: &str) -> Self {
+        let note_text = ClozeRenderer::strip_cloze_syntax(cloze_text);
+        let grams = ngrams(&normalize(&note_text));
+        Self {
+            cloze_note_id,
+            text_id,
+            text_title,
+            note_text,
+            grams,
+        }
+    }
+
+    fn to_match(&self, similarity: f64) -> DuplicateMatch {
+        DuplicateMatch {
+            cloze_note_id: self.cloze_note_id,
+            text_id: self.text_id,
+            text_title: self.text_title.clone(),
+            note_text: self.note_text.clone(),
+            similarity,
+        }
+    }
+}
+
+/// Lowercase, replace everything but letters and digits with spaces, collapse whitespace
+pub fn normalize(text: &str) -> String {
+    let lowered: String = text
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_lowercase().next().unwrap_or(c) } else { ' ' })
+        .collect();
+    lowered.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn ngrams(normalized: &str) -> HashSet<String> {
+    let chars: Vec<char> = normalized.chars().collect();
+    if chars.is_empty() {
+        return HashSet::new();
+    }
+    if chars.len() < NGRAM_SIZE {
+        return HashSet::from([normalized.to_string()]);
+    }
+    chars
+        .windows(NGRAM_SIZE)
+        .map(|w| w.iter().collect())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(b).count();
+    let union = a.len() + b.len() - intersection;
+    intersection as f64 / union as f64
+}
+
+// Jaccard can never exceed the ratio of set sizes, so this is a cheap pre-filter
+fn size_ratio(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let (small, large) = if a.len() < b.len() { (a.len(), b.len()) } else { (b.len(), a.len()) };
+    if large == 0 {
+        0.0
+    } else {
+        small as f64 / large as f64
+    }
+}
+
+/// Notes similar to `candidate`, most similar first
+pub fn find_similar(
+    candidate: &NoteFingerprint,
+    notes: &[NoteFingerprint],
+    threshold: f64,
+) -> Vec<DuplicateMatch> {
+    let mut matches: Vec<DuplicateMatch> = notes
+        .iter()
+        .filter(|note| note.cloze_note_id != candidate.cloze_note_id)
+        .filter(|note| size_ratio(&candidate.grams, &note.grams) >= threshold)
+        .filter_map(|note| {
+            let score = jaccard(&candidate.grams, &note.grams);
+            (score >= threshold).then(|| note.to_match(score))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    matches
+}
+
+/// Group notes into clusters connected by pairwise similarity at or above `threshold`
+pub fn cluster(notes: &[NoteFingerprint], threshold: f64) -> Vec<DuplicateCluster> {
+    let mut parent: Vec<usize> = (0..notes.len()).collect();
+    let mut best_score = vec![0.0f64; notes.len()];
+
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        let mut node = i;
+        while parent[node] != root {
+            let next = parent[node];
+            parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    for i in 0..notes.len() {
+        for j in (i + 1)..notes.len() {
+            if size_ratio(&notes[i].grams, &notes[j].grams) < threshold {
+                continue;
+            }
+            let score = jaccard(&notes[i].grams, &notes[j].grams);
+            if score >= threshold {
+                best_score[i] = best_score[i].max(score);
+                best_score[j] = best_score[j].max(score);
+                let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+                if root_i != root_j {
+                    parent[root_j] = root_i;
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..notes.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let max_similarity = members.iter().map(|&i| best_score[i]).fold(0.0, f64::max);
+            DuplicateCluster {
+                notes: members.iter().map(|&i| notes[i].to_match(best_score[i])).collect(),
+                max_similarity,
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        b.max_similarity
+            .partial_cmp(&a.max_similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    clusters
+}
+
+/// Load every note that has been turned into cards, except `exclude_note_id`
+pub async fn load_note_fingerprints(
+    pool: &Pool<Sqlite>,
+    exclude_note_id: Option<i64>,
+) -> Result<Vec<NoteFingerprint>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT cn.id as "id!", cn.text_id, t.title, cn.original_text
+        FROM cloze_notes cn
+        INNER JOIN texts t ON cn.text_id = t.id
+        WHERE cn.cloze_count > 0
+          AND cn.id IS NOT ?
+        "#,
+        exclude_note_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| NoteFingerprint::new(row.id, row.text_id, row.title, &row.original_text))
+        .collect())
+}
+
+/// Existing notes that look like duplicates of `cloze_text`
+pub async fn find_duplicates_for_text(
+    pool: &Pool<Sqlite>,
+    cloze_text: &str,
+    exclude_note_id: Option<i64>,
+    threshold: f64,
+) -> Result<Vec<DuplicateMatch>> {
+    let notes = load_note_fingerprints(pool, exclude_note_id).await?;
+    let candidate = NoteFingerprint::new(exclude_note_id.unwrap_or(0), 0, String::new(), cloze_text);
+    Ok(find_similar(&candidate, &notes, threshold))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trigram Jaccard similarity of two texts after cloze stripping and normalization
+    fn similarity(a: &str, b: &str) -> f64 {
+        let a = ngrams(&normalize(&ClozeRenderer::strip_cloze_syntax(a)));
+        let b = ngrams(&normalize(&ClozeRenderer::strip_cloze_syntax(b)));
+        jaccard(&a, &b)
+    }
+
+    fn note(id: i64, text: &str) -> NoteFingerprint {
+        NoteFingerprint::new(id, id * 10, format!("Text {}", id), text)
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  The Eiffel-Tower, in PARIS!  "), "the eiffel tower in paris");
+    }
+
+    #[test]
+    fn test_similarity_ignores_cloze_syntax() {
+        let a = "The {{c1::Eiffel Tower}} was completed in {{c2::1889}}.";
+        let b = "The Eiffel Tower was completed in 1889";
+        assert!((similarity(a, b) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_similarity_near_duplicate() {
+        let a = "The Eiffel Tower was completed in 1889 for the World's Fair.";
+        let b = "The Eiffel Tower was completed in 1889 for the Exposition Universelle.";
+        let c = "Mitochondria are the powerhouse of the cell.";
+
+        assert!(similarity(a, b) > 0.5);
+        assert!(similarity(a, c) < 0.2);
+    }
+
+    #[test]
+    fn test_find_similar_sorted_and_excludes_self() {
+        let candidate = note(1, "The {{c1::Eiffel Tower}} was completed in 1889.");
+        let notes = vec![
+            note(1, "The Eiffel Tower was completed in 1889."),
+            note(2, "The Eiffel Tower was finished in 1889."),
+            note(3, "The {{c1::Eiffel Tower}} was completed in {{c2::1889}}!"),
+            note(4, "Paris is the capital of France."),
+        ];
+
+        let matches = find_similar(&candidate, &notes, 0.6);
+        let ids: Vec<i64> = matches.iter().map(|m| m.cloze_note_id).collect();
+
+        assert_eq!(ids, vec![3, 2]);
+        assert_eq!(matches[0].note_text, "The Eiffel Tower was completed in 1889!");
+    }
+
+    #[test]
+    fn test_cluster_groups_transitively() {
+        let notes = vec![
+            note(1, "The Eiffel Tower was completed in 1889."),
+            note(2, "The Eiffel Tower was completed in 1889!"),
+            note(3, "Paris is the capital of France."),
+            note(4, "Paris is the capital city of France."),
+            note(5, "Mitochondria are the powerhouse of the cell."),
+        ];
+
+        let clusters = cluster(&notes, 0.7);
+        assert_eq!(clusters.len(), 2);
+
+        let mut groups: Vec<Vec<i64>> = clusters
+            .iter()
+            .map(|c| {
+                let mut ids: Vec<i64> = c.notes.iter().map(|n| n.cloze_note_id).collect();
+                ids.sort();
+                ids
+            })
+            .collect();
+        groups.sort();
+        assert_eq!(groups, vec![vec![1, 2], vec![3, 4]]);
+    }
+}
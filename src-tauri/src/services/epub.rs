@@ -0,0 +1,408 @@
+// EPUB import service
+//
+// Reads an EPUB (2 or 3) into book metadata and one chapter per spine document.
+//
+// - META-INF/container.xml points at the OPF package document, whose Dublin Core
+//   metadata gives the title, authors, publisher, ISBN, date and language. Creators with a role
+//   other than author (editors, illustrators) are left out.
+// - Chapters follow the spine; non-linear items (notes, answer keys) and the EPUB 3
+//   navigation document are skipped, as are documents without text (cover pages).
+// - Chapter XHTML goes through `wikipedia::html_to_plain_text`, like imported HTML files.
+// - Chapter titles come from the table of contents (EPUB 3 nav or EPUB 2 NCX), then the
+//   chapter's first heading.
+
+use crate::services::file_import::{normalize_content, normalize_date};
+use crate::services::wikipedia::html_to_plain_text;
+use anyhow::{anyhow, Context, Result};
+use roxmltree::{Document, Node, ParsingOptions};
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+const CHAPTER_MEDIA_TYPES: &[&str] = &["application/xhtml+xml", "text/html"];
+
+/// MARC relator codes counted as authors
+const AUTHOR_ROLES: &[&str] = &["aut", "author"];
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EpubMetadata {
+    pub title: String,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub isbn: Option<String>,
+    pub publication_date: Option<String>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpubChapter {
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpubBook {
+    pub metadata: EpubMetadata,
+    pub chapters: Vec<EpubChapter>,
+}
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+/// Parse an EPUB file's bytes into its metadata and chapters
+pub fn parse_epub(bytes: &[u8]) -> Result<EpubBook> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("Not a valid EPUB archive")?;
+
+    let container = read_entry(&mut archive, CONTAINER_PATH)?;
+    let container = parse_xml(&container)?;
+    let opf_path = container
+        .descendants()
+        .find(|n| n.has_tag_name("rootfile") && attr(*n, "media-type") != Some("application/x-dtbook+xml"))
+        .and_then(|n| attr(n, "full-path"))
+        .ok_or_else(|| anyhow!("EPUB container has no package document"))?
+        .to_string();
+    let opf_dir = parent_dir(&opf_path);
+
+    let opf = read_entry(&mut archive, &opf_path)?;
+    let opf = parse_xml(&opf)?;
+    let package = opf.root_element();
+
+    let metadata = parse_metadata(package);
+
+    let manifest: HashMap<&str, ManifestItem> = child(package, "manifest")
+        .map(|manifest| {
+            manifest
+                .children()
+                .filter(|n| n.has_tag_name("item"))
+                .filter_map(|n| {
+                    Some((
+                        attr(n, "id")?,
+                        ManifestItem {
+                            href: resolve_href(opf_dir, attr(n, "href")?),
+                            media_type: attr(n, "media-type").unwrap_or("").to_lowercase(),
+                            properties: attr(n, "properties").unwrap_or("").to_string(),
+                        },
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let spine = child(package, "spine").ok_or_else(|| anyhow!("EPUB package has no spine"))?;
+    let toc_titles = read_toc_titles(&mut archive, &manifest, spine);
+
+    let mut chapters = Vec::new();
+    for itemref in spine.children().filter(|n| n.has_tag_name("itemref")) {
+        if attr(itemref, "linear") == Some("no") {
+            continue;
+        }
+        let Some(item) = attr(itemref, "idref").and_then(|id| manifest.get(id)) else {
+            continue;
+        };
+        if !CHAPTER_MEDIA_TYPES.contains(&item.media_type.as_str()) || has_property(item, "nav") {
+            continue;
+        }
+
+        let xhtml = read_entry(&mut archive, &item.href)?;
+        let (heading, content) = chapter_text(&xhtml)?;
+        if content.is_empty() {
+            continue;
+        }
+
+        let title = toc_titles
+            .get(item.href.as_str())
+            .cloned()
+            .or(heading)
+            .unwrap_or_else(|| format!("Chapter {}", chapters.len() + 1));
+        chapters.push(EpubChapter { title, content });
+    }
+
+    if chapters.is_empty() {
+        return Err(anyhow!("EPUB has no chapters with text"));
+    }
+
+    Ok(EpubBook { metadata, chapters })
+}
+
+fn parse_metadata(package: Node) -> EpubMetadata {
+    let Some(metadata) = child(package, "metadata") else {
+        return EpubMetadata::default();
+    };
+    let elements: Vec<Node> = metadata.children().filter(|n| n.is_element()).collect();
+
+    // EPUB 3 attaches properties to elements with <meta refines="#id" property="...">
+    let refinement = |node: Node, property: &str| {
+        let id = attr(node, "id")?;
+        elements
+            .iter()
+            .find(|meta| {
+                meta.has_tag_name("meta")
+                    && attr(**meta, "refines").map(|r| r.trim_start_matches('#')) == Some(id)
+                    && attr(**meta, "property") == Some(property)
+            })
+            .and_then(|meta| meta.text())
+            .map(|text| text.trim().to_lowercase())
+    };
+    let dc = |name: &'static str| elements.iter().filter(move |n| n.has_tag_name(name));
+    let text_of = |node: &Node| node.text().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let title = dc("title")
+        .find(|n| refinement(**n, "title-type").is_none_or(|t| t == "main"))
+        .and_then(text_of)
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let authors: Vec<String> = dc("creator")
+        .filter(|n| {
+            let role = attr(**n, "role").map(|r| r.to_lowercase()).or_else(|| refinement(**n, "role"));
+            role.is_none_or(|r| AUTHOR_ROLES.contains(&r.as_str()))
+        })
+        .filter_map(text_of)
+        .collect();
+
+    let isbn = dc("identifier").find_map(|n| {
+        let value = text_of(n)?;
+        let is_isbn_scheme = attr(*n, "scheme").is_some_and(|s| s.eq_ignore_ascii_case("isbn"));
+        let value = value
+            .strip_prefix("urn:isbn:")
+            .or_else(|| value.strip_prefix("isbn:"))
+            .map(str::to_string)
+            .unwrap_or(value);
+        let compact: String = value.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        (is_isbn_scheme || looks_like_isbn(&compact)).then_some(compact)
+    });
+
+    // Prefer the publication event in EPUB 2, where several dates can be listed
+    let date = dc("date")
+        .find(|n| attr(**n, "event").is_none_or(|e| e == "publication"))
+        .and_then(text_of);
+
+    EpubMetadata {
+        title,
+        author: (!authors.is_empty()).then(|| authors.join(", ")),
+        publisher: dc("publisher").find_map(text_of),
+        isbn,
+        publication_date: date.map(|d| normalize_date(&d)),
+        language: dc("language").find_map(text_of).map(|l| l.to_lowercase()),
+    }
+}
+
+fn looks_like_isbn(compact: &str) -> bool {
+    let digits = compact.chars().filter(|c| c.is_ascii_digit()).count();
+    match compact.len() {
+        13 => digits == 13 && (compact.starts_with("978") || compact.starts_with("979")),
+        10 => digits >= 9 && compact[..9].chars().all(|c| c.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+/// Table of contents labels by document path, from the EPUB 3 nav document or the
+/// EPUB 2 NCX. The first label pointing into a document is its title.
+fn read_toc_titles<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    manifest: &HashMap<&str, ManifestItem>,
+    spine: Node,
+) -> HashMap<String, String> {
+    let mut titles = HashMap::new();
+
+    if let Some(nav) = manifest.values().find(|item| has_property(item, "nav")) {
+        if let Ok(xhtml) = read_entry(archive, &nav.href) {
+            let document = Html::parse_document(&xhtml);
+            let toc_selector = Selector::parse("nav").unwrap();
+            let link_selector = Selector::parse("a[href]").unwrap();
+            let toc = document
+                .select(&toc_selector)
+                .find(|n| n.value().attr("epub:type").is_some_and(|t| t.split_whitespace().any(|t| t == "toc")));
+            for link in toc.iter().flat_map(|toc| toc.select(&link_selector)) {
+                let label = link.text().collect::<String>().trim().to_string();
+                let href = resolve_href(parent_dir(&nav.href), link.value().attr("href").unwrap_or(""));
+                if !label.is_empty() {
+                    titles.entry(href).or_insert(label);
+                }
+            }
+        }
+    }
+
+    let ncx = attr(spine, "toc")
+        .and_then(|id| manifest.get(id))
+        .or_else(|| manifest.values().find(|item| item.media_type == "application/x-dtbncx+xml"));
+    if let Some(ncx) = ncx.filter(|_| titles.is_empty()) {
+        if let Ok(xml) = read_entry(archive, &ncx.href) {
+            if let Ok(document) = parse_xml(&xml) {
+                for nav_point in document.descendants().filter(|n| n.has_tag_name("navPoint")) {
+                    let label = child(nav_point, "navLabel")
+                        .and_then(|l| child(l, "text"))
+                        .and_then(|t| t.text())
+                        .map(|t| t.trim().to_string())
+                        .filter(|t| !t.is_empty());
+                    let src = child(nav_point, "content").and_then(|c| attr(c, "src"));
+                    if let (Some(label), Some(src)) = (label, src) {
+                        titles.entry(resolve_href(parent_dir(&ncx.href), src)).or_insert(label);
+                    }
+                }
+            }
+        }
+    }
+
+    titles
+}
+
+/// A chapter's first heading and its text
+fn chapter_text(xhtml: &str) -> Result<(Option<String>, String)> {
+    let document = Html::parse_document(xhtml);
+
+    let heading_selector = Selector::parse("h1, h2, h3").unwrap();
+    let heading = document
+        .select(&heading_selector)
+        .map(|h| h.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|h| !h.is_empty());
+
+    let body_selector = Selector::parse("body").unwrap();
+    let body_html = document
+        .select(&body_selector)
+        .next()
+        .map(|body| body.inner_html())
+        .unwrap_or_default();
+    let content = normalize_content(&html_to_plain_text(&body_html)?);
+
+    Ok((heading, content))
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>, path: &str) -> Result<String> {
+    let mut entry = archive
+        .by_name(path)
+        .with_context(|| format!("EPUB is missing {}", path))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .with_context(|| format!("Failed to read {}", path))?;
+    Ok(content)
+}
+
+fn parse_xml(xml: &str) -> Result<Document<'_>> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(xml, options).context("Invalid XML in EPUB")
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// An attribute by local name, whatever its namespace ("opf:role" and "role")
+fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes().find(|a| a.name() == name).map(|a| a.value())
+}
+
+fn has_property(item: &ManifestItem, property: &str) -> bool {
+    item.properties.split_whitespace().any(|p| p == property)
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map_or("", |i| &path[..=i])
+}
+
+/// Resolve an href relative to `base_dir` into an archive path, dropping any fragment
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or("");
+    let href = urlencoding::decode(href).map(|h| h.into_owned()).unwrap_or_else(|_| href.to_string());
+
+    let mut segments: Vec<&str> = base_dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPUB2: &[u8] = include_bytes!("../../tests/fixtures/epub/little_atlas_epub2.epub");
+    const EPUB3: &[u8] = include_bytes!("../../tests/fixtures/epub/night_trains_epub3.epub");
+
+    #[test]
+    fn test_epub2_metadata_and_ncx_titles() {
+        let book = parse_epub(EPUB2).unwrap();
+        assert_eq!(
+            book.metadata,
+            EpubMetadata {
+                title: "The Little Atlas".to_string(),
+                author: Some("Jane Doe".to_string()),
+                publisher: Some("Fixture Press".to_string()),
+                isbn: Some("9780306406157".to_string()),
+                publication_date: Some("2011-03-15".to_string()),
+                language: Some("en".to_string()),
+            }
+        );
+
+        // The image-only cover and the non-linear notes are skipped
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Mountains", "Rivers"]);
+        assert_eq!(
+            book.chapters[0].content,
+            "== Chapter 1: Mountains ==\n\nMountains cover a fifth of the land.\n\n== The Alps ==\n\nThe Alps stretch across eight countries."
+        );
+        assert_eq!(
+            book.chapters[1].content,
+            "== Chapter 2 ==\n\nRivers shape valleys.\n\n> All rivers run to the sea."
+        );
+    }
+
+    #[test]
+    fn test_epub3_refinements_and_nav() {
+        let book = parse_epub(EPUB3).unwrap();
+        assert_eq!(book.metadata.title, "Night Trains");
+        assert_eq!(book.metadata.author.as_deref(), Some("Ana Author, Ben Writer"));
+        assert_eq!(book.metadata.publisher, None);
+        assert_eq!(book.metadata.isbn.as_deref(), Some("9781234567897"));
+        assert_eq!(book.metadata.publication_date.as_deref(), Some("2019-05-01"));
+
+        // The nav document isn't a chapter; the interlude isn't in the TOC
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Departure", "Between Stations"]);
+        assert_eq!(
+            book.chapters[0].content,
+            "== Departure ==\n\nThe train left at midnight.\n\nNobody waved."
+        );
+    }
+
+    #[test]
+    fn test_chapter_text_keeps_lists_tables_and_definitions() {
+        let xhtml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Glossary</title></head><body>
+<h2>Glossary</h2>
+<dl><dt>Sleeper</dt><dd>A train with beds.</dd><dd>Also a railway tie.</dd></dl>
+<ul><li>Couchette<ul><li>six berths</li></ul></li></ul>
+<table><tr><th>Route</th><th>Hours</th></tr><tr><td>Paris–Vienna</td><td>14</td></tr></table>
+</body></html>"#;
+        let (heading, content) = chapter_text(xhtml).unwrap();
+        assert_eq!(heading.as_deref(), Some("Glossary"));
+        assert_eq!(
+            content,
+            "== Glossary ==\n\nSleeper\n: A train with beds.\n: Also a railway tie.\n\n\
+             - Couchette\n  - six berths\n\n| Route | Hours |\n| --- | --- |\n| Paris–Vienna | 14 |"
+        );
+    }
+
+    #[test]
+    fn test_resolve_href_and_invalid_archive() {
+        assert_eq!(resolve_href("OEBPS/", "Text/chapter%201.xhtml#a"), "OEBPS/Text/chapter 1.xhtml");
+        assert_eq!(resolve_href("OEBPS/Text/", "../Styles/a.css"), "OEBPS/Styles/a.css");
+        assert_eq!(resolve_href("", "./one.xhtml"), "one.xhtml");
+        assert!(parse_epub(b"not a zip").is_err());
+    }
+}
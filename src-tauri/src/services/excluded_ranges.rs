@@ -0,0 +1,228 @@
+// Excluded ranges service
+//
+// Range arithmetic for the parts of a text left out of reading progress and
+// navigation (reference lists, navboxes, boilerplate), and conversion of the legacy
+// inline "[[exclude]]...[[/exclude]]" tags into ranges.
+//
+// Excluded ranges are UTF-16 (start, end) pairs in rendered (DOM space) positions, like
+// read ranges. A text's stored ranges are kept merged: sorted, with no two ranges
+// overlapping or touching.
+
+use crate::models::read_range::ReadRange;
+use crate::services::range_calculator::RangeCalculator;
+use regex::Regex;
+use std::sync::LazyLock;
+
+static INLINE_TAG_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)\[\[exclude\]\](.*?)\[\[/exclude\]\]").unwrap());
+
+/// Sort and merge overlapping or touching ranges, dropping empty ones
+pub fn merge_ranges(mut ranges: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    ranges.retain(|(start, end)| end > start);
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// `existing` with `start..end` excluded as well
+pub fn add_range(existing: &[(i64, i64)], start: i64, end: i64) -> Vec<(i64, i64)> {
+    let mut ranges = existing.to_vec();
+    ranges.push((start, end));
+    merge_ranges(ranges)
+}
+
+/// `existing` with `start..end` included again, splitting a range that spans it
+pub fn remove_range(existing: &[(i64, i64)], start: i64, end: i64) -> Vec<(i64, i64)> {
+    let mut ranges = Vec::with_capacity(existing.len() + 1);
+    for &(range_start, range_end) in existing {
+        if range_end <= start || range_start >= end {
+            ranges.push((range_start, range_end));
+            continue;
+        }
+        if range_start < start {
+            ranges.push((range_start, start));
+        }
+        if range_end > end {
+            ranges.push((end, range_end));
+        }
+    }
+    merge_ranges(ranges)
+}
+
+/// `ranges` after an edit replaced `edit_start..edit_end` and changed the text length
+/// by `length_delta`
+///
+/// An edit within a range stays excluded, as text typed between inline exclude tags
+/// did. Otherwise ranges follow read ranges: kept before the edit, shifted after it,
+/// and trimmed to the parts outside an edit they only partly overlap.
+pub fn apply_edit(ranges: &[(i64, i64)], edit_start: i64, edit_end: i64, length_delta: i64) -> Vec<(i64, i64)> {
+    let mut edited = Vec::with_capacity(ranges.len() + 1);
+    for &(start, end) in ranges {
+        let within = if edit_start == edit_end {
+            start < edit_start && edit_start < end
+        } else {
+            start <= edit_start && edit_end <= end
+        };
+
+        if within {
+            edited.push((start, end + length_delta));
+        } else {
+            edited.extend(RangeCalculator::apply_edit_to_range(start, end, edit_start, edit_end, length_delta));
+        }
+    }
+    merge_ranges(edited)
+}
+
+/// The parts of `ranges` inside `start..end`, rebased to `start`
+pub fn slice_ranges(ranges: &[(i64, i64)], start: i64, end: i64) -> Vec<(i64, i64)> {
+    merge_ranges(
+        ranges
+            .iter()
+            .map(|&(range_start, range_end)| (range_start.max(start) - start, range_end.min(end) - start))
+            .collect(),
+    )
+}
+
+/// Total length covered by merged ranges
+pub fn covered_length(ranges: &[(i64, i64)]) -> i64 {
+    ranges.iter().map(|(start, end)| end - start).sum()
+}
+
+pub fn is_excluded(position: i64, ranges: &[(i64, i64)]) -> bool {
+    ranges.iter().any(|&(start, end)| position >= start && position < end)
+}
+
+/// Length of the overlap between two sets of merged ranges
+pub fn overlap_length(a: &[(i64, i64)], b: &[(i64, i64)]) -> i64 {
+    a.iter()
+        .flat_map(|&(a_start, a_end)| {
+            b.iter().map(move |&(b_start, b_end)| (a_end.min(b_end) - a_start.max(b_start)).max(0))
+        })
+        .sum()
+}
+
+/// Read characters outside the excluded ranges, counting overlapping reads once
+pub fn countable_read_length(read_ranges: &[ReadRange], excluded: &[(i64, i64)]) -> i64 {
+    let read = merge_ranges(
+        read_ranges
+            .iter()
+            .map(|r| (r.start_position, r.end_position))
+            .collect(),
+    );
+    covered_length(&read) - overlap_length(&read, excluded)
+}
+
+/// Remove inline exclude tags, keeping their inner text
+///
+/// Returns the content without tags and the inner texts' UTF-16 ranges in it, or None
+/// if there are no tags.
+pub fn strip_inline_tags(content: &str) -> Option<(String, Vec<(i64, i64)>)> {
+    if !INLINE_TAG_REGEX.is_match(content) {
+        return None;
+    }
+
+    let mut cleaned = String::with_capacity(content.len());
+    let mut ranges = Vec::new();
+    let mut cleaned_len = 0i64;
+    let mut last = 0;
+
+    for caps in INLINE_TAG_REGEX.captures_iter(content) {
+        let tag = caps.get(0).unwrap();
+        let inner = caps.get(1).unwrap().as_str();
+
+        let before = &content[last..tag.start()];
+        cleaned.push_str(before);
+        cleaned_len += before.encode_utf16().count() as i64;
+
+        let inner_len = inner.encode_utf16().count() as i64;
+        cleaned.push_str(inner);
+        ranges.push((cleaned_len, cleaned_len + inner_len));
+        cleaned_len += inner_len;
+
+        last = tag.end();
+    }
+    cleaned.push_str(&content[last..]);
+
+    Some((cleaned, ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove_ranges() {
+        let ranges = add_range(&[(0, 5), (10, 15)], 4, 11);
+        assert_eq!(ranges, vec![(0, 15)]);
+        assert_eq!(add_range(&ranges, 15, 20), vec![(0, 20)]);
+        assert_eq!(add_range(&ranges, 30, 30), vec![(0, 15)]);
+
+        assert_eq!(remove_range(&ranges, 5, 10), vec![(0, 5), (10, 15)]);
+        assert_eq!(remove_range(&ranges, 0, 100), vec![]);
+        assert_eq!(remove_range(&[(0, 5), (10, 15)], 3, 12), vec![(0, 3), (12, 15)]);
+    }
+
+    #[test]
+    fn test_apply_edit() {
+        let ranges = [(10, 20), (30, 40)];
+        // Typing inside a range grows it, deleting inside shrinks it
+        assert_eq!(apply_edit(&ranges, 15, 15, 3), vec![(10, 23), (33, 43)]);
+        assert_eq!(apply_edit(&ranges, 12, 18, -6), vec![(10, 14), (24, 34)]);
+        // Inserting at a boundary leaves the new text included
+        assert_eq!(apply_edit(&ranges, 20, 20, 2), vec![(10, 20), (32, 42)]);
+        assert_eq!(apply_edit(&ranges, 30, 30, 2), vec![(10, 20), (32, 42)]);
+        // An edit straddling a range's edge trims it
+        assert_eq!(apply_edit(&ranges, 25, 35, 0), vec![(10, 20), (35, 40)]);
+        assert_eq!(apply_edit(&ranges, 5, 45, -30), vec![]);
+    }
+
+    #[test]
+    fn test_slice_ranges() {
+        let ranges = [(10, 20), (30, 40)];
+        assert_eq!(slice_ranges(&ranges, 15, 35), vec![(0, 5), (15, 20)]);
+        assert_eq!(slice_ranges(&ranges, 20, 30), vec![]);
+        assert_eq!(slice_ranges(&ranges, 0, 100), vec![(10, 20), (30, 40)]);
+    }
+
+    #[test]
+    fn test_lengths_and_overlap() {
+        let excluded = [(10, 20), (30, 40)];
+        assert_eq!(covered_length(&excluded), 20);
+        assert!(is_excluded(10, &excluded) && !is_excluded(20, &excluded));
+        assert_eq!(overlap_length(&[(0, 15), (35, 50)], &excluded), 10);
+        assert_eq!(overlap_length(&[], &excluded), 0);
+    }
+
+    #[test]
+    fn test_countable_read_length() {
+        let read = |start, end| ReadRange {
+            id: 0,
+            text_id: 1,
+            user_id: 1,
+            start_position: start,
+            end_position: end,
+            marked_at: chrono::Utc::now(),
+            is_auto_completed: false,
+        };
+        // 0..25 read (overlapping marks), 10..20 excluded
+        let ranges = [read(0, 15), read(5, 25), read(40, 45)];
+        assert_eq!(countable_read_length(&ranges, &[(10, 20)]), 20);
+        assert_eq!(countable_read_length(&ranges, &[]), 30);
+    }
+
+    #[test]
+    fn test_strip_inline_tags_across_lines_with_utf16() {
+        let (cleaned, ranges) =
+            strip_inline_tags("Hi 👋 [[exclude]]refs\nmore[[/exclude]] end [[exclude]]世界[[/exclude]]").unwrap();
+        assert_eq!(cleaned, "Hi 👋 refs\nmore end 世界");
+        assert_eq!(ranges, vec![(6, 15), (20, 22)]);
+        assert!(strip_inline_tags("No tags here").is_none());
+    }
+}
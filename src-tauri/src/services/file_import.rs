@@ -0,0 +1,322 @@
+// File import service
+//
+// Turns local Markdown, plain text and HTML files into text content plus the metadata
+// the library stores (title, author, publication date).
+//
+// - Markdown keeps its headings and links as they are. YAML front matter supplies the
+//   metadata and is removed; without a front-matter title the first "# " heading is used.
+// - HTML goes through the same scraper pipeline as Wikipedia articles
+//   (`wikipedia::html_to_plain_text`), with metadata from <title> and <meta> tags.
+// - Plain text is used as it is.
+//
+// The file name without its extension is the fallback title.
+
+use crate::services::parser::detect_header_ranges;
+use crate::services::wikipedia::html_to_plain_text;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::path::Path;
+use std::sync::LazyLock;
+
+static EXTRA_BLANK_LINES_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+
+static ISO_DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}").unwrap());
+
+/// <meta> names/properties holding the author, in order of preference
+pub const AUTHOR_META_NAMES: &[&str] = &["author", "article:author", "dc.creator", "citation_author"];
+
+/// <meta> names/properties holding the publication date, in order of preference
+pub const DATE_META_NAMES: &[&str] = &[
+    "article:published_time",
+    "citation_publication_date",
+    "dc.date",
+    "dcterms.date",
+    "date",
+    "pubdate",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    Markdown,
+    PlainText,
+    Html,
+}
+
+impl FileFormat {
+    /// Detect from the extension, falling back to sniffing the content
+    pub fn detect(path: &Path, raw: &str) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        match extension.as_deref() {
+            Some("md" | "markdown" | "mdown" | "mkd") => FileFormat::Markdown,
+            Some("html" | "htm" | "xhtml") => FileFormat::Html,
+            Some("txt" | "text") => FileFormat::PlainText,
+            _ => {
+                let start = raw.trim_start().get(..15).unwrap_or("").to_lowercase();
+                if start.starts_with("<!doctype html") || start.starts_with("<html") {
+                    FileFormat::Html
+                } else {
+                    FileFormat::PlainText
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedDocument {
+    pub title: String,
+    pub content: String,
+    pub author: Option<String>,
+    pub publication_date: Option<String>,
+    pub doi: Option<String>,
+}
+
+/// Parse a file's contents into a document, detecting its format from `path`
+pub fn parse_document(path: &Path, raw: &str) -> Result<ImportedDocument> {
+    let raw = raw.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+    let (title, content, author, publication_date, doi) = match FileFormat::detect(path, &raw) {
+        FileFormat::Markdown => parse_markdown(&raw),
+        FileFormat::Html => parse_html(&raw)?,
+        FileFormat::PlainText => (None, raw.clone(), None, None, None),
+    };
+
+    let content = normalize_content(&content);
+    if content.is_empty() {
+        return Err(anyhow!("File has no text content"));
+    }
+
+    let title = title
+        .filter(|t| !t.is_empty())
+        .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    Ok(ImportedDocument {
+        title,
+        content,
+        author,
+        publication_date: publication_date.map(|d| normalize_date(&d)),
+        doi,
+    })
+}
+
+/// Title, content, author, publication date and DOI
+type ParsedParts = (Option<String>, String, Option<String>, Option<String>, Option<String>);
+
+fn parse_markdown(raw: &str) -> ParsedParts {
+    let (front_matter, body) = split_front_matter(raw);
+    let field = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            front_matter
+                .iter()
+                .find(|(key, value)| key == name && !value.is_empty())
+                .map(|(_, value)| value.clone())
+        })
+    };
+
+    let title = field(&["title"]).or_else(|| {
+        detect_header_ranges(body)
+            .into_iter()
+            .find(|header| header.level == 1)
+            .map(|header| header.title)
+    });
+
+    (
+        title,
+        body.to_string(),
+        field(&["author", "authors"]),
+        field(&["date", "published", "publication_date"]),
+        field(&["doi"]),
+    )
+}
+
+/// Split a leading "---" YAML front matter block into lowercased `key: value` pairs
+/// Only flat fields are read; lists keep their first item.
+fn split_front_matter(raw: &str) -> (Vec<(String, String)>, &str) {
+    let Some(rest) = raw.strip_prefix("---\n") else {
+        return (Vec::new(), raw);
+    };
+    // The block ends at the first line that is exactly "---"; the body starts after it
+    let mut end = 0;
+    let mut closing_len = None;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            closing_len = Some(line.len());
+            break;
+        }
+        end += line.len();
+    }
+    let Some(closing_len) = closing_len else {
+        return (Vec::new(), raw);
+    };
+
+    let fields = rest[..end]
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value
+                .trim()
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or("")
+                .split(',')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'');
+            Some((key.trim().to_lowercase(), value.to_string()))
+        })
+        .collect();
+
+    let body = &rest[end + closing_len..];
+    (fields, body)
+}
+
+fn parse_html(raw: &str) -> Result<ParsedParts> {
+    let document = Html::parse_document(raw);
+
+    let meta_selector = Selector::parse("meta").unwrap();
+    let metas: Vec<(String, String)> = document
+        .select(&meta_selector)
+        .filter_map(|meta| {
+            let element = meta.value();
+            let name = element.attr("name").or_else(|| element.attr("property"))?;
+            let content = element.attr("content")?.trim();
+            (!content.is_empty()).then(|| (name.to_lowercase(), content.to_string()))
+        })
+        .collect();
+    let meta = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            metas
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        })
+    };
+
+    let title_selector = Selector::parse("title").unwrap();
+    let title = meta(&["og:title", "citation_title", "dc.title"]).or_else(|| {
+        document
+            .select(&title_selector)
+            .next()
+            .map(|t| t.text().collect::<String>().trim().to_string())
+    });
+
+    let body_selector = Selector::parse("body").unwrap();
+    let body_html = document
+        .select(&body_selector)
+        .next()
+        .map(|body| body.inner_html())
+        .unwrap_or_else(|| raw.to_string());
+    let content = html_to_plain_text(&body_html)?;
+
+    Ok((
+        title,
+        content,
+        meta(AUTHOR_META_NAMES),
+        meta(DATE_META_NAMES),
+        meta(&["citation_doi", "dc.identifier.doi"]),
+    ))
+}
+
+pub fn normalize_content(content: &str) -> String {
+    EXTRA_BLANK_LINES_REGEX
+        .replace_all(content.trim(), "\n\n")
+        .to_string()
+}
+
+/// Shorten ISO timestamps ("2024-03-05T10:00:00Z") to their date
+pub fn normalize_date(date: &str) -> String {
+    let date = date.trim();
+    match ISO_DATE_REGEX.find(date) {
+        Some(day) => day.as_str().to_string(),
+        None => date.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(FileFormat::detect(Path::new("a/notes.MD"), ""), FileFormat::Markdown);
+        assert_eq!(FileFormat::detect(Path::new("page.htm"), ""), FileFormat::Html);
+        assert_eq!(FileFormat::detect(Path::new("saved"), "  <!DOCTYPE html><html>"), FileFormat::Html);
+        assert_eq!(FileFormat::detect(Path::new("notes"), "Just words"), FileFormat::PlainText);
+    }
+
+    #[test]
+    fn test_markdown_front_matter_and_headings() {
+        let raw = "---\ntitle: \"Rome: A History\"\nauthors: [Mary Beard, Other]\ndate: 2015-10-20T00:00:00Z\n---\n\n# Intro\n\nRome was founded.\n\n## Kings\n\nSeven of them.\n";
+        let doc = parse_document(Path::new("rome.md"), raw).unwrap();
+        assert_eq!(doc.title, "Rome: A History");
+        assert_eq!(doc.author.as_deref(), Some("Mary Beard"));
+        assert_eq!(doc.publication_date.as_deref(), Some("2015-10-20"));
+        assert_eq!(doc.content, "# Intro\n\nRome was founded.\n\n## Kings\n\nSeven of them.");
+
+        // Only the closing line goes; a list or rule opening the body stays
+        let doc = parse_document(Path::new("list.md"), "---\ntitle: Chores\n---\n- Sweep\n- Dust\n").unwrap();
+        assert_eq!(doc.title, "Chores");
+        assert_eq!(doc.content, "- Sweep\n- Dust");
+        let doc = parse_document(Path::new("rule.md"), "---\ntitle: Ruled\n---\n---\n\nBelow.").unwrap();
+        assert_eq!(doc.content, "---\n\nBelow.");
+
+        let doc = parse_document(Path::new("notes.md"), "Lead line.\n\n# Real Title\r\n\r\nBody.").unwrap();
+        assert_eq!(doc.title, "Real Title");
+        assert_eq!(doc.content, "Lead line.\n\n# Real Title\n\nBody.");
+        assert_eq!(doc.author, None);
+    }
+
+    #[test]
+    fn test_html_metadata_and_cleaning() {
+        let raw = r#"<!DOCTYPE html><html><head>
+            <title>Fallback title</title>
+            <meta property="og:title" content="The Real Title">
+            <meta name="Author" content="Ada Lovelace">
+            <meta property="article:published_time" content="1843-07-01T12:00:00Z">
+            <style>p { color: red; }</style>
+            </head><body><main>
+            <h2>Notes</h2>
+            <p>The engine <a href="https://example.com/engine">weaves</a> patterns.</p>
+            <script>track();</script>
+            <table><tr><td>skipped</td></tr></table>
+            <p>Second paragraph.</p>
+            </main></body></html>"#;
+        let doc = parse_document(Path::new("note.html"), raw).unwrap();
+        assert_eq!(doc.title, "The Real Title");
+        assert_eq!(doc.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(doc.publication_date.as_deref(), Some("1843-07-01"));
+        assert_eq!(
+            doc.content,
+            "== Notes ==\n\nThe engine [weaves](https://example.com/engine) patterns.\n\nSecond paragraph."
+        );
+    }
+
+    #[test]
+    fn test_html_lists_tables_and_definitions() {
+        let raw = "<html><body><h2>Gear</h2><ol start=\"2\"><li>Pitch the tent.</li><li>Light the stove.</li></ol>\
+            <table><tr><th>Item</th><th>Price</th></tr><tr><td>Tent</td><td>120</td></tr></table>\
+            <dl><dt>Bivvy</dt><dd>A small shelter.</dd></dl></body></html>";
+        let doc = parse_document(Path::new("gear.html"), raw).unwrap();
+        assert_eq!(
+            doc.content,
+            "== Gear ==\n\n2. Pitch the tent.\n3. Light the stove.\n\n\
+             | Item | Price |\n| --- | --- |\n| Tent | 120 |\n\nBivvy\n: A small shelter."
+        );
+    }
+
+    #[test]
+    fn test_plain_text_title_from_file_name() {
+        let doc = parse_document(Path::new("/tmp/Reading List.txt"), "\u{feff}One.\n\n\n\nTwo.\n").unwrap();
+        assert_eq!(doc.title, "Reading List");
+        assert_eq!(doc.content, "One.\n\nTwo.");
+        assert!(parse_document(Path::new("empty.txt"), " \n ").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+// HTTP client service
+//
+// The reqwest client shared by the importers that fetch over the network. A client can
+// be pointed at another base URL, such as a local stand-in server in tests: requests
+// keep their path and query but go to the base's scheme, host and port.
+
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+
+pub const USER_AGENT: &str = "Trivium/0.1.0 (Incremental Reading Application; contact@example.com)";
+
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    base_url: Option<Url>,
+}
+
+impl HttpClient {
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
+        Ok(Self { client, base_url: None })
+    }
+
+    /// A client sending every request to `base_url` instead of the requested host
+    #[cfg(test)]
+    pub fn with_base_url(base_url: &str) -> Result<Self> {
+        let base_url = Url::parse(base_url).map_err(|e| anyhow!("Invalid base URL {}: {}", base_url, e))?;
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .no_proxy()
+            .build()?;
+        Ok(Self {
+            client,
+            base_url: Some(base_url),
+        })
+    }
+
+    /// The URL actually requested for `url`
+    pub fn resolve(&self, url: &str) -> Result<Url> {
+        let mut resolved = Url::parse(url).map_err(|e| anyhow!("Invalid URL {}: {}", url, e))?;
+        if let Some(base) = &self.base_url {
+            resolved
+                .set_scheme(base.scheme())
+                .map_err(|_| anyhow!("Cannot use the scheme of {}", base))?;
+            resolved.set_host(base.host_str())?;
+            resolved
+                .set_port(base.port())
+                .map_err(|_| anyhow!("Cannot use the port of {}", base))?;
+        }
+        Ok(resolved)
+    }
+
+    /// GET `url`, failing on an unsuccessful status
+    pub async fn get(&self, url: &str) -> Result<reqwest::Response> {
+        let response = self.client.get(self.resolve(url)?).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("HTTP error: {}", response.status()));
+        }
+        Ok(response)
+    }
+}
+
+/// A local stand-in server for tests that fetch through an `HttpClient`
+#[cfg(test)]
+pub mod test_server {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer one request with `body` as `content_type`, returning the request line
+    pub async fn serve_once(listener: TcpListener, content_type: &str, body: &str) -> String {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let read = socket.read(&mut buffer).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).lines().next().unwrap_or("").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_with_base_url() {
+        let client = HttpClient::with_base_url("http://127.0.0.1:8123").unwrap();
+        assert_eq!(
+            client.resolve("https://example.com/a/b?c=d#e").unwrap().as_str(),
+            "http://127.0.0.1:8123/a/b?c=d#e"
+        );
+
+        let client = HttpClient::new().unwrap();
+        assert_eq!(client.resolve("https://example.com/a").unwrap().as_str(), "https://example.com/a");
+        assert!(client.resolve("not a url").is_err());
+    }
+}
@@ -0,0 +1,293 @@
+// Mark anchoring service
+//
+// Finds where a mark's text lives in a text's current content, for marks whose
+// stored range may no longer be correct after an edit.
+//
+// When the marked text occurs more than once, the occurrence closest to the mark's
+// old position wins. Re-anchoring after an edit only looks within a window around the
+// old position: an exact match there first, then an approximate one using edit
+// distance (Sellers' algorithm: Levenshtein distance where the match may start and
+// end anywhere in the window). A copy of the text elsewhere is never taken for the mark.
+//
+// All positions are UTF-16 code units, matching the DOM-space positions stored on
+// cloze notes.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorMatch {
+    pub start: i64,
+    pub end: i64,
+    pub matched_text: String,
+    /// 1.0 for an exact match, 1 - edit distance / marked length otherwise
+    pub confidence: f64,
+}
+
+/// Confidence at or above which a mark is relocated automatically after an edit
+pub const DEFAULT_REANCHOR_THRESHOLD: f64 = 0.8;
+
+/// Minimum search window on each side of the old position (UTF-16 units)
+const MIN_SEARCH_WINDOW: i64 = 200;
+
+/// How far either side of a mark's old range re-anchoring searches (UTF-16 units)
+fn search_window(needle_len: i64) -> i64 {
+    MIN_SEARCH_WINDOW.max(needle_len * 2)
+}
+
+fn byte_to_utf16(text: &str, byte_offset: usize) -> i64 {
+    text[..byte_offset].encode_utf16().count() as i64
+}
+
+/// Locate `needle` in `content`, preferring the occurrence nearest `old_start`
+pub fn find_anchor(content: &str, needle: &str, old_start: Option<i64>) -> Option<AnchorMatch> {
+    if needle.trim().is_empty() {
+        return None;
+    }
+
+    let needle_len = needle.encode_utf16().count() as i64;
+    let target = old_start.unwrap_or(0);
+
+    content
+        .match_indices(needle)
+        .map(|(byte_start, _)| byte_to_utf16(content, byte_start))
+        .min_by_key(|start| (start - target).abs())
+        .map(|start| AnchorMatch {
+            start,
+            end: start + needle_len,
+            matched_text: needle.to_string(),
+            confidence: 1.0,
+        })
+}
+
+/// Best approximate match for `needle` within a window around `old_start..old_end`
+///
+/// The window extends by the larger of 200 units and twice the needle's length on
+/// each side. Returns None when nothing reaches `min_confidence`.
+pub fn find_anchor_fuzzy(
+    content: &str,
+    needle: &str,
+    old_start: i64,
+    old_end: i64,
+    min_confidence: f64,
+) -> Option<AnchorMatch> {
+    let needle_chars: Vec<char> = needle.chars().collect();
+    if needle.trim().is_empty() {
+        return None;
+    }
+
+    let content_chars: Vec<char> = content.chars().collect();
+    // UTF-16 offset of each char boundary, so results can be reported in UTF-16
+    let mut utf16_offsets = Vec::with_capacity(content_chars.len() + 1);
+    let mut offset = 0i64;
+    for c in &content_chars {
+        utf16_offsets.push(offset);
+        offset += c.len_utf16() as i64;
+    }
+    utf16_offsets.push(offset);
+
+    let needle_len = needle.encode_utf16().count() as i64;
+    let window = search_window(needle_len);
+    let window_start = utf16_offsets.partition_point(|&o| o < old_start.saturating_sub(window).max(0));
+    let window_end = utf16_offsets
+        .partition_point(|&o| o <= old_end.max(old_start) + window)
+        .saturating_sub(1)
+        .min(content_chars.len());
+    if window_start >= window_end {
+        return None;
+    }
+    let haystack = &content_chars[window_start..window_end];
+
+    // prev[j] / prev_start[j]: best distance for the needle prefix ending at haystack[..j],
+    // and the haystack index where that alignment started
+    let m = needle_chars.len();
+    let mut prev: Vec<usize> = vec![0; haystack.len() + 1];
+    let mut prev_start: Vec<usize> = (0..=haystack.len()).collect();
+    let mut curr = vec![0usize; haystack.len() + 1];
+    let mut curr_start = vec![0usize; haystack.len() + 1];
+
+    for (i, nc) in needle_chars.iter().enumerate() {
+        curr[0] = i + 1;
+        curr_start[0] = 0;
+        for (j, hc) in haystack.iter().enumerate() {
+            let substitute = prev[j] + usize::from(nc != hc);
+            let delete = prev[j + 1] + 1;
+            let insert = curr[j] + 1;
+
+            if substitute <= delete && substitute <= insert {
+                curr[j + 1] = substitute;
+                curr_start[j + 1] = prev_start[j];
+            } else if delete <= insert {
+                curr[j + 1] = delete;
+                curr_start[j + 1] = prev_start[j + 1];
+            } else {
+                curr[j + 1] = insert;
+                curr_start[j + 1] = curr_start[j];
+            }
+        }
+        std::mem::swap(&mut prev, &mut curr);
+        std::mem::swap(&mut prev_start, &mut curr_start);
+    }
+
+    // Lowest distance wins; ties go to the match nearest the old position
+    let (end, distance) = (1..=haystack.len())
+        .map(|j| (j, prev[j]))
+        .min_by_key(|&(j, d)| {
+            let start = utf16_offsets[window_start + prev_start[j]];
+            (d, (start - old_start).abs())
+        })?;
+
+    let confidence = 1.0 - distance as f64 / m as f64;
+    if confidence < min_confidence {
+        return None;
+    }
+
+    let start_char = window_start + prev_start[end];
+    let end_char = window_start + end;
+    if start_char >= end_char {
+        return None;
+    }
+
+    Some(AnchorMatch {
+        start: utf16_offsets[start_char],
+        end: utf16_offsets[end_char],
+        matched_text: content_chars[start_char..end_char].iter().collect(),
+        confidence,
+    })
+}
+
+/// Re-locate a mark: exact match nearest the old position first, then fuzzy near it
+///
+/// Both are limited to the search window around the old range. A mark without a
+/// stored position takes the first exact match anywhere.
+pub fn reanchor(
+    content: &str,
+    needle: &str,
+    old_start: Option<i64>,
+    old_end: Option<i64>,
+    min_confidence: f64,
+) -> Option<AnchorMatch> {
+    let Some(start) = old_start else {
+        return find_anchor(content, needle, None);
+    };
+    let end = old_end.unwrap_or(start).max(start);
+    let window = search_window(needle.encode_utf16().count() as i64);
+
+    find_anchor(content, needle, Some(start))
+        .filter(|anchor| anchor.start >= start - window && anchor.end <= end + window)
+        .or_else(|| find_anchor_fuzzy(content, needle, start, end, min_confidence))
+}
+
+/// Extract the UTF-16 range `start..end` of `content`, or None if it's out of bounds
+pub fn slice_utf16(content: &str, start: i64, end: i64) -> Option<String> {
+    if start < 0 || end <= start {
+        return None;
+    }
+    let units: Vec<u16> = content.encode_utf16().collect();
+    let (start, end) = (start as usize, end as usize);
+    if end > units.len() {
+        return None;
+    }
+    String::from_utf16(&units[start..end]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_anchor_prefers_nearest_occurrence() {
+        let content = "Paris is big. Lyon is nice. Paris is old.";
+        let first = find_anchor(content, "Paris", Some(2)).unwrap();
+        let second = find_anchor(content, "Paris", Some(30)).unwrap();
+
+        assert_eq!((first.start, first.end), (0, 5));
+        assert_eq!((second.start, second.end), (28, 33));
+        assert_eq!(second.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_find_anchor_missing_text() {
+        assert_eq!(find_anchor("Paris is big.", "Berlin", Some(0)), None);
+        assert_eq!(find_anchor("Paris is big.", "  ", Some(0)), None);
+    }
+
+    #[test]
+    fn test_find_anchor_utf16_positions() {
+        let content = "🎉 café — Paris";
+        let anchor = find_anchor(content, "Paris", None).unwrap();
+        assert_eq!(anchor.start, 10);
+        assert_eq!(slice_utf16(content, anchor.start, anchor.end).as_deref(), Some("Paris"));
+    }
+
+    #[test]
+    fn test_find_anchor_fuzzy_survives_small_edit() {
+        let content = "Intro text. The Eiffel Tower was finished in 1889 for the fair. Outro.";
+        let anchor = find_anchor_fuzzy(
+            content,
+            "The Eiffel Tower was completed in 1889 for the fair.",
+            14,
+            66,
+            0.8,
+        )
+        .unwrap();
+
+        assert_eq!(anchor.matched_text, "The Eiffel Tower was finished in 1889 for the fair.");
+        assert_eq!(anchor.start, 12);
+        assert!(anchor.confidence > 0.8 && anchor.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_find_anchor_fuzzy_rejects_rewritten_text() {
+        let content = "Intro text. Something entirely different is written here now. Outro.";
+        assert_eq!(
+            find_anchor_fuzzy(content, "The Eiffel Tower was completed in 1889.", 12, 51, 0.8),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_anchor_fuzzy_stays_in_window() {
+        let far = "x".repeat(1000);
+        let content = format!("The Eiffel Tower was finished in 1889.{}", far);
+        // Old position is past the window, so the match at the start isn't reachable
+        assert_eq!(
+            find_anchor_fuzzy(&content, "The Eiffel Tower was completed in 1889.", 900, 939, 0.8),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reanchor_prefers_exact_match() {
+        let content = "Alpha beta. Gamma delta.";
+        let anchor = reanchor(content, "Gamma delta", Some(0), Some(11), 0.8).unwrap();
+        assert_eq!((anchor.start, anchor.end), (12, 23));
+        assert_eq!(anchor.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_reanchor_ignores_far_away_duplicate() {
+        let filler = "x".repeat(1000);
+        // The marked sentence was reworded in place, and an exact copy sits far below
+        let content = format!(
+            "The Eiffel Tower was finished in 1889 for the fair.{}The Eiffel Tower was completed in 1889 for the fair.",
+            filler
+        );
+        let needle = "The Eiffel Tower was completed in 1889 for the fair.";
+
+        let anchor = reanchor(&content, needle, Some(0), Some(52), 0.8).unwrap();
+        assert_eq!(anchor.start, 0);
+        assert!(anchor.confidence < 1.0);
+
+        // With nothing near the old position the mark is flagged, not moved to the copy
+        let content = format!("Something else entirely.{}{}", filler, needle);
+        assert_eq!(reanchor(&content, needle, Some(0), Some(52), 0.8), None);
+    }
+
+    #[test]
+    fn test_slice_utf16_bounds() {
+        assert_eq!(slice_utf16("abc", 1, 3).as_deref(), Some("bc"));
+        assert_eq!(slice_utf16("abc", 2, 4), None);
+        assert_eq!(slice_utf16("abc", 2, 2), None);
+    }
+}
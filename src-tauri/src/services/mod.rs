@@ -5,3 +5,17 @@ pub mod range_calculator;
 pub mod cloze_parser;
 pub mod cloze_renderer;
 pub mod fsrs;
+pub mod cloze_suggester;
+pub mod duplicate_detector;
+pub mod mark_anchor;
+pub mod text_revisions;
+pub mod reading_queue;
+pub mod text_splitter;
+pub mod outline;
+pub mod sentence_segmenter;
+pub mod excluded_ranges;
+pub mod file_import;
+pub mod epub;
+pub mod pdf_import;
+pub mod http_client;
+pub mod web_article;
@@ -0,0 +1,179 @@
+// Outline service
+//
+// Builds a text's table of contents from its headers, with reading progress and
+// mining stats (marks, cards) for each section.
+//
+// A section runs from its header to the next header of the same or a shallower level,
+// so it includes its subsections, and so do its stats. Header positions are found in
+// the raw content and converted with `to_rendered`, because read ranges and marks use
+// rendered (DOM space) positions.
+
+use crate::models::read_range::ReadRange;
+use crate::services::parser::detect_header_ranges;
+use crate::services::range_calculator::RangeCalculator;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineSection {
+    pub level: usize,
+    pub title: String,
+    /// Rendered (DOM space) UTF-16 range, header included
+    pub start_position: i64,
+    pub end_position: i64,
+    pub read_percentage: f64,
+    pub mark_count: i64,
+    pub card_count: i64,
+    pub children: Vec<OutlineSection>,
+}
+
+/// A mark's rendered start position and how many cards it has
+#[derive(Debug, Clone, Copy)]
+pub struct MarkPosition {
+    pub start_position: i64,
+    pub card_count: i64,
+}
+
+fn section_stats(start: i64, end: i64, read_ranges: &[ReadRange], marks: &[MarkPosition]) -> (f64, i64, i64) {
+    let clipped: Vec<ReadRange> = read_ranges
+        .iter()
+        .filter(|r| r.start_position < end && r.end_position > start)
+        .map(|r| ReadRange {
+            start_position: r.start_position.max(start),
+            end_position: r.end_position.min(end),
+            ..r.clone()
+        })
+        .collect();
+
+    let read_percentage = if end > start {
+        RangeCalculator::calculate_read_characters(clipped) as f64 / (end - start) as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let in_section = marks.iter().filter(|m| m.start_position >= start && m.start_position < end);
+    let mark_count = in_section.clone().count() as i64;
+    let card_count = in_section.map(|m| m.card_count).sum();
+
+    (read_percentage, mark_count, card_count)
+}
+
+/// Hierarchical outline of `content`
+///
+/// `to_rendered` maps a raw UTF-16 position at a line start (or the end of the content)
+/// to its rendered position.
+pub fn build_outline(
+    content: &str,
+    to_rendered: impl Fn(i64) -> i64,
+    read_ranges: &[ReadRange],
+    marks: &[MarkPosition],
+) -> Vec<OutlineSection> {
+    let headers = detect_header_ranges(content);
+    let content_end = content.encode_utf16().count() as i64;
+
+    // Flat list in document order, each with its extent
+    let flat: Vec<OutlineSection> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            let raw_end = headers[i + 1..]
+                .iter()
+                .find(|next| next.level <= header.level)
+                .map_or(content_end, |next| next.start_position);
+            let start = to_rendered(header.start_position);
+            let end = to_rendered(raw_end);
+            let (read_percentage, mark_count, card_count) = section_stats(start, end, read_ranges, marks);
+
+            OutlineSection {
+                level: header.level,
+                title: header.title.clone(),
+                start_position: start,
+                end_position: end,
+                read_percentage,
+                mark_count,
+                card_count,
+                children: Vec::new(),
+            }
+        })
+        .collect();
+
+    // Nest: each section becomes a child of the nearest preceding shallower section
+    let mut roots: Vec<OutlineSection> = Vec::new();
+    let mut stack: Vec<OutlineSection> = Vec::new();
+    for section in flat {
+        while stack.last().is_some_and(|open| open.level >= section.level) {
+            let done = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(done),
+                None => roots.push(done),
+            }
+        }
+        stack.push(section);
+    }
+    while let Some(done) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(done),
+            None => roots.push(done),
+        }
+    }
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const ARTICLE: &str = "Lead.\n== A ==\nAaaa.\n=== A1 ===\nBbbb.\n== B ==\nCccc.";
+
+    fn range(start: i64, end: i64) -> ReadRange {
+        ReadRange {
+            id: 0,
+            text_id: 1,
+            user_id: 1,
+            start_position: start,
+            end_position: end,
+            marked_at: Utc::now(),
+            is_auto_completed: false,
+        }
+    }
+
+    #[test]
+    fn test_outline_nesting_and_extents() {
+        let outline = build_outline(ARTICLE, |p| p, &[], &[]);
+        let top: Vec<(&str, i64, i64)> = outline
+            .iter()
+            .map(|s| (s.title.as_str(), s.start_position, s.end_position))
+            .collect();
+
+        assert_eq!(top, vec![("A", 6, 37), ("B", 37, 50)]);
+        assert_eq!(outline[0].children.len(), 1);
+        assert_eq!(outline[0].children[0].title, "A1");
+        assert_eq!(outline[0].children[0].level, 3);
+        assert_eq!(outline[0].children[0].end_position, 37);
+    }
+
+    #[test]
+    fn test_outline_progress_and_counts() {
+        let marks = [
+            MarkPosition { start_position: 15, card_count: 2 },
+            MarkPosition { start_position: 31, card_count: 0 },
+            MarkPosition { start_position: 44, card_count: 1 },
+        ];
+        // Read all of "== A ==\nAaaa.\n" (6..20) and nothing else
+        let outline = build_outline(ARTICLE, |p| p, &[range(0, 20)], &marks);
+
+        let a = &outline[0];
+        assert!((a.read_percentage - 14.0 / 31.0 * 100.0).abs() < 1e-9);
+        assert_eq!((a.mark_count, a.card_count), (2, 2));
+        assert_eq!(a.children[0].read_percentage, 0.0);
+        assert_eq!((a.children[0].mark_count, a.children[0].card_count), (1, 0));
+        assert_eq!((outline[1].mark_count, outline[1].card_count), (1, 1));
+    }
+
+    #[test]
+    fn test_outline_without_headers() {
+        assert!(build_outline("Just text.", |p| p, &[], &[]).is_empty());
+    }
+}
@@ -8,14 +8,14 @@
 // - Validate cloze syntax
 // - Split text into sentences or logical chunks
 // - Clean and normalize text content
-// - Parse exclude tags ([[exclude]]...[[/exclude]])
+// - Detect headings (MediaWiki "== X ==", Markdown ATX "## X" and Setext underlines)
 //
 // The parser supports Anki-style cloze deletion syntax, which is the
 // standard format for creating fill-in-the-blank flashcards.
 
 use anyhow::Result;
 use regex::Regex;
-use sqlx::{Pool, Sqlite};
+use sqlx::SqliteConnection;
 
 #[derive(Debug, Clone)]
 pub struct Paragraph {
@@ -104,8 +104,9 @@ pub fn detect_paragraphs(content: &str) -> Vec<Paragraph> {
     paragraphs
 }
 
+/// Insert detected paragraphs on a pooled connection or inside a caller's transaction
 pub async fn store_paragraphs(
-    pool: &Pool<Sqlite>,
+    conn: &mut SqliteConnection,
     text_id: i64,
     paragraphs: &[Paragraph],
 ) -> Result<()> {
@@ -121,34 +122,21 @@ pub async fn store_paragraphs(
             paragraph.end_position,
             paragraph.character_count
         )
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
     }
 
     Ok(())
 }
 
-pub fn calculate_excluded_character_count(content: &str) -> i64 {
-    let re = match Regex::new(r"\[\[exclude\]\](.*?)\[\[/exclude\]\]") {
-        Ok(r) => r,
-        Err(_) => return 0,
-    };
-    let mut total_excluded = 0i64;
-
-    for cap in re.captures_iter(content) {
-        if let Some(excluded_text) = cap.get(1) {
-            // Use UTF-16 code units to match JavaScript's string.length
-            total_excluded += excluded_text.as_str().encode_utf16().count() as i64;
-        }
-    }
-
-    total_excluded
-}
-
 #[derive(Debug, Clone)]
 pub struct HeaderRange {
     pub start_position: i64,
     pub end_position: i64,
+    /// Heading level on the Markdown scale: "# Title" and "= Title =" underlines are 1,
+    /// "## Title" and "== Section ==" are 2, and so on
+    pub level: usize,
+    pub title: String,
 }
 
 // Convert byte offset to UTF-16 code unit offset to match JavaScript's string.length
@@ -156,31 +144,93 @@ fn byte_offset_to_utf16_offset(content: &str, byte_offset: usize) -> usize {
     content[..byte_offset].encode_utf16().count()
 }
 
+/// A line that could be the title of a Setext heading
+struct SetextCandidate<'a> {
+    start: usize,
+    title: &'a str,
+}
+
+/// Detect headings in any of the supported syntaxes:
+/// - MediaWiki: "== Section ==" (matching counts of '=' on both sides)
+/// - Markdown ATX: "## Section", optionally closed with '#'s
+/// - Markdown Setext: a line underlined with '=' (level 1) or '-' (level 2)
+///
+/// Lines inside fenced code blocks (``` or ~~~) and a leading "---" front matter block
+/// are never headings.
+/// A Setext heading's range covers both the title and the underline.
 pub fn detect_header_ranges(content: &str) -> Vec<HeaderRange> {
     let mut header_ranges = Vec::new();
 
-    let re = match Regex::new(r"(?m)^(={2,})\s*(.+?)\s*(={2,})$") {
-        Ok(r) => r,
-        Err(_) => return header_ranges,
+    let (mediawiki, atx, setext_underline, list_marker) = match (
+        Regex::new(r"^(={2,})\s*(.*?[^=\s].*?)\s*(={2,})$"),
+        Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+(.*?))?(?:[ \t]+#+)?[ \t]*$"),
+        Regex::new(r"^ {0,3}(=+|-+)[ \t]*$"),
+        Regex::new(r"^ {0,3}(?:[-*+>]|\d+[.)])(?:[ \t]|$)"),
+    ) {
+        (Ok(a), Ok(b), Ok(c), Ok(d)) => (a, b, c, d),
+        _ => return header_ranges,
     };
 
-    for cap in re.captures_iter(content) {
-        if let (Some(full_match), Some(opening), Some(closing)) = (cap.get(0), cap.get(1), cap.get(3)) {
-            let opening_count = opening.as_str().len();
-            let closing_count = closing.as_str().len();
-
-            if opening_count == closing_count {
-                let start_char = byte_offset_to_utf16_offset(content, full_match.start()) as i64;
-                let header_text = &content[full_match.start()..full_match.end()];
-                // Use UTF-16 code units to match JavaScript's string.length
-                let end_char = start_char + header_text.encode_utf16().count() as i64;
-
-                header_ranges.push(HeaderRange {
-                    start_position: start_char,
-                    end_position: end_char,
-                });
+    let mut push = |start: usize, end: usize, level: usize, title: &str| {
+        header_ranges.push(HeaderRange {
+            start_position: byte_offset_to_utf16_offset(content, start) as i64,
+            end_position: byte_offset_to_utf16_offset(content, end) as i64,
+            level,
+            title: title.trim().to_string(),
+        });
+    };
+
+    let mut in_fence = false;
+    let mut in_front_matter = content.starts_with("---\n");
+    let mut candidate: Option<SetextCandidate> = None;
+    let mut line_start = 0;
+
+    for raw_line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += raw_line.len();
+        let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+        let end = start + line.len();
+        let previous = candidate.take();
+
+        if in_front_matter {
+            in_front_matter = start == 0 || !(line == "---" || line == "...");
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(cap) = mediawiki.captures(line) {
+            let (opening, closing) = (cap[1].len(), cap[3].len());
+            if opening == closing {
+                push(start, end, opening, &cap[2]);
+                continue;
             }
         }
+
+        if let Some(cap) = atx.captures(line) {
+            let title = cap.get(2).map_or("", |m| m.as_str());
+            if !title.trim().is_empty() {
+                push(start, end, cap[1].len(), title);
+            }
+            continue;
+        }
+
+        if let (Some(cap), Some(previous)) = (setext_underline.captures(line), &previous) {
+            let level = if cap[1].starts_with('=') { 1 } else { 2 };
+            push(previous.start, end, level, previous.title);
+            continue;
+        }
+
+        if !list_marker.is_match(line) {
+            candidate = Some(SetextCandidate { start, title: line });
+        }
     }
 
     header_ranges
@@ -236,6 +286,49 @@ Final content
         assert_eq!(headers.len(), 3);
     }
 
+    #[test]
+    fn test_detect_header_ranges_level_and_title() {
+        let content = "Intro\n\n== History ==\n\nText\n\n=== Early years ===\n\nMore";
+        let headers = detect_header_ranges(content);
+        let found: Vec<(usize, &str)> = headers.iter().map(|h| (h.level, h.title.as_str())).collect();
+        assert_eq!(found, vec![(2, "History"), (3, "Early years")]);
+    }
+
+    #[test]
+    fn test_detect_atx_headings() {
+        let content = "# Title\n\nText\n\n## Section ##\n\n###### Deep\n\n#hashtag\n####### too deep";
+        let headers = detect_header_ranges(content);
+        let found: Vec<(usize, &str)> = headers.iter().map(|h| (h.level, h.title.as_str())).collect();
+        assert_eq!(found, vec![(1, "Title"), (2, "Section"), (6, "Deep")]);
+        assert_eq!((headers[0].start_position, headers[0].end_position), (0, 7));
+    }
+
+    #[test]
+    fn test_detect_setext_headings() {
+        let content = "Title 👋\n=====\n\nBody\n\nSection\n---\n\nMore\n\n---\n\n- item\n---";
+        let headers = detect_header_ranges(content);
+        let found: Vec<(usize, &str)> = headers.iter().map(|h| (h.level, h.title.as_str())).collect();
+        // The rule after a blank line and the one under a list item are not headings
+        assert_eq!(found, vec![(1, "Title 👋"), (2, "Section")]);
+        // Range covers title and underline: "Title 👋\n=====" = 14 UTF-16 units
+        assert_eq!((headers[0].start_position, headers[0].end_position), (0, 14));
+    }
+
+    #[test]
+    fn test_headings_ignored_in_code_and_front_matter() {
+        let content = "---\ntitle: Notes\n---\n\n```\n# not a heading\n```\n\n# Real\n\n~~~\n== Nor this ==\n~~~";
+        let headers = detect_header_ranges(content);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].title, "Real");
+    }
+
+    #[test]
+    fn test_mixed_heading_styles() {
+        let content = "== Wiki ==\nText\n## Markdown\nText\nSetext\n======";
+        let levels: Vec<usize> = detect_header_ranges(content).iter().map(|h| h.level).collect();
+        assert_eq!(levels, vec![2, 2, 1]);
+    }
+
     #[test]
     fn test_detect_header_ranges_mismatched_equals() {
         let content = "Text\n\n=== Not A Header ==\n\nContent";
@@ -259,30 +352,6 @@ Final content
 
     // UTF-16 Bug #4 Tests - Emoji and Multi-byte Characters
 
-    #[test]
-    fn test_excluded_character_count_with_emoji() {
-        let content = "Hello [[exclude]]👋 world[[/exclude]] Test";
-        let excluded = calculate_excluded_character_count(content);
-        // "👋 world" = 8 UTF-16 code units (👋 is 2 units + 1 space + 5 chars)
-        assert_eq!(excluded, 8);
-    }
-
-    #[test]
-    fn test_excluded_character_count_with_chinese() {
-        let content = "Hello [[exclude]]世界 测试[[/exclude]] World";
-        let excluded = calculate_excluded_character_count(content);
-        // "世界 测试" = 5 UTF-16 code units (each CJK char is 1 unit)
-        assert_eq!(excluded, 5);
-    }
-
-    #[test]
-    fn test_excluded_character_count_mixed_unicode() {
-        let content = "Test [[exclude]]👋 世界 测试[[/exclude]] End";
-        let excluded = calculate_excluded_character_count(content);
-        // "👋 世界 测试" = 8 UTF-16 code units (👋=2, space=1, 世=1, 界=1, space=1, 测=1, 试=1)
-        assert_eq!(excluded, 8);
-    }
-
     #[test]
     fn test_header_character_count_with_emoji() {
         let content = "== Test 👋 World ==\nContent";
@@ -348,14 +417,6 @@ Final content
         }
     }
 
-    #[test]
-    fn test_ascii_only_unchanged() {
-        // Verify ASCII text behavior is unchanged
-        let content = "Hello [[exclude]]test[[/exclude]] World";
-        let excluded = calculate_excluded_character_count(content);
-        assert_eq!(excluded, 4); // "test" = 4 UTF-16 units (same as chars)
-    }
-
     #[test]
     fn test_fallback_paragraph_with_emoji() {
         // Test single paragraph fallback with emoji
@@ -57,4 +57,116 @@ impl RangeCalculator {
 
         unread
     }
+
+    /// Where `old` and `new` differ, as (edit_start, old_edit_end, new_edit_end) in UTF-16 units
+    ///
+    /// Found by trimming the common prefix and suffix, so a single contiguous edit is
+    /// recovered exactly and scattered edits collapse into one span covering them all.
+    pub fn edit_span(old: &str, new: &str) -> (i64, i64, i64) {
+        let old_units: Vec<u16> = old.encode_utf16().collect();
+        let new_units: Vec<u16> = new.encode_utf16().collect();
+
+        let is_high_surrogate = |unit: u16| (0xD800..=0xDBFF).contains(&unit);
+        let is_low_surrogate = |unit: u16| (0xDC00..=0xDFFF).contains(&unit);
+
+        let mut prefix = old_units
+            .iter()
+            .zip(&new_units)
+            .take_while(|(a, b)| a == b)
+            .count();
+        // Never end the prefix between the two halves of a surrogate pair
+        if prefix > 0 && is_high_surrogate(old_units[prefix - 1]) {
+            prefix -= 1;
+        }
+        let max_suffix = old_units.len().min(new_units.len()) - prefix;
+        let mut suffix = old_units
+            .iter()
+            .rev()
+            .zip(new_units.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if suffix > 0 && is_low_surrogate(old_units[old_units.len() - suffix]) {
+            suffix -= 1;
+        }
+
+        (
+            prefix as i64,
+            (old_units.len() - suffix) as i64,
+            (new_units.len() - suffix) as i64,
+        )
+    }
+
+    /// Map a range through an edit that replaced `edit_start..edit_end` and changed
+    /// the text length by `length_delta`
+    ///
+    /// Ranges before the edit are kept, ranges after it are shifted. A range the edit
+    /// overlaps is trimmed to the parts outside the edit, and split in two when the
+    /// edit falls inside it: the edited text itself hasn't been read.
+    pub fn apply_edit_to_range(
+        start: i64,
+        end: i64,
+        edit_start: i64,
+        edit_end: i64,
+        length_delta: i64,
+    ) -> Vec<(i64, i64)> {
+        if end <= edit_start {
+            return vec![(start, end)];
+        }
+        if start >= edit_end {
+            return vec![(start + length_delta, end + length_delta)];
+        }
+
+        let mut pieces = Vec::new();
+        if start < edit_start {
+            pieces.push((start, edit_start));
+        }
+        if end > edit_end {
+            pieces.push((edit_end + length_delta, end + length_delta));
+        }
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_span() {
+        assert_eq!(RangeCalculator::edit_span("hello world", "hello brave world"), (6, 6, 12));
+        assert_eq!(RangeCalculator::edit_span("hello world", "hello"), (5, 11, 5));
+        assert_eq!(RangeCalculator::edit_span("same", "same"), (4, 4, 4));
+        // Repeated characters don't let prefix and suffix overlap
+        assert_eq!(RangeCalculator::edit_span("aaa", "aaaa"), (3, 3, 4));
+        // 🦊 and 🦁 share a high surrogate, the span must still cover whole characters
+        assert_eq!(RangeCalculator::edit_span("a🦊b", "a🦁b"), (1, 3, 3));
+    }
+
+    #[test]
+    fn test_apply_edit_before_and_after() {
+        // Edit replaces 20..25 with 10 units (+5)
+        assert_eq!(RangeCalculator::apply_edit_to_range(0, 20, 20, 25, 5), vec![(0, 20)]);
+        assert_eq!(RangeCalculator::apply_edit_to_range(25, 40, 20, 25, 5), vec![(30, 45)]);
+    }
+
+    #[test]
+    fn test_apply_edit_trims_overlap() {
+        assert_eq!(RangeCalculator::apply_edit_to_range(10, 22, 20, 25, 5), vec![(10, 20)]);
+        assert_eq!(RangeCalculator::apply_edit_to_range(22, 40, 20, 25, -3), vec![(22, 37)]);
+        assert!(RangeCalculator::apply_edit_to_range(21, 24, 20, 25, 0).is_empty());
+    }
+
+    #[test]
+    fn test_apply_edit_splits_range() {
+        assert_eq!(
+            RangeCalculator::apply_edit_to_range(0, 100, 40, 50, 2),
+            vec![(0, 40), (52, 102)]
+        );
+        // Pure insertion inside a range
+        assert_eq!(
+            RangeCalculator::apply_edit_to_range(0, 100, 40, 40, 7),
+            vec![(0, 40), (47, 107)]
+        );
+    }
 }
@@ -0,0 +1,84 @@
+// Reading queue scheduling
+//
+// Incremental reading in the style of SuperMemo: a text is read a little at a time
+// and then postponed. Each postpone multiplies the text's interval by a factor, so
+// texts come back less and less often as they are worked through.
+//
+// Priority (0-10, higher is more important) sets the factor: top-priority texts grow
+// their interval slowly and keep coming back, low-priority ones drift away quickly.
+
+use chrono::{DateTime, Duration, Utc};
+
+pub const MIN_READING_PRIORITY: i64 = 0;
+pub const MAX_READING_PRIORITY: i64 = 10;
+pub const DEFAULT_READING_PRIORITY: i64 = 5;
+
+/// Interval a newly queued text starts from
+pub const INITIAL_INTERVAL_DAYS: f64 = 1.0;
+
+/// Interval growth for the highest and lowest priority
+const MIN_INTERVAL_FACTOR: f64 = 1.2;
+const MAX_INTERVAL_FACTOR: f64 = 2.0;
+
+const MAX_INTERVAL_DAYS: f64 = 365.0;
+
+/// How much a postpone multiplies the interval at `priority`
+pub fn interval_factor(priority: i64) -> f64 {
+    let priority = priority.clamp(MIN_READING_PRIORITY, MAX_READING_PRIORITY);
+    let low = (MAX_READING_PRIORITY - priority) as f64 / (MAX_READING_PRIORITY - MIN_READING_PRIORITY) as f64;
+    MIN_INTERVAL_FACTOR + (MAX_INTERVAL_FACTOR - MIN_INTERVAL_FACTOR) * low
+}
+
+/// Interval after postponing a text whose current interval is `interval_days`
+pub fn next_interval(interval_days: f64, priority: i64) -> f64 {
+    (interval_days.max(INITIAL_INTERVAL_DAYS) * interval_factor(priority)).min(MAX_INTERVAL_DAYS)
+}
+
+/// When a text postponed at `now` with interval `interval_days` comes back
+pub fn next_read_at(now: DateTime<Utc>, interval_days: f64) -> DateTime<Utc> {
+    now + Duration::seconds((interval_days * 86_400.0).round() as i64)
+}
+
+/// Characters left in today's reading budget, None when there is no budget
+pub fn budget_remaining(daily_budget: Option<i64>, read_today: i64) -> Option<i64> {
+    daily_budget.map(|budget| (budget - read_today).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_factor_by_priority() {
+        assert!((interval_factor(10) - 1.2).abs() < 1e-9);
+        assert!((interval_factor(0) - 2.0).abs() < 1e-9);
+        assert!((interval_factor(5) - 1.6).abs() < 1e-9);
+        assert_eq!(interval_factor(42), interval_factor(10));
+    }
+
+    #[test]
+    fn test_next_interval_grows_and_caps() {
+        let mut interval = INITIAL_INTERVAL_DAYS;
+        let mut previous = 0.0;
+        for _ in 0..5 {
+            interval = next_interval(interval, DEFAULT_READING_PRIORITY);
+            assert!(interval > previous);
+            previous = interval;
+        }
+        assert!((next_interval(1.0, 0) - 2.0).abs() < 1e-9);
+        assert_eq!(next_interval(300.0, 0), MAX_INTERVAL_DAYS);
+    }
+
+    #[test]
+    fn test_next_read_at() {
+        let now = DateTime::parse_from_rfc3339("2025-10-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(next_read_at(now, 1.5), now + Duration::hours(36));
+    }
+
+    #[test]
+    fn test_budget_remaining() {
+        assert_eq!(budget_remaining(None, 500), None);
+        assert_eq!(budget_remaining(Some(2000), 500), Some(1500));
+        assert_eq!(budget_remaining(Some(2000), 2500), Some(0));
+    }
+}
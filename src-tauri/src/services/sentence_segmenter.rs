@@ -0,0 +1,296 @@
+// Sentence segmentation service
+//
+// Splits text into sentences with UTF-16 ranges, the finer-grained sibling of
+// `parser::detect_paragraphs`. Used for sentence-by-sentence navigation and for
+// snapping excerpts to a sentence end.
+//
+// A sentence ends at terminal punctuation followed by whitespace (or the end of the
+// text), or at a line break. Closing quotes and brackets after the punctuation belong
+// to the sentence. A period doesn't end a sentence when it:
+// - is part of a number or token ("3.14", "e.g", "example.com"), since no space follows
+// - ends a known abbreviation ("Dr.", "etc.", "e.g.") or a single-letter initial ("J.")
+// - is followed by a lowercase word ("approx. three")
+//
+// Full-width CJK punctuation ("。", "！", "？") ends a sentence even with no space after
+// it, as CJK text doesn't put spaces between sentences.
+
+use anyhow::Result;
+use sqlx::SqliteConnection;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentenceRange {
+    pub sentence_index: i64,
+    pub start_position: i64,
+    pub end_position: i64,
+}
+
+/// Terminal punctuation that needs whitespace after it: Latin, Greek question mark,
+/// Armenian, Devanagari danda, Arabic/Urdu, Ethiopic
+const TERMINATORS: &[char] = &[
+    '.', '!', '?', '…', '‼', '⁇', '⁈', '⁉', '\u{037E}', '։', '।', '؟', '۔', '።', '፧',
+];
+
+/// Terminal punctuation that ends a sentence on its own
+const CJK_TERMINATORS: &[char] = &['。', '！', '？', '｡', '．'];
+
+const CLOSING_PUNCTUATION: &[char] = &[
+    '"', '\'', '”', '’', '»', '›', ')', ']', '}', '」', '』', '）', '】', '》', '〉', '〕', '］',
+];
+
+const OPENING_PUNCTUATION: &[char] = &[
+    '"', '\'', '“', '‘', '«', '‹', '(', '[', '{', '「', '『', '（', '《',
+];
+
+/// Lowercased, without the final period
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "ave", "rd", "blvd", "mt", "mtn",
+    "ft", "pt", "sq", "ln", "etc", "vs", "e.g", "i.e", "cf", "viz", "al", "ibid", "op",
+    "loc", "et", "ca", "fl", "lit", "fig", "vol", "vols", "no", "nos", "pp", "ed", "eds",
+    "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+    "corp", "inc", "ltd", "co", "llc", "approx", "misc", "dept", "assn", "bros", "esq",
+    "rev", "gov", "sen", "rep", "gen", "adm", "est", "max", "min", "ph.d", "m.d", "b.a",
+    "m.a", "a.m", "p.m", "p.s",
+];
+
+fn is_terminator(c: char) -> bool {
+    TERMINATORS.contains(&c) || CJK_TERMINATORS.contains(&c)
+}
+
+/// The whitespace-delimited word ending just before `dot`, minus opening punctuation
+fn word_before(chars: &[char], dot: usize) -> String {
+    let mut start = dot;
+    while start > 0 && !chars[start - 1].is_whitespace() {
+        start -= 1;
+    }
+    while start < dot && OPENING_PUNCTUATION.contains(&chars[start]) {
+        start += 1;
+    }
+    chars[start..dot].iter().collect::<String>().to_lowercase()
+}
+
+fn is_abbreviation(chars: &[char], dot: usize) -> bool {
+    let word = word_before(chars, dot);
+    let mut letters = word.chars();
+    let is_initial = matches!((letters.next(), letters.next()), (Some(c), None) if c.is_alphabetic());
+    is_initial || ABBREVIATIONS.contains(&word.as_str())
+}
+
+/// Whether the sentence carries on after the punctuation run `run_start..run_end`,
+/// which is followed by whitespace
+fn continues_after(chars: &[char], run_start: usize, run_end: usize, next: usize) -> bool {
+    if run_end - run_start == 1 && chars[run_start] == '.' && is_abbreviation(chars, run_start) {
+        return true;
+    }
+
+    // A lowercase word on the same line continues the sentence
+    let mut i = next;
+    while i < chars.len() && chars[i].is_whitespace() && chars[i] != '\n' {
+        i += 1;
+    }
+    i < chars.len() && chars[i].is_lowercase()
+}
+
+/// Split `content` into sentences, as trimmed UTF-16 ranges in document order
+pub fn segment_sentences(content: &str) -> Vec<SentenceRange> {
+    let chars: Vec<char> = content.chars().collect();
+    // UTF-16 offset of each char, plus the end of the content
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0i64;
+    for c in &chars {
+        offsets.push(offset);
+        offset += c.len_utf16() as i64;
+    }
+    offsets.push(offset);
+
+    let mut sentences = Vec::new();
+    let mut push = |start: usize, end: usize| {
+        let mut end = end;
+        while end > start && chars[end - 1].is_whitespace() {
+            end -= 1;
+        }
+        if end > start {
+            sentences.push(SentenceRange {
+                sentence_index: sentences.len() as i64,
+                start_position: offsets[start],
+                end_position: offsets[end],
+            });
+        }
+    };
+
+    let mut start: Option<usize> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\n' {
+            if let Some(s) = start.take() {
+                push(s, i);
+            }
+            i += 1;
+            continue;
+        }
+
+        if start.is_none() {
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            start = Some(i);
+        }
+
+        if is_terminator(c) {
+            let mut run_end = i + 1;
+            while run_end < chars.len() && is_terminator(chars[run_end]) {
+                run_end += 1;
+            }
+            let mut end = run_end;
+            while end < chars.len() && CLOSING_PUNCTUATION.contains(&chars[end]) {
+                end += 1;
+            }
+
+            let is_boundary = if chars[i..run_end].iter().any(|c| CJK_TERMINATORS.contains(c)) {
+                true
+            } else {
+                (end == chars.len() || chars[end].is_whitespace())
+                    && !continues_after(&chars, i, run_end, end)
+            };
+
+            if is_boundary {
+                if let Some(s) = start.take() {
+                    push(s, end);
+                }
+            }
+            i = end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    if let Some(s) = start {
+        push(s, chars.len());
+    }
+
+    sentences
+}
+
+/// Whether `text` ends where a sentence does: at a line break, or at terminal
+/// punctuation that isn't an abbreviation's period
+fn ends_at_boundary(text: &str) -> bool {
+    let trimmed = text.trim_end_matches(|c: char| c.is_whitespace() && c != '\n');
+    if trimmed.ends_with('\n') {
+        return true;
+    }
+
+    let chars: Vec<char> = trimmed.chars().collect();
+    let mut end = chars.len();
+    while end > 0 && CLOSING_PUNCTUATION.contains(&chars[end - 1]) {
+        end -= 1;
+    }
+    end > 0
+        && is_terminator(chars[end - 1])
+        && !(chars[end - 1] == '.' && is_abbreviation(&chars, end - 1))
+}
+
+/// UTF-16 offset just past the last sentence in `text` known to be complete, i.e.
+/// followed by another sentence or ending at its terminator, or None if there's no
+/// such sentence
+pub fn last_sentence_end(text: &str) -> Option<usize> {
+    let sentences = segment_sentences(text);
+    let complete = if ends_at_boundary(text) {
+        sentences.len()
+    } else {
+        sentences.len().saturating_sub(1)
+    };
+    complete
+        .checked_sub(1)
+        .map(|i| sentences[i].end_position as usize)
+}
+
+/// Insert segmented sentences on a pooled connection or inside a caller's transaction
+pub async fn store_sentences(
+    conn: &mut SqliteConnection,
+    text_id: i64,
+    sentences: &[SentenceRange],
+) -> Result<()> {
+    for sentence in sentences {
+        sqlx::query!(
+            r#"
+            INSERT INTO sentences (text_id, sentence_index, start_position, end_position)
+            VALUES (?, ?, ?, ?)
+            "#,
+            text_id,
+            sentence.sentence_index,
+            sentence.start_position,
+            sentence.end_position
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(content: &str) -> Vec<String> {
+        let units: Vec<u16> = content.encode_utf16().collect();
+        segment_sentences(content)
+            .iter()
+            .map(|s| String::from_utf16(&units[s.start_position as usize..s.end_position as usize]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_basic_sentences_and_lines() {
+        assert_eq!(
+            texts("First one.  Second one! Third?\n\n== Header ==\nNo punctuation"),
+            vec!["First one.", "Second one!", "Third?", "== Header ==", "No punctuation"]
+        );
+        assert!(segment_sentences("  \n\n ").is_empty());
+    }
+
+    #[test]
+    fn test_abbreviations_decimals_and_initials() {
+        assert_eq!(
+            texts("Dr. Smith paid $3.50 for it, e.g. on Mon. at 5 p.m. sharp. J. R. R. Tolkien wrote it. See example.com now."),
+            vec![
+                "Dr. Smith paid $3.50 for it, e.g. on Mon. at 5 p.m. sharp.",
+                "J. R. R. Tolkien wrote it.",
+                "See example.com now.",
+            ]
+        );
+        assert_eq!(texts("It weighs approx. three tons. Wait... What?!"), vec!["It weighs approx. three tons.", "Wait...", "What?!"]);
+    }
+
+    #[test]
+    fn test_quotes_and_brackets() {
+        assert_eq!(
+            texts("He said \"Stop.\" Then he left. (It was late.) «Oui.» Fin."),
+            vec!["He said \"Stop.\"", "Then he left.", "(It was late.)", "«Oui.»", "Fin."]
+        );
+        assert_eq!(texts("\"Why?\" she asked."), vec!["\"Why?\" she asked."]);
+    }
+
+    #[test]
+    fn test_non_latin_punctuation_and_utf16_offsets() {
+        assert_eq!(texts("今日は晴れ。明日は雨！本当？"), vec!["今日は晴れ。", "明日は雨！", "本当？"]);
+        assert_eq!(texts("यह किताब है। वह घर है।"), vec!["यह किताब है।", "वह घर है।"]);
+        assert_eq!(texts("هل أنت بخير؟ نعم."), vec!["هل أنت بخير؟", "نعم."]);
+
+        let sentences = segment_sentences("🦊 runs. Fox.");
+        assert_eq!((sentences[1].start_position, sentences[1].end_position), (9, 13));
+    }
+
+    #[test]
+    fn test_last_sentence_end() {
+        assert_eq!(last_sentence_end("One. Two. Thr"), Some(9));
+        assert_eq!(last_sentence_end("Only part of one"), None);
+        assert_eq!(last_sentence_end("One. Two."), Some(9));
+        assert_eq!(last_sentence_end("One. \"Two!\"  "), Some(11));
+        assert_eq!(last_sentence_end("One. Two\n"), Some(8));
+        assert_eq!(last_sentence_end("Ask Dr."), None);
+    }
+}
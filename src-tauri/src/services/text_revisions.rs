@@ -0,0 +1,383 @@
+// Text revision service
+//
+// Stores text edit history as compact diffs and rebuilds past versions from them.
+//
+// Each revision records a single replaced span: where it starts (UTF-16 units), the
+// text it removed, and the text it inserted. The latest revision always matches the
+// text's current content, so any earlier version is rebuilt by undoing diffs from
+// newest to oldest. Revision 0 is the content before the first recorded edit.
+//
+// Also provides a line-level diff for showing what changed between two versions, and a
+// paragraph-level one that splits a whole-text replacement into per-paragraph edits.
+
+use crate::services::range_calculator::RangeCalculator;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// A single replaced span between two versions of a text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDiff {
+    pub start: i64,
+    pub removed: String,
+    pub inserted: String,
+}
+
+impl TextDiff {
+    /// The diff that turns `old` into `new`
+    pub fn between(old: &str, new: &str) -> Self {
+        let (start, old_end, new_end) = RangeCalculator::edit_span(old, new);
+        let old_units: Vec<u16> = old.encode_utf16().collect();
+        let new_units: Vec<u16> = new.encode_utf16().collect();
+
+        Self {
+            start,
+            removed: String::from_utf16_lossy(&old_units[start as usize..old_end as usize]),
+            inserted: String::from_utf16_lossy(&new_units[start as usize..new_end as usize]),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.inserted.is_empty()
+    }
+
+    pub fn invert(&self) -> Self {
+        Self {
+            start: self.start,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+        }
+    }
+
+    /// Apply to `content`, checking that the removed text is actually there
+    pub fn apply(&self, content: &str) -> Result<String> {
+        let units: Vec<u16> = content.encode_utf16().collect();
+        let removed: Vec<u16> = self.removed.encode_utf16().collect();
+        let start = self.start as usize;
+        let end = start + removed.len();
+
+        if self.start < 0 || end > units.len() || units[start..end] != removed[..] {
+            return Err(anyhow!("Diff does not match content at position {}", self.start));
+        }
+
+        let mut result: Vec<u16> = Vec::with_capacity(units.len() - removed.len() + self.inserted.len());
+        result.extend_from_slice(&units[..start]);
+        result.extend(self.inserted.encode_utf16());
+        result.extend_from_slice(&units[end..]);
+        Ok(String::from_utf16_lossy(&result))
+    }
+}
+
+/// Rebuild an earlier version by undoing `newer_diffs` (newest first) from `current`
+pub fn rewind<'a>(current: &str, newer_diffs: impl IntoIterator<Item = &'a TextDiff>) -> Result<String> {
+    newer_diffs
+        .into_iter()
+        .try_fold(current.to_string(), |content, diff| diff.invert().apply(&content))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Above this many line pairs the LCS table gets too large, so the changed middle
+/// is reported as a plain delete + insert instead
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// One step aligning two sequences: indices into the old and/or new one
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aligned {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Align two sequences along their longest common subsequence
+fn align(old: &[&str], new: &[&str]) -> Vec<Aligned> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result: Vec<Aligned> = (0..prefix).map(|i| Aligned::Equal(i, i)).collect();
+
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    let old_mid = &old[prefix..old_end];
+    let new_mid = &new[prefix..new_end];
+
+    if old_mid.len() * new_mid.len() > MAX_LCS_CELLS {
+        result.extend((prefix..old_end).map(Aligned::Delete));
+        result.extend((prefix..new_end).map(Aligned::Insert));
+    } else {
+        // lcs[i][j]: longest common subsequence of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                result.push(Aligned::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                result.push(Aligned::Delete(prefix + i));
+                i += 1;
+            } else {
+                result.push(Aligned::Insert(prefix + j));
+                j += 1;
+            }
+        }
+        result.extend((prefix + i..old_end).map(Aligned::Delete));
+        result.extend((prefix + j..new_end).map(Aligned::Insert));
+    }
+
+    result.extend((0..suffix).map(|k| Aligned::Equal(old_end + k, new_end + k)));
+    result
+}
+
+fn diff_line(old: &[&str], new: &[&str], step: Aligned) -> DiffLine {
+    let (op, text) = match step {
+        Aligned::Equal(i, _) => (DiffOp::Equal, old[i]),
+        Aligned::Delete(i) => (DiffOp::Delete, old[i]),
+        Aligned::Insert(j) => (DiffOp::Insert, new[j]),
+    };
+    DiffLine {
+        op,
+        text: text.to_string(),
+    }
+}
+
+/// Line-level diff of two versions
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    align(&old_lines, &new_lines)
+        .into_iter()
+        .map(|step| diff_line(&old_lines, &new_lines, step))
+        .collect()
+}
+
+/// A paragraph (run of non-blank lines) and its UTF-16 span in the text
+struct Paragraph<'a> {
+    text: &'a str,
+    start: i64,
+    end: i64,
+}
+
+fn paragraphs(content: &str) -> Vec<Paragraph<'_>> {
+    let mut result = Vec::new();
+    // Byte and UTF-16 offsets of the current line, and where the open paragraph began
+    let (mut byte, mut unit) = (0usize, 0i64);
+    let mut open: Option<(usize, i64)> = None;
+    let mut last_end = (0usize, 0i64);
+
+    for line in content.split('\n') {
+        let line_units = line.encode_utf16().count() as i64;
+        if line.trim().is_empty() {
+            if let Some((start_byte, start_unit)) = open.take() {
+                result.push(Paragraph {
+                    text: &content[start_byte..last_end.0],
+                    start: start_unit,
+                    end: last_end.1,
+                });
+            }
+        } else {
+            open.get_or_insert((byte, unit));
+            last_end = (byte + line.trim_end().len(), unit + line.trim_end().encode_utf16().count() as i64);
+        }
+        byte += line.len() + 1;
+        unit += line_units + 1;
+    }
+    if let Some((start_byte, start_unit)) = open {
+        result.push(Paragraph {
+            text: &content[start_byte..last_end.0],
+            start: start_unit,
+            end: last_end.1,
+        });
+    }
+    result
+}
+
+/// A paragraph-level comparison of two versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParagraphDiff {
+    /// Every paragraph of both versions, in order, as kept, removed or added
+    pub paragraphs: Vec<DiffLine>,
+    /// Edits turning the old version into the new one, by position in the old version
+    /// and in ascending order. Applied from last to first, each one's position is still
+    /// valid, and text between unchanged paragraphs is never touched.
+    pub edits: Vec<TextDiff>,
+}
+
+/// Paragraph-level diff of two versions
+pub fn diff_paragraphs(old: &str, new: &str) -> ParagraphDiff {
+    let old_paragraphs = paragraphs(old);
+    let new_paragraphs = paragraphs(new);
+    let old_texts: Vec<&str> = old_paragraphs.iter().map(|p| p.text).collect();
+    let new_texts: Vec<&str> = new_paragraphs.iter().map(|p| p.text).collect();
+    let steps = align(&old_texts, &new_texts);
+
+    let old_units: Vec<u16> = old.encode_utf16().collect();
+    let new_units: Vec<u16> = new.encode_utf16().collect();
+    let slice = |units: &[u16], start: i64, end: i64| String::from_utf16_lossy(&units[start as usize..end as usize]);
+
+    // Each stretch between two unchanged paragraphs (or a text's start or end) that
+    // differs becomes one edit, narrowed to the part that actually changed
+    let mut edits = Vec::new();
+    let mut previous = (0, 0);
+    let anchors = steps
+        .iter()
+        .filter_map(|step| match step {
+            Aligned::Equal(i, j) => Some((
+                (old_paragraphs[*i].start, new_paragraphs[*j].start),
+                (old_paragraphs[*i].end, new_paragraphs[*j].end),
+            )),
+            _ => None,
+        })
+        .chain(std::iter::once((
+            (old_units.len() as i64, new_units.len() as i64),
+            (old_units.len() as i64, new_units.len() as i64),
+        )));
+    for ((old_start, new_start), end) in anchors {
+        let removed = slice(&old_units, previous.0, old_start);
+        let inserted = slice(&new_units, previous.1, new_start);
+        if removed != inserted {
+            let narrowed = TextDiff::between(&removed, &inserted);
+            edits.push(TextDiff {
+                start: previous.0 + narrowed.start,
+                ..narrowed
+            });
+        }
+        previous = end;
+    }
+
+    ParagraphDiff {
+        paragraphs: steps
+            .into_iter()
+            .map(|step| diff_line(&old_texts, &new_texts, step))
+            .collect(),
+        edits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_roundtrip() {
+        let old = "The quick brown fox 🦊 jumps.";
+        let new = "The quick red fox 🦊 jumps high.";
+        let diff = TextDiff::between(old, new);
+
+        assert_eq!(diff.apply(old).unwrap(), new);
+        assert_eq!(diff.invert().apply(new).unwrap(), old);
+    }
+
+    #[test]
+    fn test_diff_utf16_start() {
+        let diff = TextDiff::between("🦊 fox", "🦊 cat");
+        assert_eq!(diff.start, 3);
+        assert_eq!(diff.removed, "fox");
+        assert_eq!(diff.inserted, "cat");
+
+        let emoji = TextDiff::between("a🦊b", "a🦁b");
+        assert_eq!((emoji.removed.as_str(), emoji.inserted.as_str()), ("🦊", "🦁"));
+    }
+
+    #[test]
+    fn test_apply_rejects_mismatched_content() {
+        let diff = TextDiff::between("hello world", "hello there");
+        assert!(diff.apply("hello earth").is_err());
+    }
+
+    #[test]
+    fn test_rewind_through_history() {
+        let v0 = "one two three";
+        let v1 = "one 2 three";
+        let v2 = "one 2 three four";
+        let d1 = TextDiff::between(v0, v1);
+        let d2 = TextDiff::between(v1, v2);
+
+        assert_eq!(rewind(v2, [&d2]).unwrap(), v1);
+        assert_eq!(rewind(v2, [&d2, &d1]).unwrap(), v0);
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "a\nb\nc\nd";
+        let new = "a\nc\nx\nd";
+        let diff = diff_lines(old, new);
+        let ops: Vec<(DiffOp, &str)> = diff.iter().map(|l| (l.op, l.text.as_str())).collect();
+
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Insert, "x"),
+                (DiffOp::Equal, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_paragraphs() {
+        let old = "Intro 🦊.\n\nKept one.\n\nChanged here.\n\nRemoved.\n\nKept two.";
+        let new = "Intro 🦊.\n\nKept one.\n\nChanged there.\n\nKept two.\n\nAdded.\n";
+        let diff = diff_paragraphs(old, new);
+        let ops: Vec<(DiffOp, &str)> = diff.paragraphs.iter().map(|l| (l.op, l.text.as_str())).collect();
+
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "Intro 🦊."),
+                (DiffOp::Equal, "Kept one."),
+                (DiffOp::Delete, "Changed here."),
+                (DiffOp::Delete, "Removed."),
+                (DiffOp::Insert, "Changed there."),
+                (DiffOp::Equal, "Kept two."),
+                (DiffOp::Insert, "Added."),
+            ]
+        );
+
+        // One narrowed edit per changed stretch, positions in UTF-16 units of the old text
+        let edits: Vec<(i64, &str, &str)> = diff
+            .edits
+            .iter()
+            .map(|e| (e.start, e.removed.as_str(), e.inserted.as_str()))
+            .collect();
+        assert_eq!(
+            edits,
+            vec![(30, "here.\n\nRemoved", "there"), (56, "", "\n\nAdded.\n")]
+        );
+
+        let rebuilt = diff.edits.iter().rev().try_fold(old.to_string(), |content, edit| edit.apply(&content));
+        assert_eq!(rebuilt.unwrap(), new);
+    }
+}
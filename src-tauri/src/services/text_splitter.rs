@@ -0,0 +1,106 @@
+// Text splitting service
+//
+// Divides a long text into sections at its headers, so each section can become its
+// own text. Splitting can stop at a header level: with a maximum level of 2, only
+// "== Section ==" / "## Section" headers (and shallower) start a new section and
+// subsections stay inside it.
+//
+// Positions are UTF-16 code units in the raw content. A section starts at its header
+// line, or at 0 for the lead before the first header, and ends before the next
+// section's header with trailing whitespace left out.
+
+use crate::services::parser::detect_header_ranges;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Header title, None for the lead before the first header
+    pub title: Option<String>,
+    pub level: Option<usize>,
+    pub start: i64,
+    pub end: i64,
+    pub content: String,
+}
+
+/// Split `content` at headers of `max_level` or shallower (every header when None)
+/// Whitespace-only sections are dropped
+pub fn split_sections(content: &str, max_level: Option<usize>) -> Vec<Section> {
+    let units: Vec<u16> = content.encode_utf16().collect();
+    let headers: Vec<_> = detect_header_ranges(content)
+        .into_iter()
+        .filter(|h| max_level.is_none_or(|max| h.level <= max))
+        .collect();
+
+    let mut starts: Vec<(i64, Option<String>, Option<usize>)> = vec![(0, None, None)];
+    starts.extend(
+        headers
+            .into_iter()
+            .map(|h| (h.start_position, Some(h.title), Some(h.level))),
+    );
+
+    let boundaries: Vec<i64> = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _, _)| *start)
+        .chain(std::iter::once(units.len() as i64))
+        .collect();
+
+    starts
+        .into_iter()
+        .zip(boundaries)
+        .filter_map(|((start, title, level), next_start)| {
+            let raw = String::from_utf16_lossy(&units[start as usize..next_start as usize]);
+            let trimmed = raw.trim_end();
+            if trimmed.trim_start().is_empty() {
+                return None;
+            }
+            Some(Section {
+                title,
+                level,
+                start,
+                end: start + trimmed.encode_utf16().count() as i64,
+                content: trimmed.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = "Lead text.\n\n== History ==\n\nOld 🏰.\n\n=== Early ===\n\nVery old.\n\n== Today ==\n\nNew.\n";
+
+    #[test]
+    fn test_split_every_header() {
+        let sections = split_sections(ARTICLE, None);
+        let titles: Vec<Option<&str>> = sections.iter().map(|s| s.title.as_deref()).collect();
+        assert_eq!(titles, vec![None, Some("History"), Some("Early"), Some("Today")]);
+        assert_eq!(sections[0].content, "Lead text.");
+        assert_eq!(sections[3].content, "== Today ==\n\nNew.");
+    }
+
+    #[test]
+    fn test_split_down_to_level_keeps_subsections() {
+        let sections = split_sections(ARTICLE, Some(2));
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[1].content, "== History ==\n\nOld 🏰.\n\n=== Early ===\n\nVery old.");
+    }
+
+    #[test]
+    fn test_section_positions_are_utf16() {
+        let sections = split_sections(ARTICLE, Some(2));
+        let units: Vec<u16> = ARTICLE.encode_utf16().collect();
+        for section in &sections {
+            let slice = String::from_utf16_lossy(&units[section.start as usize..section.end as usize]);
+            assert_eq!(slice, section.content);
+        }
+    }
+
+    #[test]
+    fn test_split_skips_empty_lead() {
+        let sections = split_sections("\n\n== Only ==\n\nBody", None);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].start, 2);
+        assert_eq!(sections[0].level, Some(2));
+    }
+}
@@ -0,0 +1,508 @@
+// Web article service
+//
+// Imports an article from any web page, readability style:
+// - Paragraphs score their parent (and half their grandparent) by length and commas;
+//   class and id names hint at content ("article", "post") or clutter ("comment",
+//   "sidebar"), and a node's score shrinks with its share of link text. The best
+//   scoring node is taken as the article.
+// - Navigation, headers, footers, forms, ads, share widgets and comments are dropped,
+//   both while scoring and from the article itself.
+// - The article is then converted like a Wikipedia article
+//   (`wikipedia::html_to_plain_text`), with links made absolute.
+//
+// Title, author, publish date, site name and canonical URL come from <meta> and <link>
+// tags and schema.org JSON-LD, falling back to the page itself.
+
+use crate::services::file_import::{normalize_content, normalize_date, AUTHOR_META_NAMES, DATE_META_NAMES};
+use crate::services::http_client::HttpClient;
+use crate::services::wikipedia::html_to_plain_text;
+use anyhow::{anyhow, Result};
+use ego_tree::NodeId;
+use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static UNLIKELY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(^|[-_\s])(ads?|advert\w*|banner|breadcrumbs?|comments?|community|cookie\w*|disqus|footer|gdpr|masthead|menu|modal|nav\w*|newsletter\w*|outbrain|pager|popup|promo\w*|related|replies|share\w*|sidebar|social|sponsor\w*|subscribe|taboola|widget)($|[-_\s])",
+    )
+    .unwrap()
+});
+
+static POSITIVE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|hentry|main|page|post|story|text").unwrap()
+});
+
+static WHITESPACE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// Elements that are never part of an article
+const UNWANTED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "iframe", "svg", "canvas", "dialog", "menu", "select", "textarea", "img",
+    "picture", "video", "audio", "object",
+];
+
+const UNWANTED_ROLES: &[&str] = &["navigation", "banner", "contentinfo", "complementary", "dialog", "alert"];
+
+/// Tags kept as they are when cleaning the article; other containers become <div>
+const KEPT_TAGS: &[&str] = &[
+    "p", "h1", "h2", "h3", "h4", "h5", "h6", "ul", "ol", "li", "dl", "dt", "dd", "blockquote",
+    "table", "caption", "thead", "tbody", "tfoot", "tr", "th", "td",
+    "a", "em", "strong", "b", "i", "code", "sup", "sub", "span", "small", "mark", "abbr",
+];
+
+/// Tags that flow within a paragraph
+const INLINE_TAGS: &[&str] = &[
+    "a", "em", "strong", "b", "i", "u", "code", "sup", "sub", "span", "small", "mark", "abbr",
+    "cite", "q", "time", "br", "s", "del", "ins", "kbd", "var",
+];
+
+/// Paragraphs shorter than this don't count towards a node's score
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebArticle {
+    pub title: String,
+    pub content: String,
+    pub author: Option<String>,
+    pub publication_date: Option<String>,
+    pub site_name: Option<String>,
+    pub canonical_url: String,
+}
+
+/// Fetch a web page and extract its article
+pub async fn fetch_web_article(client: &HttpClient, url: &str) -> Result<WebArticle> {
+    let page_url = Url::parse(url.trim()).map_err(|e| anyhow!("Invalid URL: {}", e))?;
+    if !matches!(page_url.scheme(), "http" | "https") {
+        return Err(anyhow!("URL must start with http:// or https://"));
+    }
+
+    let response = client.get(page_url.as_str()).await?;
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|content_type| content_type.contains("html"));
+    if !is_html {
+        return Err(anyhow!("URL is not a web page"));
+    }
+
+    let html = response.text().await?;
+    extract_article(&html, &page_url)
+}
+
+/// Extract the article and its metadata from a page's HTML
+pub fn extract_article(html: &str, page_url: &Url) -> Result<WebArticle> {
+    let document = Html::parse_document(html);
+    let metas = meta_tags(&document);
+    let meta = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| metas.get(*name).filter(|value| !value.starts_with("http")).cloned())
+    };
+    let linked_data = LinkedData::from_document(&document);
+
+    let content_root = find_content_root(&document).ok_or_else(|| anyhow!("Page has no body"))?;
+    let mut cleaned = String::new();
+    write_clean_html(content_root, page_url, &mut cleaned);
+    let content = normalize_content(&html_to_plain_text(&cleaned)?);
+    if content.is_empty() {
+        return Err(anyhow!("No article text found on the page"));
+    }
+
+    let title = meta(&["og:title", "twitter:title"])
+        .or(linked_data.headline)
+        .or_else(|| first_text(&document, "h1"))
+        .or_else(|| first_text(&document, "title"))
+        .unwrap_or_else(|| page_url.to_string());
+
+    let author = meta(AUTHOR_META_NAMES)
+        .or(linked_data.author)
+        .or_else(|| first_text(&document, "[rel=\"author\"], [itemprop=\"author\"]"));
+
+    let publication_date = meta(DATE_META_NAMES)
+        .or(linked_data.date_published)
+        .or_else(|| {
+            let selector = Selector::parse("time[datetime]").unwrap();
+            document
+                .select(&selector)
+                .next()
+                .and_then(|time| time.value().attr("datetime"))
+                .map(str::to_string)
+        })
+        .map(|date| normalize_date(&date));
+
+    let canonical_selector = Selector::parse("link[rel=\"canonical\"]").unwrap();
+    let canonical_url = document
+        .select(&canonical_selector)
+        .next()
+        .and_then(|link| link.value().attr("href"))
+        .or_else(|| metas.get("og:url").map(String::as_str))
+        .and_then(|href| page_url.join(href.trim()).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    Ok(WebArticle {
+        title,
+        content,
+        author,
+        publication_date,
+        site_name: meta(&["og:site_name", "application-name"]).or(linked_data.publisher),
+        canonical_url: canonical_url.to_string(),
+    })
+}
+
+/// <meta> contents by lowercased name or property, first one winning
+fn meta_tags(document: &Html) -> HashMap<String, String> {
+    let selector = Selector::parse("meta[content]").unwrap();
+    let mut metas = HashMap::new();
+    for meta in document.select(&selector) {
+        let element = meta.value();
+        let Some(name) = element.attr("name").or_else(|| element.attr("property")) else {
+            continue;
+        };
+        let content = element.attr("content").unwrap_or("").trim();
+        if !content.is_empty() {
+            metas.entry(name.to_lowercase()).or_insert_with(|| content.to_string());
+        }
+    }
+    metas
+}
+
+fn first_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    document
+        .select(&selector)
+        .map(|element| collapse_whitespace(&element.text().collect::<String>()))
+        .find(|text| !text.is_empty())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    WHITESPACE_REGEX.replace_all(text, " ").trim().to_string()
+}
+
+/// Article fields from schema.org JSON-LD
+#[derive(Default)]
+struct LinkedData {
+    headline: Option<String>,
+    author: Option<String>,
+    date_published: Option<String>,
+    publisher: Option<String>,
+}
+
+impl LinkedData {
+    fn from_document(document: &Html) -> Self {
+        let selector = Selector::parse("script[type=\"application/ld+json\"]").unwrap();
+        let mut objects = Vec::new();
+        for script in document.select(&selector) {
+            if let Ok(value) = serde_json::from_str::<Value>(&script.text().collect::<String>()) {
+                collect_objects(value, &mut objects);
+            }
+        }
+
+        let Some(article) = objects.iter().find(|object| object.get("headline").is_some()) else {
+            return Self::default();
+        };
+        let string = |key: &str| article.get(key).and_then(Value::as_str).map(str::to_string);
+
+        Self {
+            headline: string("headline"),
+            author: article.get("author").and_then(names).filter(|authors| !authors.is_empty()),
+            date_published: string("datePublished"),
+            publisher: article.get("publisher").and_then(names),
+        }
+    }
+}
+
+/// Flatten JSON-LD arrays and @graph lists into their objects
+fn collect_objects(value: Value, objects: &mut Vec<Value>) {
+    match value {
+        Value::Array(items) => items.into_iter().for_each(|item| collect_objects(item, objects)),
+        Value::Object(mut object) => {
+            if let Some(graph) = object.remove("@graph") {
+                collect_objects(graph, objects);
+            }
+            objects.push(Value::Object(object));
+        }
+        _ => {}
+    }
+}
+
+/// A JSON-LD person or organization (or a list of them) as comma-separated names
+fn names(value: &Value) -> Option<String> {
+    match value {
+        Value::String(name) => Some(name.trim().to_string()),
+        Value::Object(object) => object.get("name").and_then(Value::as_str).map(|name| name.trim().to_string()),
+        Value::Array(items) => Some(items.iter().filter_map(names).collect::<Vec<_>>().join(", ")),
+        _ => None,
+    }
+}
+
+/// Whether an element is clutter: by tag, ARIA role, or class and id names
+fn is_unwanted(element: ElementRef) -> bool {
+    let value = element.value();
+    let tag = value.name();
+    if UNWANTED_TAGS.contains(&tag) {
+        return true;
+    }
+    if value.attr("role").is_some_and(|role| UNWANTED_ROLES.contains(&role)) {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if matches!(tag, "html" | "body" | "article" | "main") {
+        return false;
+    }
+
+    let names = format!("{} {}", value.attr("class").unwrap_or(""), value.attr("id").unwrap_or(""));
+    UNLIKELY_REGEX.is_match(&names)
+}
+
+fn has_unwanted_ancestor(element: ElementRef) -> bool {
+    std::iter::successors(Some(element), |e| e.parent().and_then(ElementRef::wrap)).any(is_unwanted)
+}
+
+fn class_weight(element: ElementRef) -> f64 {
+    let value = element.value();
+    let names = format!("{} {}", value.attr("class").unwrap_or(""), value.attr("id").unwrap_or(""));
+    let mut weight = 0.0;
+    if POSITIVE_REGEX.is_match(&names) {
+        weight += 25.0;
+    }
+    if UNLIKELY_REGEX.is_match(&names) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+fn tag_weight(tag: &str) -> f64 {
+    match tag {
+        "article" => 10.0,
+        "div" | "main" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+/// Share of an element's text inside links
+fn link_density(element: ElementRef) -> f64 {
+    let text_length = element.text().map(|t| t.trim().len()).sum::<usize>();
+    if text_length == 0 {
+        return 0.0;
+    }
+    let link_selector = Selector::parse("a").unwrap();
+    let link_length: usize = element
+        .select(&link_selector)
+        .map(|link| link.text().map(|t| t.trim().len()).sum::<usize>())
+        .sum();
+    link_length as f64 / text_length as f64
+}
+
+/// The element holding the article, or the body when no paragraph stands out
+fn find_content_root(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraph_selector = Selector::parse("p, pre, td").unwrap();
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+
+    for paragraph in document.select(&paragraph_selector) {
+        if has_unwanted_ancestor(paragraph) {
+            continue;
+        }
+        let text = collapse_whitespace(&paragraph.text().collect::<String>());
+        if text.chars().count() < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (text.chars().count() as f64 / 100.0).min(3.0);
+
+        let ancestors = std::iter::successors(paragraph.parent().and_then(ElementRef::wrap), |e| {
+            e.parent().and_then(ElementRef::wrap)
+        });
+        for (level, ancestor) in ancestors.take(2).enumerate() {
+            let base = tag_weight(ancestor.value().name()) + class_weight(ancestor);
+            let entry = scores.entry(ancestor.id()).or_insert(base);
+            *entry += if level == 0 { score } else { score / 2.0 };
+        }
+    }
+
+    let best = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((element, score * (1.0 - link_density(element))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(element, _)| element);
+
+    best.or_else(|| {
+        let body_selector = Selector::parse("body").unwrap();
+        document.select(&body_selector).next()
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn is_inline(node: ego_tree::NodeRef<Node>) -> bool {
+    match node.value() {
+        Node::Text(_) => true,
+        Node::Element(element) => INLINE_TAGS.contains(&element.name()),
+        _ => false,
+    }
+}
+
+/// Serialize an element without clutter, attributes (except absolute link targets and
+/// table cell spans) or unknown tags. Text loose in a container is wrapped in a paragraph.
+fn write_clean_html(element: ElementRef, page_url: &Url, out: &mut String) {
+    let tag = element.value().name();
+    let tag = match tag {
+        "pre" => "p",
+        _ if KEPT_TAGS.contains(&tag) => tag,
+        _ => "div",
+    };
+
+    if tag == "a" {
+        match element.value().attr("href").and_then(|href| page_url.join(href).ok()) {
+            Some(href) if matches!(href.scheme(), "http" | "https") => {
+                out.push_str(&format!("<a href=\"{}\">", href.as_str().replace('"', "%22")));
+            }
+            _ => out.push_str("<a>"),
+        }
+    } else if matches!(tag, "th" | "td") {
+        out.push_str(&format!("<{}", tag));
+        for name in ["colspan", "rowspan"] {
+            if let Some(span) = element.value().attr(name).and_then(|span| span.trim().parse::<u32>().ok()) {
+                out.push_str(&format!(" {}=\"{}\"", name, span));
+            }
+        }
+        out.push('>');
+    } else {
+        out.push_str(&format!("<{}>", tag));
+    }
+
+    let wraps_loose_text = tag == "div";
+    let mut inline_run = String::new();
+    for child in element.children() {
+        if wraps_loose_text && is_inline(child) {
+            write_node(child, page_url, &mut inline_run);
+            continue;
+        }
+        if !inline_run.trim().is_empty() {
+            out.push_str(&format!("<p>{}</p>", inline_run.trim()));
+        }
+        inline_run.clear();
+        write_node(child, page_url, out);
+    }
+    if !inline_run.trim().is_empty() {
+        out.push_str(&format!("<p>{}</p>", inline_run.trim()));
+    }
+
+    out.push_str(&format!("</{}>", tag));
+}
+
+fn write_node(node: ego_tree::NodeRef<Node>, page_url: &Url, out: &mut String) {
+    match node.value() {
+        Node::Text(text) => out.push_str(&escape_html(&WHITESPACE_REGEX.replace_all(text, " "))),
+        Node::Element(element) if element.name() == "br" => out.push(' '),
+        Node::Element(_) => {
+            if let Some(child) = ElementRef::wrap(node) {
+                if !is_unwanted(child) {
+                    write_clean_html(child, page_url, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::test_server::serve_once;
+    use tokio::net::TcpListener;
+
+    const NEWS_ARTICLE: &str = include_str!("../../tests/fixtures/web/news_article.html");
+
+    const EXPECTED_CONTENT: &str = "After decades of decline, sleeper services are returning across Europe, with new routes linking capitals overnight.\n\n\
+        Operators say demand has grown every year since 2020, and that [last year's report](https://news.example.com/2023/rail-report) underestimated it, especially among younger travellers.\n\n\
+        == A slower way to travel ==\n\n\
+        Passengers trade speed for a bed, a view and a morning arrival in the city centre, which many find more relaxing than flying.\n\n\
+        Loose text outside of any paragraph still belongs to the story, as it did here.\n\n\
+        > We sold out the first month in two days.";
+
+    #[test]
+    fn test_extract_article_and_metadata() {
+        let page_url = Url::parse("https://news.example.com/2024/02/night-trains?utm_source=feed").unwrap();
+        let article = extract_article(NEWS_ARTICLE, &page_url).unwrap();
+
+        assert_eq!(article.title, "Night trains are back");
+        // article:author is a profile URL, so the JSON-LD authors are used
+        assert_eq!(article.author.as_deref(), Some("Ana Rail, Ben Track"));
+        assert_eq!(article.publication_date.as_deref(), Some("2024-02-11"));
+        assert_eq!(article.site_name.as_deref(), Some("The Daily Fixture"));
+        assert_eq!(article.canonical_url, "https://news.example.com/2024/02/night-trains");
+        assert_eq!(article.content, EXPECTED_CONTENT);
+    }
+
+    #[test]
+    fn test_extract_article_without_metadata() {
+        let html = "<html><head><title>Plain page</title></head><body>\
+            <div id=\"menu\"><p>Home, About, Contact, Archive, and every other page on this site.</p></div>\
+            <div><p>The only real paragraph on this page, long enough to count as content.</p>\
+            <p>By <span itemprop=\"author\">Cy Writer</span>, <time datetime=\"2021-07-04\">July 4</time>.</p></div>\
+            </body></html>";
+        let page_url = Url::parse("https://example.org/page").unwrap();
+        let article = extract_article(html, &page_url).unwrap();
+
+        assert_eq!(article.title, "Plain page");
+        assert_eq!(article.author.as_deref(), Some("Cy Writer"));
+        assert_eq!(article.publication_date.as_deref(), Some("2021-07-04"));
+        assert_eq!(article.site_name, None);
+        assert_eq!(article.canonical_url, "https://example.org/page");
+        assert!(article.content.starts_with("The only real paragraph"));
+        assert!(!article.content.contains("Contact"));
+    }
+
+    #[test]
+    fn test_extract_article_keeps_lists_tables_and_definitions() {
+        let html = "<html><head><title>Camping</title></head><body><article>\
+            <p>Pack the following items for an overnight trip in the hills, and check them twice.</p>\
+            <ul><li>a tent</li><li>a stove<ul><li>with fuel</li></ul></li></ul>\
+            <table class=\"prices\"><caption>Costs</caption><tr><th>Item</th><th>Price</th></tr>\
+            <tr><td>Tent</td><td>120</td></tr><tr><td colspan=\"2\">Prices in euros</td></tr></table>\
+            <dl><dt>Bivvy</dt><dd>A small shelter for one sleeper.</dd></dl>\
+            </article></body></html>";
+        let page_url = Url::parse("https://example.org/camping").unwrap();
+        let article = extract_article(html, &page_url).unwrap();
+
+        assert_eq!(
+            article.content,
+            "Pack the following items for an overnight trip in the hills, and check them twice.\n\n\
+             - a tent\n- a stove\n  - with fuel\n\n\
+             Costs\n\n| Item | Price |\n| --- | --- |\n| Tent | 120 |\n| Prices in euros |  |\n\n\
+             Bivvy\n: A small shelter for one sleeper."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_through_local_stand_in() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener, "text/html", NEWS_ARTICLE));
+
+        let client = HttpClient::with_base_url(&base_url).unwrap();
+        let article = fetch_web_article(&client, "https://news.example.com/2024/02/night-trains?ref=home")
+            .await
+            .unwrap();
+
+        assert_eq!(server.await.unwrap(), "GET /2024/02/night-trains?ref=home HTTP/1.1");
+        assert_eq!(article.canonical_url, "https://news.example.com/2024/02/night-trains");
+        assert_eq!(article.content, EXPECTED_CONTENT);
+
+        assert!(fetch_web_article(&client, "ftp://example.com/file").await.is_err());
+    }
+}
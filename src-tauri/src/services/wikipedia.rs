@@ -1,6 +1,8 @@
+use crate::services::http_client::HttpClient;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use regex::Regex;
+use reqwest::Url;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
@@ -9,11 +11,33 @@ static REFERENCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\[\d+\]").unwrap()
 });
 
+/// Wikimedia projects, served from `<language>.<project>` with the API at /w/api.php
+const WIKIMEDIA_PROJECTS: &[&str] = &[
+    "wikipedia.org",
+    "wiktionary.org",
+    "wikibooks.org",
+    "wikiquote.org",
+    "wikisource.org",
+    "wikiversity.org",
+    "wikivoyage.org",
+    "wikinews.org",
+];
+
+/// Path prefixes MediaWiki sites serve articles under
+const ARTICLE_PATH_PREFIXES: &[&str] = &["/wiki/", "/index.php/", "/title/"];
+
+/// Origin relative links resolve against when the article's site is unknown
+const DEFAULT_ORIGIN: &str = "https://en.wikipedia.org";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WikipediaArticle {
     pub title: String,
     pub extract: String,
     pub url: String,
+    pub language: Option<String>,
+    pub page_id: i64,
+    /// The revision parsed, absent from some older MediaWiki versions
+    pub revision_id: Option<i64>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -40,15 +64,78 @@ impl std::fmt::Display for WikipediaError {
 
 impl std::error::Error for WikipediaError {}
 
+/// The MediaWiki site an article URL belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaWikiSite {
+    /// The site's api.php endpoint
+    pub api_url: String,
+    /// Scheme and host that relative links in the parsed HTML resolve against
+    pub origin: String,
+    /// Language from the subdomain of a Wikimedia project
+    pub language: Option<String>,
+}
+
+/// Find the MediaWiki site serving `url`: any language edition of a Wikimedia project
+/// (mobile "m." hosts included), or a third-party wiki whose api.php URL is listed in
+/// `extra_api_urls`
+pub fn resolve_site(url: &str, extra_api_urls: &[String]) -> Result<MediaWikiSite> {
+    let parsed = Url::parse(url.trim())
+        .map_err(|e| anyhow!(WikipediaError::InvalidUrl(format!("{}: {}", url, e))))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| anyhow!(WikipediaError::InvalidUrl("URL has no host".to_string())))?
+        .to_lowercase();
+
+    for api_url in extra_api_urls.iter().map(|u| u.trim()).filter(|u| !u.is_empty()) {
+        let Ok(api) = Url::parse(api_url) else {
+            continue;
+        };
+        if api.host_str().is_some_and(|h| h.eq_ignore_ascii_case(&host)) && api.port() == parsed.port() {
+            return Ok(MediaWikiSite {
+                api_url: api.to_string(),
+                origin: api.origin().ascii_serialization(),
+                language: None,
+            });
+        }
+    }
+
+    for project in WIKIMEDIA_PROJECTS {
+        let Some(subdomain) = host.strip_suffix(project).and_then(|h| h.strip_suffix('.')) else {
+            continue;
+        };
+        // "de.m.wikipedia.org" is the mobile view of "de.wikipedia.org"
+        let labels: Vec<&str> = subdomain.split('.').filter(|l| *l != "m").collect();
+        let [language] = labels.as_slice() else {
+            break;
+        };
+        if *language == "www" {
+            break;
+        }
+        let site_host = format!("{}.{}", language, project);
+        return Ok(MediaWikiSite {
+            api_url: format!("https://{}/w/api.php", site_host),
+            origin: format!("https://{}", site_host),
+            language: Some(language.to_string()),
+        });
+    }
+
+    Err(anyhow!(WikipediaError::InvalidUrl(format!(
+        "{} is not a Wikimedia project or a configured MediaWiki site",
+        host
+    ))))
+}
+
 #[derive(Debug, Deserialize)]
 struct WikiApiResponse {
-    parse: WikiApiParse,
+    parse: Option<WikiApiParse>,
+    error: Option<WikiApiError>,
 }
 
 #[derive(Debug, Deserialize)]
 struct WikiApiParse {
     title: String,
     pageid: i64,
+    revid: Option<i64>,
     text: WikiApiText,
 }
 
@@ -58,60 +145,143 @@ struct WikiApiText {
     content: String,
 }
 
-pub async fn fetch_wikipedia_article(url: &str) -> Result<WikipediaArticle> {
-    let title = extract_title_from_url(url)?;
-    let encoded_title = urlencoding::encode(&title);
+#[derive(Debug, Deserialize)]
+struct WikiApiError {
+    code: String,
+    info: String,
+}
 
-    let api_url = format!(
-        "https://en.wikipedia.org/w/api.php?action=parse&page={}&format=json&prop=text",
-        encoded_title
-    );
+/// Fetch and convert the current revision of the article at `url` from the parse API of
+/// its site (see `resolve_site`). Requests go through `client`, so tests can point it at
+/// a local server.
+pub async fn fetch_wikipedia_article(
+    client: &HttpClient,
+    url: &str,
+    extra_api_urls: &[String],
+) -> Result<WikipediaArticle> {
+    fetch_article(client, url, extra_api_urls, Page::Latest).await
+}
 
-    let client = reqwest::Client::builder()
-        .user_agent("Trivium/0.1.0 (Incremental Reading Application; contact@example.com)")
-        .build()
-        .map_err(|e| anyhow!(WikipediaError::NetworkError(e.to_string())))?;
+/// `fetch_wikipedia_article` for the page with `page_id` on the URL's site, which
+/// still finds an article that has been renamed since `url` was stored
+pub async fn fetch_wikipedia_page(
+    client: &HttpClient,
+    url: &str,
+    extra_api_urls: &[String],
+    page_id: i64,
+) -> Result<WikipediaArticle> {
+    fetch_article(client, url, extra_api_urls, Page::Id(page_id)).await
+}
+
+/// `fetch_wikipedia_article` for a given revision of the article
+pub async fn fetch_wikipedia_revision(
+    client: &HttpClient,
+    url: &str,
+    extra_api_urls: &[String],
+    revision_id: i64,
+) -> Result<WikipediaArticle> {
+    fetch_article(client, url, extra_api_urls, Page::Revision(revision_id)).await
+}
+
+/// What a parse request asks for
+enum Page {
+    /// The latest revision of the page titled in the URL
+    Latest,
+    /// The latest revision of the page with this id
+    Id(i64),
+    Revision(i64),
+}
+
+async fn fetch_article(
+    client: &HttpClient,
+    url: &str,
+    extra_api_urls: &[String],
+    page: Page,
+) -> Result<WikipediaArticle> {
+    let site = resolve_site(url, extra_api_urls)?;
+    let title = extract_title_from_url(url)?;
+
+    let mut api_url = Url::parse(&site.api_url)
+        .map_err(|e| anyhow!(WikipediaError::InvalidUrl(format!("{}: {}", site.api_url, e))))?;
+    {
+        let mut query = api_url.query_pairs_mut();
+        query.append_pair("action", "parse");
+        match page {
+            Page::Latest => query.append_pair("page", &title),
+            Page::Id(page_id) => query.append_pair("pageid", &page_id.to_string()),
+            Page::Revision(revision_id) => query.append_pair("oldid", &revision_id.to_string()),
+        };
+        query.append_pair("format", "json").append_pair("prop", "text|revid");
+    }
 
     let response = client
-        .get(&api_url)
-        .send()
+        .get(api_url.as_str())
         .await
         .map_err(|e| anyhow!(WikipediaError::NetworkError(e.to_string())))?;
 
-    if !response.status().is_success() {
-        return Err(anyhow!(WikipediaError::NetworkError(format!(
-            "HTTP error: {}",
-            response.status()
-        ))));
-    }
-
     let api_response: WikiApiResponse = response
         .json()
         .await
         .map_err(|e| anyhow!(WikipediaError::ParseError(e.to_string())))?;
 
-    let html_content = &api_response.parse.text.content;
-    let plain_text = html_to_plain_text(html_content)?;
-    let text_without_refs = strip_reference_indicators(&plain_text);
-    let cleaned_text = clean_empty_sections(&text_without_refs);
+    let parse = match (api_response.parse, api_response.error) {
+        (Some(parse), _) => parse,
+        (None, Some(error)) if matches!(error.code.as_str(), "missingtitle" | "nosuchpageid" | "nosuchrevid") => {
+            return Err(anyhow!(WikipediaError::ArticleNotFound(title)));
+        }
+        (None, Some(error)) => return Err(anyhow!(WikipediaError::ParseError(error.info))),
+        (None, None) => {
+            return Err(anyhow!(WikipediaError::ParseError("Response has no parse result".to_string())))
+        }
+    };
+
+    let html_content = &parse.text.content;
+    let cleaned_text = article_text(html_content, &site.origin)?;
 
     Ok(WikipediaArticle {
-        title: api_response.parse.title.clone(),
+        title: parse.title,
         extract: cleaned_text,
         url: url.to_string(),
+        language: content_language(html_content).or(site.language),
+        page_id: parse.pageid,
+        revision_id: parse.revid,
         timestamp: Utc::now(),
     })
 }
 
-fn html_to_plain_text(html: &str) -> Result<String> {
+/// The `lang` attribute MediaWiki puts on the parser output wrapper
+fn content_language(html: &str) -> Option<String> {
+    let document = Html::parse_fragment(html);
+    let selector = Selector::parse(".mw-parser-output[lang]").unwrap();
+    document
+        .select(&selector)
+        .next()
+        .and_then(|wrapper| wrapper.value().attr("lang"))
+        .map(|lang| lang.trim().to_lowercase())
+        .filter(|lang| !lang.is_empty())
+}
+
+/// Convert article HTML to the app's plain text: "== Heading ==" lines (one more "="
+/// per level below h2), paragraphs separated by blank lines, Markdown links, lists and
+/// pipe tables, "Term" / ": definition" pairs, and "> " blockquotes
+pub fn html_to_plain_text(html: &str) -> Result<String> {
+    html_to_plain_text_with_origin(html, DEFAULT_ORIGIN)
+}
+
+/// `html_to_plain_text` for a page of the MediaWiki site at `origin`, which its
+/// relative article links point into
+fn html_to_plain_text_with_origin(html: &str, origin: &str) -> Result<String> {
     let document = Html::parse_fragment(html);
 
     let unwanted_selectors = vec![
-        ".infobox",
         ".navbox",
         ".vertical-navbox",
+        ".sidebar",
         ".ambox",
-        "table",
+        ".metadata",
+        ".side-box",
+        ".sistersitebox",
+        ".gallery",
         ".reflist",
         ".reference",
         ".mw-editsection",
@@ -135,7 +305,7 @@ fn html_to_plain_text(html: &str) -> Result<String> {
 
     for node in document.root_element().children() {
         if let Some(elem) = scraper::ElementRef::wrap(node) {
-            extract_text_recursive(elem, &mut result, &unwanted_selectors);
+            extract_text_recursive(elem, &mut result, &unwanted_selectors, origin);
         }
     }
 
@@ -146,76 +316,91 @@ fn html_to_plain_text(html: &str) -> Result<String> {
     Ok(text)
 }
 
+/// The text stored for an article's parse output: converted, without reference markers
+/// and without empty or back-matter sections
+fn article_text(html: &str, origin: &str) -> Result<String> {
+    let plain_text = html_to_plain_text_with_origin(html, origin)?;
+    let text_without_refs = strip_reference_indicators(&plain_text);
+    Ok(clean_empty_sections(&text_without_refs))
+}
+
+fn is_unwanted(node: scraper::element_ref::ElementRef, unwanted_selectors: &[&str]) -> bool {
+    unwanted_selectors.iter().any(|sel_str| {
+        Selector::parse(sel_str).is_ok_and(|selector| matches_selector(node, &selector))
+    })
+}
+
 fn extract_text_recursive(
     node: scraper::element_ref::ElementRef,
     result: &mut Vec<String>,
     unwanted_selectors: &[&str],
+    origin: &str,
 ) {
     let tag_name = node.value().name();
 
-    for sel_str in unwanted_selectors {
-        if let Ok(selector) = Selector::parse(sel_str) {
-            if matches_selector(node, &selector) {
-                return;
-            }
-        }
+    if is_unwanted(node, unwanted_selectors) {
+        return;
     }
 
     match tag_name {
-        "h1" | "h2" => {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
             let text = node.text().collect::<String>().trim().to_string();
             if !text.is_empty() && !text.starts_with('[') {
-                result.push(format!("\n\n== {} ==\n", text));
+                // h1 and h2 are both top-level sections
+                let level = tag_name[1..].parse::<usize>().unwrap_or(2).max(2);
+                let marker = "=".repeat(level);
+                result.push(format!("\n\n{} {} {}\n", marker, text, marker));
             }
         }
-        "h3" => {
-            let text = node.text().collect::<String>().trim().to_string();
-            if !text.is_empty() && !text.starts_with('[') {
-                result.push(format!("\n\n=== {} ===\n", text));
+        "p" => {
+            let text = get_text_with_links(node, origin).trim().to_string();
+            if !text.is_empty() {
+                result.push(text);
+                result.push("".to_string());
             }
         }
-        "h4" | "h5" | "h6" => {
-            let text = node.text().collect::<String>().trim().to_string();
-            if !text.is_empty() && !text.starts_with('[') {
-                result.push(format!("\n\n==== {} ====\n", text));
+        "ul" | "ol" => {
+            let mut lines = Vec::new();
+            list_lines(node, "", &mut lines, unwanted_selectors, origin);
+            if !lines.is_empty() {
+                result.push(lines.join("\n"));
+                result.push("".to_string());
             }
         }
-        "p" | "li" | "dd" => {
-            let text = get_text_with_links(node).trim().to_string();
-            if !text.is_empty() {
-                result.push(text);
+        "dl" => definition_list(node, result, unwanted_selectors, origin),
+        "table" => {
+            if let Some(caption) = table_caption(node, origin) {
+                result.push(caption);
                 result.push("".to_string());
             }
-        }
-        "ul" | "ol" | "dl" => {
-            for child in node.children() {
-                if let Some(elem) = scraper::ElementRef::wrap(child) {
-                    extract_text_recursive(elem, result, unwanted_selectors);
-                }
+            let rows = if node.value().classes().any(|c| c == "infobox") {
+                infobox_rows(node, origin)
+            } else {
+                table_rows(node, origin)
+            };
+            if let Some(rows) = rows {
+                result.push(pipe_table(rows));
+                result.push("".to_string());
             }
         }
-        "div" | "section" | "article" => {
+        "div" | "section" | "article" | "main" => {
             for child in node.children() {
                 if let Some(elem) = scraper::ElementRef::wrap(child) {
-                    extract_text_recursive(elem, result, unwanted_selectors);
+                    extract_text_recursive(elem, result, unwanted_selectors, origin);
                 }
             }
         }
         "blockquote" => {
             let mut quote_parts = Vec::new();
-            for child in node.children() {
-                if let Some(elem) = scraper::ElementRef::wrap(child) {
-                    extract_text_recursive(elem, &mut quote_parts, unwanted_selectors);
-                }
-            }
+            quote_content(node, &mut quote_parts, unwanted_selectors, origin);
 
             if !quote_parts.is_empty() {
                 let quote_text = quote_parts.join("\n");
                 let trimmed = quote_text.trim();
                 if !trimmed.is_empty() {
                     for line in trimmed.lines() {
-                        let trimmed_line = line.trim();
-                        if !trimmed_line.is_empty() {
+                        let trimmed_line = line.trim_end();
+                        if !trimmed_line.trim().is_empty() {
                             result.push(format!("> {}", trimmed_line));
                         } else {
                             result.push(">".to_string());
@@ -229,6 +414,271 @@ fn extract_text_recursive(
     }
 }
 
+/// Block elements inside a blockquote, converted like the rest of the article
+const QUOTE_BLOCK_TAGS: &[&str] = &[
+    "p", "ul", "ol", "dl", "table", "blockquote", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+/// A blockquote's content. Unlike elsewhere, text outside paragraphs is kept, as quote
+/// templates put the quotation and its attribution straight into divs.
+fn quote_content(
+    node: scraper::element_ref::ElementRef,
+    result: &mut Vec<String>,
+    unwanted_selectors: &[&str],
+    origin: &str,
+) {
+    let mut inline = String::new();
+    let flush = |inline: &mut String, result: &mut Vec<String>| {
+        let text = inline.trim().to_string();
+        if !text.is_empty() {
+            result.push(text);
+            result.push("".to_string());
+        }
+        inline.clear();
+    };
+
+    for child in node.children() {
+        match scraper::ElementRef::wrap(child) {
+            Some(elem) if is_unwanted(elem, unwanted_selectors) => {}
+            Some(elem) if elem.value().name() == "div" => {
+                flush(&mut inline, result);
+                quote_content(elem, result, unwanted_selectors, origin);
+            }
+            Some(elem) if QUOTE_BLOCK_TAGS.contains(&elem.value().name()) => {
+                flush(&mut inline, result);
+                extract_text_recursive(elem, result, unwanted_selectors, origin);
+            }
+            _ => push_inline(child, &mut inline, origin),
+        }
+    }
+    flush(&mut inline, result);
+}
+
+/// Markdown lines for a list: "- " bullets or "1. " numbers, with nested lists indented
+/// under the text of their item
+fn list_lines(
+    list: scraper::element_ref::ElementRef,
+    indent: &str,
+    lines: &mut Vec<String>,
+    unwanted_selectors: &[&str],
+    origin: &str,
+) {
+    let ordered = list.value().name() == "ol";
+    let mut number = list
+        .value()
+        .attr("start")
+        .and_then(|start| start.trim().parse::<i64>().ok())
+        .unwrap_or(1);
+
+    for item in list.children().filter_map(scraper::ElementRef::wrap) {
+        if item.value().name() != "li" || is_unwanted(item, unwanted_selectors) {
+            continue;
+        }
+
+        let mut text = String::new();
+        for child in item.children() {
+            let is_block = scraper::ElementRef::wrap(child)
+                .is_some_and(|elem| matches!(elem.value().name(), "ul" | "ol" | "dl" | "table"));
+            if !is_block {
+                push_inline(child, &mut text, origin);
+            }
+        }
+        let text = collapse_whitespace(&text);
+
+        // An item holding only a nested list doesn't get a line of its own
+        let nested_indent = if text.is_empty() {
+            indent.to_string()
+        } else {
+            let marker = if ordered { format!("{}. ", number) } else { "- ".to_string() };
+            number += 1;
+            lines.push(format!("{}{}{}", indent, marker, text));
+            format!("{}{}", indent, " ".repeat(marker.len()))
+        };
+
+        for nested in item.children().filter_map(scraper::ElementRef::wrap) {
+            if is_unwanted(nested, unwanted_selectors) {
+                continue;
+            }
+            match nested.value().name() {
+                "ul" | "ol" => list_lines(nested, &nested_indent, lines, unwanted_selectors, origin),
+                // Indented text under an item continues it
+                "dl" => lines.extend(
+                    nested
+                        .children()
+                        .filter_map(scraper::ElementRef::wrap)
+                        .map(|entry| collapse_whitespace(&get_text_with_links(entry, origin)))
+                        .filter(|text| !text.is_empty())
+                        .map(|text| format!("{}{}", nested_indent, text)),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A definition list as "Term" lines followed by ": definition" lines, one block per
+/// term. Lists without terms are MediaWiki's indentation and become plain paragraphs.
+fn definition_list(
+    list: scraper::element_ref::ElementRef,
+    result: &mut Vec<String>,
+    unwanted_selectors: &[&str],
+    origin: &str,
+) {
+    let items: Vec<_> = list
+        .children()
+        .filter_map(scraper::ElementRef::wrap)
+        .filter(|item| !is_unwanted(*item, unwanted_selectors))
+        .collect();
+    let has_terms = items.iter().any(|item| item.value().name() == "dt");
+
+    let mut group: Vec<String> = Vec::new();
+    let flush = |group: &mut Vec<String>, result: &mut Vec<String>| {
+        if !group.is_empty() {
+            result.push(group.join("\n"));
+            result.push("".to_string());
+            group.clear();
+        }
+    };
+
+    for item in items {
+        let text = get_text_with_links(item, origin).trim().to_string();
+        match item.value().name() {
+            "dt" => {
+                flush(&mut group, result);
+                if !text.is_empty() {
+                    group.push(collapse_whitespace(&text));
+                }
+            }
+            "dd" if text.is_empty() => {}
+            "dd" if has_terms => group.push(format!(": {}", collapse_whitespace(&text))),
+            "dd" => {
+                result.push(text);
+                result.push("".to_string());
+            }
+            _ => {}
+        }
+
+        // Nested lists (deeper indentation, sub-definitions) follow their item
+        for nested in item.children().filter_map(scraper::ElementRef::wrap) {
+            if matches!(nested.value().name(), "ul" | "ol" | "dl") {
+                flush(&mut group, result);
+                extract_text_recursive(nested, result, unwanted_selectors, origin);
+            }
+        }
+    }
+    flush(&mut group, result);
+}
+
+fn table_caption(table: scraper::element_ref::ElementRef, origin: &str) -> Option<String> {
+    table
+        .children()
+        .filter_map(scraper::ElementRef::wrap)
+        .find(|child| child.value().name() == "caption")
+        .map(|caption| collapse_whitespace(&get_text_with_links(caption, origin)))
+        .filter(|caption| !caption.is_empty())
+}
+
+/// The rows of a table simple enough for pipe syntax: a header and at least one more
+/// row (single rows are layout), no nested tables and no cells spanning rows. Cells
+/// spanning columns are padded with empty cells.
+fn table_rows(table: scraper::element_ref::ElementRef, origin: &str) -> Option<Vec<Vec<String>>> {
+    let elements: Vec<_> = table.descendants().skip(1).filter_map(scraper::ElementRef::wrap).collect();
+    if elements.iter().any(|elem| elem.value().name() == "table") {
+        return None;
+    }
+
+    let mut rows = Vec::new();
+    for row in elements.iter().filter(|elem| elem.value().name() == "tr") {
+        let mut cells = Vec::new();
+        for cell in row.children().filter_map(scraper::ElementRef::wrap) {
+            if !matches!(cell.value().name(), "th" | "td") {
+                continue;
+            }
+            let span = |name: &str| cell.value().attr(name).and_then(|v| v.trim().parse::<usize>().ok());
+            if span("rowspan").is_some_and(|rows| rows > 1) {
+                return None;
+            }
+            cells.push(cell_text(cell, origin));
+            let colspan = span("colspan").unwrap_or(1).clamp(1, 100);
+            cells.extend(std::iter::repeat_n(String::new(), colspan - 1));
+        }
+        if cells.iter().any(|cell| !cell.is_empty()) {
+            rows.push(cells);
+        }
+    }
+
+    (rows.len() > 1).then_some(rows)
+}
+
+/// An infobox as a two-column table of its label/value rows, headed by its title
+fn infobox_rows(table: scraper::element_ref::ElementRef, origin: &str) -> Option<Vec<Vec<String>>> {
+    let mut title = None;
+    let mut rows = Vec::new();
+
+    for row in table.descendants().filter_map(scraper::ElementRef::wrap) {
+        if row.value().name() != "tr" {
+            continue;
+        }
+        let cells: Vec<_> = row
+            .children()
+            .filter_map(scraper::ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "th" | "td"))
+            .collect();
+
+        match cells.as_slice() {
+            [label, value] if label.value().name() == "th" => {
+                let label = cell_text(*label, origin);
+                let value = cell_text(*value, origin);
+                if !label.is_empty() && !value.is_empty() {
+                    rows.push(vec![label, value]);
+                }
+            }
+            [heading] if title.is_none() && rows.is_empty() && heading.value().name() == "th" => {
+                title = Some(cell_text(*heading, origin)).filter(|t| !t.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    if rows.is_empty() {
+        return None;
+    }
+    let header = match title {
+        Some(title) => vec![title, String::new()],
+        None => rows.remove(0),
+    };
+    rows.insert(0, header);
+    Some(rows)
+}
+
+/// A table cell on one line, with line breaks and list items separated by "; "
+fn cell_text(cell: scraper::element_ref::ElementRef, origin: &str) -> String {
+    get_text_with_links(cell, origin)
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+        .replace('|', "\\|")
+}
+
+/// Rows as a pipe table, the first row being the header
+fn pipe_table(mut rows: Vec<Vec<String>>) -> String {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut rows {
+        row.resize(width, String::new());
+    }
+
+    let line = |cells: &[String]| format!("| {} |", cells.join(" | "));
+    let mut lines = vec![line(&rows[0]), line(&vec!["---".to_string(); width])];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 fn is_image_url(url: &str) -> bool {
     let lower = url.to_lowercase();
     lower.ends_with(".jpg") ||
@@ -266,22 +716,58 @@ fn should_skip_element(elem: scraper::element_ref::ElementRef) -> bool {
     false
 }
 
-fn get_text_with_links(node: scraper::element_ref::ElementRef) -> String {
+/// A plain `<sup>` holding only digits, in Unicode superscript characters. Footnote
+/// markers are classed or wrap a link, and are left to `should_skip_element`.
+fn superscript_digits(elem: scraper::element_ref::ElementRef) -> Option<String> {
+    if elem.value().name() != "sup"
+        || elem.value().attr("class").is_some()
+        || !elem.children().all(|child| child.value().is_text())
+    {
+        return None;
+    }
+    let text = elem.text().collect::<String>();
+    let text = text.trim();
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(
+        text.chars()
+            .map(|c| ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'][c as usize - '0' as usize])
+            .collect(),
+    )
+}
+
+fn get_text_with_links(node: scraper::element_ref::ElementRef, origin: &str) -> String {
     let mut result = String::new();
     for child in node.children() {
-        if let Some(text) = child.value().as_text() {
-            result.push_str(text);
-        } else if let Some(elem) = scraper::ElementRef::wrap(child) {
-            // Skip elements that should be filtered (like citation needed markers)
-            if should_skip_element(elem) {
-                continue;
-            }
+        push_inline(child, &mut result, origin);
+    }
+    result
+}
 
-            if elem.value().name() == "a" {
+/// Append one node's inline text, with links in Markdown. Line breaks and the ends of
+/// list items start a new line.
+fn push_inline(child: ego_tree::NodeRef<scraper::Node>, result: &mut String, origin: &str) {
+    if let Some(text) = child.value().as_text() {
+        result.push_str(text);
+    } else if let Some(elem) = scraper::ElementRef::wrap(child) {
+        // Exponents such as km² are the one kind of superscript kept
+        if let Some(exponent) = superscript_digits(elem) {
+            result.push_str(&exponent);
+            return;
+        }
+
+        // Skip elements that should be filtered (like citation needed markers)
+        if should_skip_element(elem) {
+            return;
+        }
+
+        match elem.value().name() {
+            "a" => {
                 let link_text = elem.text().collect::<String>();
                 if let Some(href) = elem.value().attr("href") {
-                    let full_url = if href.starts_with("/wiki/") {
-                        format!("https://en.wikipedia.org{}", href)
+                    let full_url = if ARTICLE_PATH_PREFIXES.iter().any(|prefix| href.starts_with(prefix)) {
+                        format!("{}{}", origin, href)
                     } else if href.starts_with("http://") || href.starts_with("https://") {
                         href.to_string()
                     } else {
@@ -289,11 +775,11 @@ fn get_text_with_links(node: scraper::element_ref::ElementRef) -> String {
                     };
 
                     if is_image_url(&full_url) {
-                        continue;
+                        return;
                     }
 
                     if link_text.trim().is_empty() {
-                        continue;
+                        return;
                     }
 
                     if full_url != link_text {
@@ -304,12 +790,16 @@ fn get_text_with_links(node: scraper::element_ref::ElementRef) -> String {
                 } else {
                     result.push_str(&link_text);
                 }
-            } else {
-                result.push_str(&get_text_with_links(elem));
             }
+            "br" => result.push('\n'),
+            "style" | "script" => {}
+            "li" => {
+                result.push_str(&get_text_with_links(elem, origin));
+                result.push('\n');
+            }
+            _ => result.push_str(&get_text_with_links(elem, origin)),
         }
     }
-    result
 }
 
 fn remove_css_artifacts(text: &str) -> String {
@@ -339,6 +829,13 @@ fn strip_reference_indicators(text: &str) -> String {
     REFERENCE_REGEX.replace_all(text, "").to_string()
 }
 
+/// The level of a "== Heading ==" line: the number of "=" around it
+fn heading_level(line: &str) -> Option<usize> {
+    (line.starts_with("==") && line.ends_with("==")).then(|| line.chars().take_while(|&c| c == '=').count())
+}
+
+/// Drop back-matter sections and sections without text, each with its subsections.
+/// A section whose text is all in its subsections keeps its heading.
 fn clean_empty_sections(text: &str) -> String {
     let lines: Vec<&str> = text.lines().collect();
     let mut result = Vec::new();
@@ -355,36 +852,25 @@ fn clean_empty_sections(text: &str) -> String {
     while i < lines.len() {
         let line = lines[i];
 
-        if line.starts_with("==") && line.ends_with("==") {
-            let section_header = line;
+        if let Some(level) = heading_level(line) {
             let section_title = line.trim_matches(|c| c == '=' || c == ' ');
-
             let should_skip = skip_sections.iter().any(|&skip| section_title == skip);
 
-            i += 1;
-
-            let mut section_content = Vec::new();
-            while i < lines.len() {
-                let next_line = lines[i];
-                if next_line.starts_with("==") && next_line.ends_with("==") {
-                    break;
-                }
-                section_content.push(next_line);
-                i += 1;
-            }
+            let section_end = (i + 1..lines.len())
+                .find(|&j| heading_level(lines[j]).is_some_and(|next| next <= level))
+                .unwrap_or(lines.len());
+            let has_content = lines[i + 1..section_end]
+                .iter()
+                .any(|line| heading_level(line).is_none() && !line.trim().is_empty());
 
-            if should_skip {
+            if should_skip || !has_content {
+                i = section_end;
                 continue;
             }
 
-            let has_content = section_content
-                .iter()
-                .any(|line| !line.trim().is_empty());
-
-            if has_content {
-                result.push(section_header);
-                result.extend(section_content);
-            }
+            // Subsections are checked on their own as the loop reaches them
+            result.push(line);
+            i += 1;
         } else {
             result.push(line);
             i += 1;
@@ -400,31 +886,24 @@ fn clean_empty_sections(text: &str) -> String {
     cleaned.trim().to_string()
 }
 
+/// The article title in a MediaWiki URL: the path after an article prefix such as
+/// /wiki/, or the `title` parameter of an index.php URL
 fn extract_title_from_url(url: &str) -> Result<String> {
-    let url = url.trim();
+    let parsed = Url::parse(url.trim())
+        .map_err(|e| anyhow!(WikipediaError::InvalidUrl(format!("{}: {}", url, e))))?;
 
-    if !url.contains("wikipedia.org") {
-        return Err(anyhow!(WikipediaError::InvalidUrl(
-            "URL must be from wikipedia.org".to_string()
-        )));
+    if let Some((_, title)) = parsed.query_pairs().find(|(key, _)| key == "title") {
+        if !title.is_empty() {
+            return Ok(title.into_owned());
+        }
     }
 
-    let wiki_prefix = "/wiki/";
-    let wiki_index = url
-        .find(wiki_prefix)
+    let path = parsed.path();
+    let title = ARTICLE_PATH_PREFIXES
+        .iter()
+        .find_map(|prefix| path.find(prefix).map(|index| &path[index + prefix.len()..]))
         .ok_or_else(|| anyhow!(WikipediaError::InvalidUrl("URL must contain /wiki/".to_string())))?;
 
-    let title_start = wiki_index + wiki_prefix.len();
-    let title_with_params = &url[title_start..];
-
-    let title = if let Some(hash_index) = title_with_params.find('#') {
-        &title_with_params[..hash_index]
-    } else if let Some(query_index) = title_with_params.find('?') {
-        &title_with_params[..query_index]
-    } else {
-        title_with_params
-    };
-
     if title.is_empty() {
         return Err(anyhow!(WikipediaError::InvalidUrl(
             "No article title found in URL".to_string()
@@ -440,6 +919,8 @@ fn extract_title_from_url(url: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::http_client::test_server::serve_once;
+    use tokio::net::TcpListener;
 
     #[test]
     fn test_extract_title_simple() {
@@ -479,10 +960,59 @@ mod tests {
     #[test]
     fn test_invalid_url_no_wikipedia() {
         let url = "https://example.com/wiki/Article";
-        let result = extract_title_from_url(url);
+        let result = resolve_site(url, &[]);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_extract_title_from_index_php() {
+        let url = "https://wiki.example.org/w/index.php?title=Main_Page&oldid=42";
+        assert_eq!(extract_title_from_url(url).unwrap(), "Main_Page");
+        let url = "https://wiki.archlinux.org/title/Pacman%2FTips";
+        assert_eq!(extract_title_from_url(url).unwrap(), "Pacman/Tips");
+        let url = "https://de.wikipedia.org/wiki/K%C3%B6ln";
+        assert_eq!(extract_title_from_url(url).unwrap(), "Köln");
+    }
+
+    #[test]
+    fn test_resolve_site_wikimedia_projects() {
+        let site = resolve_site("https://de.wikipedia.org/wiki/K%C3%B6ln", &[]).unwrap();
+        assert_eq!(
+            site,
+            MediaWikiSite {
+                api_url: "https://de.wikipedia.org/w/api.php".to_string(),
+                origin: "https://de.wikipedia.org".to_string(),
+                language: Some("de".to_string()),
+            }
+        );
+
+        let site = resolve_site("https://fr.m.wiktionary.org/wiki/chat", &[]).unwrap();
+        assert_eq!(site.api_url, "https://fr.wiktionary.org/w/api.php");
+        assert_eq!(site.language.as_deref(), Some("fr"));
+
+        let site = resolve_site("https://EN.WIKIBOOKS.ORG/wiki/Rust", &[]).unwrap();
+        assert_eq!(site.api_url, "https://en.wikibooks.org/w/api.php");
+
+        assert!(resolve_site("https://www.wikipedia.org/", &[]).is_err());
+        assert!(resolve_site("https://wikipedia.org/wiki/Rust", &[]).is_err());
+        assert!(resolve_site("https://notwikipedia.org/wiki/Rust", &[]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_site_configured_hosts() {
+        let configured = vec!["".to_string(), "https://wiki.archlinux.org/api.php".to_string()];
+        let site = resolve_site("https://wiki.archlinux.org/title/Pacman", &configured).unwrap();
+        assert_eq!(
+            site,
+            MediaWikiSite {
+                api_url: "https://wiki.archlinux.org/api.php".to_string(),
+                origin: "https://wiki.archlinux.org".to_string(),
+                language: None,
+            }
+        );
+        assert!(resolve_site("https://other.example.org/wiki/Pacman", &configured).is_err());
+    }
+
     #[test]
     fn test_invalid_url_no_wiki_prefix() {
         let url = "https://en.wikipedia.org/Article";
@@ -623,7 +1153,7 @@ mod tests {
         let document = Html::parse_fragment(html);
         let p_selector = Selector::parse("p").unwrap();
         let p_elem = document.select(&p_selector).next().unwrap();
-        let result = get_text_with_links(p_elem);
+        let result = get_text_with_links(p_elem, DEFAULT_ORIGIN);
         assert!(!result.contains(".jpg"));
         assert!(result.contains("[link](https://example.com/article)"));
     }
@@ -634,7 +1164,7 @@ mod tests {
         let document = Html::parse_fragment(html);
         let p_selector = Selector::parse("p").unwrap();
         let p_elem = document.select(&p_selector).next().unwrap();
-        let result = get_text_with_links(p_elem);
+        let result = get_text_with_links(p_elem, DEFAULT_ORIGIN);
         assert!(!result.contains("[]("));
         assert!(result.contains("[text](https://example.com/other)"));
     }
@@ -714,4 +1244,186 @@ mod tests {
         // Regular links should be preserved
         assert!(result.contains("[this article](https://en.wikipedia.org/wiki/Article)"));
     }
+
+    #[test]
+    fn test_links_resolve_against_article_site() {
+        let html = r#"<p>See <a href="/wiki/Rhein">Rhein</a> and <a href="/title/Pacman">Pacman</a>.</p>"#;
+        let result = html_to_plain_text_with_origin(html, "https://de.wikipedia.org").unwrap();
+        assert!(result.contains("[Rhein](https://de.wikipedia.org/wiki/Rhein)"));
+        assert!(result.contains("[Pacman](https://de.wikipedia.org/title/Pacman)"));
+    }
+
+    const PARSE_RESPONSE: &str = r#"{"parse":{"title":"Köln","pageid":2303,"revid":245001234,"text":{"*":"<div class=\"mw-content-ltr mw-parser-output\" lang=\"de\" dir=\"ltr\"><p>Köln liegt am <a href=\"/wiki/Rhein\">Rhein</a>.<sup class=\"reference\">[1]</sup></p><h2>Weblinks</h2></div>"}}}"#;
+
+    const MISSING_RESPONSE: &str = r#"{"error":{"code":"missingtitle","info":"The page you specified doesn't exist."}}"#;
+
+    #[tokio::test]
+    async fn test_fetch_from_language_edition_through_local_mock() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener, "application/json", PARSE_RESPONSE));
+
+        let client = HttpClient::with_base_url(&base_url).unwrap();
+        let article = fetch_wikipedia_article(&client, "https://de.wikipedia.org/wiki/K%C3%B6ln", &[])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /w/api.php?action=parse&page=K%C3%B6ln&format=json&prop=text%7Crevid HTTP/1.1"
+        );
+        assert_eq!(article.title, "Köln");
+        assert_eq!(article.extract, "Köln liegt am [Rhein](https://de.wikipedia.org/wiki/Rhein).");
+        assert_eq!(article.language.as_deref(), Some("de"));
+        assert_eq!((article.page_id, article.revision_id), (2303, Some(245001234)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_revision_by_id() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener, "application/json", PARSE_RESPONSE));
+
+        let client = HttpClient::with_base_url(&base_url).unwrap();
+        let article = fetch_wikipedia_revision(&client, "https://de.wikipedia.org/wiki/K%C3%B6ln", &[], 245001234)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /w/api.php?action=parse&oldid=245001234&format=json&prop=text%7Crevid HTTP/1.1"
+        );
+        assert_eq!(article.revision_id, Some(245001234));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_renamed_page_by_id() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener, "application/json", PARSE_RESPONSE));
+
+        // Stored under an old title; the page id still finds the article
+        let client = HttpClient::with_base_url(&base_url).unwrap();
+        let article = fetch_wikipedia_page(&client, "https://de.wikipedia.org/wiki/Coeln", &[], 2303)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /w/api.php?action=parse&pageid=2303&format=json&prop=text%7Crevid HTTP/1.1"
+        );
+        assert_eq!(article.title, "Köln");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_missing_article_from_configured_site() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_once(listener, "application/json", MISSING_RESPONSE));
+
+        let client = HttpClient::with_base_url(&base_url).unwrap();
+        let configured = vec!["https://wiki.example.org/api.php".to_string()];
+        let error = fetch_wikipedia_article(&client, "https://wiki.example.org/wiki/Nowhere", &configured)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            server.await.unwrap(),
+            "GET /api.php?action=parse&page=Nowhere&format=json&prop=text%7Crevid HTTP/1.1"
+        );
+        assert_eq!(error.to_string(), "Wikipedia article not found: Nowhere");
+    }
+
+    // Built by hand in the markup of action=parse output (mw-heading wrappers, cite
+    // notes, TemplateStyles, navboxes) rather than saved from a live page; swap in real
+    // saved articles when the fixtures are next refreshed
+    const RIVER_ARTICLE: &str = include_str!("../../tests/fixtures/wikipedia/river_article.html");
+    const GLOSSARY_ARTICLE: &str = include_str!("../../tests/fixtures/wikipedia/glossary_article.html");
+
+    const RIVER_EXPECTED: &str = "\
+| Vell |  |
+| --- | --- |
+| Countries | [Austria](https://en.wikipedia.org/wiki/Austria); [Germany](https://en.wikipedia.org/wiki/Germany) |
+| Length | 212 km (132 mi) |
+| Basin size | 3,140 km² |
+
+The Vell is a river in [Central Europe](https://en.wikipedia.org/wiki/Central_Europe), a left tributary of the [Danube](https://en.wikipedia.org/wiki/Danube). It rises in the [Limestone Alps](https://en.wikipedia.org/wiki/Limestone_Alps) and flows north.
+
+== Course ==
+
+=== Upper course ===
+
+==== Springs ====
+
+Three karst springs feed the river:
+
+- the [Blautopf](https://en.wikipedia.org/wiki/Blautopf_(Vell)), the largest
+- the Kessel spring
+  - which dries up in late summer
+  - and floods in spring
+- the Mühlquelle
+
+=== Lower course ===
+
+Below Armsdorf the river passes, in order:
+
+1. [Armsdorf](https://en.wikipedia.org/wiki/Armsdorf)
+2. Kleinvell (a former mill town)
+3. the Vell Gorge
+
+Discharge at Armsdorf
+
+| Month | Mean (m³/s) | Notes |
+| --- | --- | --- |
+| April | 41.2 | snowmelt \\| peak |
+| August | 8.7 |  |
+
+== In literature ==
+
+> The Vell runs green in May and grey by October.
+>
+> —\u{200A}Anna Weber, Letters from the Valley";
+
+    #[test]
+    fn test_article_keeps_infobox_lists_tables_and_sections() {
+        // Nested sections, the infobox and the simple table survive; the table with a
+        // row span, the hatnote, references and navbox don't
+        assert_eq!(article_text(RIVER_ARTICLE, DEFAULT_ORIGIN).unwrap(), RIVER_EXPECTED);
+    }
+
+    #[test]
+    fn test_article_keeps_definition_lists() {
+        assert_eq!(
+            article_text(GLOSSARY_ARTICLE, DEFAULT_ORIGIN).unwrap(),
+            "This glossary of weaving lists terms used in hand weaving.\n\n\
+             == Terms ==\n\n\
+             === A–L ===\n\n\
+             [Heddle](https://en.wikipedia.org/wiki/Heddle)\n\
+             : A cord or wire with an eye through which a warp thread passes.\n\n\
+             Loom\n\
+             : A frame that holds the warp under tension.\n\
+             : Also the whole weaving machine.\n\n\
+             === M–Z ===\n\n\
+             Shed\n\
+             : The opening between raised and lowered warp threads.\n\n\
+             == Weaving a plain cloth ==\n\n\
+             3. Raise the odd heddles.\n\
+             4. Pass the shuttle.\n   \
+             Keep the weft loose at the edges."
+        );
+    }
+
+    #[test]
+    fn test_clean_empty_sections_keeps_parents_of_subsections() {
+        let text = "Intro\n\n== Parent ==\n\n=== Child ===\nChild text\n\n=== Empty child ===\n\n== References ==\n=== Books ===\nA book";
+        assert_eq!(clean_empty_sections(text), "Intro\n\n== Parent ==\n\n=== Child ===\nChild text");
+    }
+
+    #[test]
+    fn test_heading_levels_below_h4() {
+        let html = "<h5>Deep</h5><p>a</p><h6>Deeper</h6><p>b</p>";
+        let result = html_to_plain_text(html).unwrap();
+        assert!(result.contains("===== Deep ====="));
+        assert!(result.contains("====== Deeper ======"));
+    }
 }
@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react'
 import { useTextHistory } from '../../../hooks/useTextHistory'
 import { useFlashcardStore } from '../../stores/flashcard'
+import { useCardCreationStore } from '../../stores/cardCreation'
 import { Button, Textarea, Label } from '../ui'
 import { FlashcardPreview } from '../flashcard/FlashcardPreview'
 import { getModifierKey } from '../../utils/platform'
@@ -42,6 +43,7 @@ export function CardCreator({
   const [isCreating, setIsCreating] = useState(false)
   const [successMessage, setSuccessMessage] = useState('')
   const { getPreview } = useFlashcardStore()
+  const clozeDiagnostic = useCardCreationStore((state) => state.clozeDiagnostic)
   const mod = getModifierKey()
 
   useEffect(() => {
@@ -204,6 +206,12 @@ export function CardCreator({
             No cloze deletions found. Add at least one using {'{{c1::text}}'} syntax.
           </div>
         )}
+        {clozeDiagnostic && (
+          <div className="text-xs text-destructive">
+            {clozeDiagnostic.message}
+            {clozeDiagnostic.suggestion && ` - ${clozeDiagnostic.suggestion}`}
+          </div>
+        )}
       </div>
 
       {showPreview && previewHtml && (
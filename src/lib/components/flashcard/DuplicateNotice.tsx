@@ -0,0 +1,40 @@
+import { AlertTriangle, X } from 'lucide-react'
+import { Button } from '../ui'
+import type { DuplicateMatch } from '../../types'
+
+interface DuplicateNoticeProps {
+  duplicates: DuplicateMatch[]
+  onDismiss: () => void
+}
+
+/**
+ * Existing notes that look like the same fact as the cards just created
+ */
+export function DuplicateNotice({ duplicates, onDismiss }: DuplicateNoticeProps) {
+  if (duplicates.length === 0) return null
+
+  return (
+    <div className="bg-amber-50 dark:bg-amber-950 border border-amber-200 dark:border-amber-800 rounded-lg p-3 space-y-2">
+      <div className="flex items-start justify-between gap-2">
+        <div className="flex items-center gap-2 text-sm font-medium text-amber-800 dark:text-amber-200">
+          <AlertTriangle className="w-4 h-4 shrink-0" />
+          {duplicates.length === 1
+            ? 'An existing note looks like the same fact'
+            : `${duplicates.length} existing notes look like the same fact`}
+        </div>
+        <Button variant="ghost" size="sm" onClick={onDismiss} className="h-6 w-6 p-0">
+          <X className="w-4 h-4" />
+        </Button>
+      </div>
+      <ul className="space-y-1">
+        {duplicates.map((duplicate) => (
+          <li key={duplicate.clozeNoteId} className="text-xs text-amber-700 dark:text-amber-300">
+            <span className="font-medium">{Math.round(duplicate.similarity * 100)}%</span>
+            {' '}{duplicate.noteText}
+            <span className="text-muted-foreground"> - {duplicate.textTitle}</span>
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
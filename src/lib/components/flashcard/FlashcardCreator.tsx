@@ -47,7 +47,7 @@ export function FlashcardCreator({
   const [previewHtml, setPreviewHtml] = useState('')
   const [previewClozeNumber, setPreviewClozeNumber] = useState(1)
   const [showPreview, setShowPreview] = useState(false)
-  const { createFlashcard, getPreview, isLoading } = useFlashcardStore()
+  const { createFlashcard, getPreview, isLoading, clozeDiagnostic } = useFlashcardStore()
 
   useEffect(() => {
     if (open && selectedText) {
@@ -229,6 +229,12 @@ export function FlashcardCreator({
                 No cloze deletions detected. Add at least one cloze using {'{{c1::text}}'} syntax.
               </div>
             )}
+            {clozeDiagnostic && (
+              <div className="text-xs text-destructive">
+                {clozeDiagnostic.message}
+                {clozeDiagnostic.suggestion && ` - ${clozeDiagnostic.suggestion}`}
+              </div>
+            )}
           </div>
 
           {showPreview && previewHtml && (
@@ -3,6 +3,7 @@ import { X, ChevronDown, ChevronUp, Trash2, ArrowUpDown, Zap } from 'lucide-reac
 import { useFlashcardStore } from '../../stores/flashcard'
 import { Button, Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription, DropdownMenu, DropdownMenuTrigger, DropdownMenuContent, DropdownMenuItem } from '../ui'
 import { formatRelativeDue, formatDueDate, getDueColorClass } from '../../utils'
+import { DuplicateNotice } from './DuplicateNotice'
 import type { Flashcard } from '../../types'
 
 type FlashcardSortOption = 'display-order' | 'alpha-asc' | 'alpha-desc' | 'due-soonest' | 'due-latest' | 'difficulty'
@@ -59,7 +60,9 @@ export function FlashcardSidebar({ textId, onClose }: FlashcardSidebarProps) {
     error,
     loadFlashcards,
     deleteFlashcard,
-    setMostRecentlyReadTextId
+    setMostRecentlyReadTextId,
+    duplicates,
+    dismissDuplicates
   } = useFlashcardStore()
 
   const [deleteDialogOpen, setDeleteDialogOpen] = useState(false)
@@ -258,6 +261,12 @@ export function FlashcardSidebar({ textId, onClose }: FlashcardSidebarProps) {
       </div>
 
       <div className="flex-1 overflow-y-auto p-4">
+        {duplicates.length > 0 && (
+          <div className="mb-3">
+            <DuplicateNotice duplicates={duplicates} onDismiss={dismissDuplicates} />
+          </div>
+        )}
+
         {isLoading && (
           <div className="text-center text-sm text-muted-foreground py-4">
             Loading flashcards...
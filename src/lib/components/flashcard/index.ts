@@ -4,3 +4,4 @@ export { FlashcardPreview as FlashcardPreviewComponent } from './FlashcardPrevie
 export { FlashcardList } from './FlashcardList';
 export { FlashcardCreator } from './FlashcardCreator';
 export { FlashcardSidebar } from './FlashcardSidebar';
+export { DuplicateNotice } from './DuplicateNotice';
@@ -5,6 +5,8 @@ import type { ReadRange, ExcludedRange } from '../../types'
 interface ReadHighlighterProps {
   content: string
   readRanges: ReadRange[]
+  // Stored excluded ranges (rendered space), shown along with any inline [[exclude]] tags
+  excludedRanges?: ExcludedRange[]
   className?: string
   onExcludedRangesParsed?: (excludedRanges: ExcludedRange[]) => void
   linksEnabled?: boolean
@@ -571,9 +573,12 @@ function isPositionInHeader(position: number, headerRanges: HeaderRange[]): bool
 // Note: Blockquote detection and boundary adjustment are no longer needed
 // since we now format blockquotes once on the full content before segmentation
 
+const NO_EXCLUDED_RANGES: ExcludedRange[] = []
+
 const ReadHighlighterComponent = ({
   content,
   readRanges,
+  excludedRanges: storedExcludedRanges = NO_EXCLUDED_RANGES,
   className,
   linksEnabled = false,
   searchMatches = [],
@@ -582,7 +587,8 @@ const ReadHighlighterComponent = ({
   const containerRef = useRef<HTMLDivElement>(null)
 
   const segments = useMemo(() => {
-    const { cleanedContent, excludedRanges } = parseExcludedRanges(content)
+    const { cleanedContent, excludedRanges: inlineExcludedRanges } = parseExcludedRanges(content)
+    const excludedRanges = [...inlineExcludedRanges, ...storedExcludedRanges]
     const headerRanges = detectHeaderRanges(cleanedContent)
 
     if (!readRanges.length && !excludedRanges.length) {
@@ -682,7 +688,7 @@ const ReadHighlighterComponent = ({
     }
 
     return result
-  }, [content, readRanges])
+  }, [content, readRanges, storedExcludedRanges])
 
   const convertedSearchMatches = useMemo(() => {
     if (searchMatches.length === 0) return []
@@ -1,11 +1,14 @@
+import { useState } from 'react';
 import { Label } from '../ui/label';
 import { Switch } from '../ui/switch';
+import { Textarea } from '../ui/textarea';
 import { useSettingsStore } from '../../stores/settings';
 import { useLibraryStore } from '../../../stores/library';
 import { api } from '../../utils/tauri';
 
 export function DefaultsSection() {
-  const { defaultLinksVisible, setDefaultLinksVisible, enableFocusTracking, setEnableFocusTracking, showLibraryControlsInSidebar, setShowLibraryControlsInSidebar } = useSettingsStore();
+  const { defaultLinksVisible, setDefaultLinksVisible, enableFocusTracking, setEnableFocusTracking, showLibraryControlsInSidebar, setShowLibraryControlsInSidebar, mediawikiHosts, setMediawikiHosts } = useSettingsStore();
+  const [mediawikiHostsDraft, setMediawikiHostsDraft] = useState(mediawikiHosts.join('\n'));
   const syncSidebarSelection = useLibraryStore((state) => state.syncSidebarSelection);
   const toggleSyncSidebarSelection = useLibraryStore((state) => state.toggleSyncSidebarSelection);
 
@@ -36,6 +39,20 @@ export function DefaultsSection() {
     }
   };
 
+  const handleMediawikiHostsSave = async () => {
+    const apiUrls = mediawikiHostsDraft
+      .split('\n')
+      .map((line) => line.trim())
+      .filter((line) => line.length > 0);
+    try {
+      await api.settings.updateSetting('mediawikiHosts', apiUrls.join('\n'));
+      setMediawikiHosts(apiUrls);
+      setMediawikiHostsDraft(apiUrls.join('\n'));
+    } catch (error) {
+      console.error('Failed to update setting:', error);
+    }
+  };
+
   return (
     <div className="space-y-6">
       <div>
@@ -110,6 +127,26 @@ export function DefaultsSection() {
           aria-label="Toggle library controls in sidebar"
         />
       </div>
+
+      <div className="space-y-3 py-4">
+        <div className="space-y-1">
+          <Label htmlFor="mediawiki-hosts">
+            Other MediaWiki Sites
+          </Label>
+          <p className="text-sm text-muted-foreground">
+            API endpoints of wikis to import like Wikipedia, one per line (e.g. https://wiki.archlinux.org/api.php). Wikipedia, Wiktionary and other Wikimedia projects work in every language without this.
+          </p>
+        </div>
+        <Textarea
+          id="mediawiki-hosts"
+          value={mediawikiHostsDraft}
+          onChange={(e) => setMediawikiHostsDraft(e.target.value)}
+          onBlur={handleMediawikiHostsSave}
+          placeholder="https://wiki.example.org/w/api.php"
+          rows={3}
+          className="font-mono text-sm"
+        />
+      </div>
     </div>
   );
 }
@@ -1,6 +1,7 @@
 import { create } from 'zustand';
-import type { HubScope, MarkWithContext, CreatedCard, CreateCardRequest } from '../types';
+import type { HubScope, MarkWithContext, CreatedCard, CreateCardRequest, ClozeDiagnostic, DuplicateMatch } from '../types';
 import { api } from '../utils/tauri';
+import { cardErrorMessage, clozeDiagnosticOf } from '../utils/cloze';
 
 export interface CardCreationState {
   scope: HubScope;
@@ -10,6 +11,9 @@ export interface CardCreationState {
   createdCards: CreatedCard[];
   isLoading: boolean;
   error: string | null;
+  clozeDiagnostic: ClozeDiagnostic | null;
+  /** Existing notes that look like the same fact as the last created cards */
+  duplicates: DuplicateMatch[];
   totalCount: number;
   hasMore: boolean;
   currentOffset: number;
@@ -23,6 +27,7 @@ export interface CardCreationState {
   createCard: (selectedText: string, clozeText: string) => Promise<void>;
   deleteCard: (id: number) => void;
   editCard: (id: number, question: string, answer: string) => Promise<void>;
+  dismissDuplicates: () => void;
   reset: () => void;
 }
 
@@ -34,6 +39,8 @@ export const useCardCreationStore = create<CardCreationState>((set, get) => ({
   createdCards: [],
   isLoading: false,
   error: null,
+  clozeDiagnostic: null,
+  duplicates: [],
   totalCount: 0,
   hasMore: false,
   currentOffset: 0,
@@ -160,25 +167,29 @@ export const useCardCreationStore = create<CardCreationState>((set, get) => ({
       throw new Error('No current mark selected');
     }
 
-    set({ isLoading: true, error: null });
+    set({ isLoading: true, error: null, clozeDiagnostic: null, duplicates: [] });
     try {
       const request: CreateCardRequest = {
         markId: currentMark.id,
         selectedText,
         clozeText
       };
-      const createdCards = await api.hub.createCardFromMark(request);
+      const { cards: createdCards, duplicates } = await api.hub.createCardFromMark(request);
 
       set((state) => ({
         createdCards: [...createdCards, ...state.createdCards],
+        duplicates,
         isLoading: false
       }));
 
       get().nextMark();
     } catch (error) {
       console.error('Failed to create card:', error);
+      // A problem in the cloze text is shown in the editor, not as a page error
+      const clozeDiagnostic = clozeDiagnosticOf(error);
       set({
-        error: error instanceof Error ? error.message : 'Failed to create card',
+        error: clozeDiagnostic ? null : cardErrorMessage(error, 'Failed to create card'),
+        clozeDiagnostic,
         isLoading: false
       });
       throw error;
@@ -211,6 +222,10 @@ export const useCardCreationStore = create<CardCreationState>((set, get) => ({
     }
   },
 
+  dismissDuplicates: () => {
+    set({ duplicates: [] });
+  },
+
   reset: () => {
     set({
       scope: 'library',
@@ -220,6 +235,8 @@ export const useCardCreationStore = create<CardCreationState>((set, get) => ({
       createdCards: [],
       isLoading: false,
       error: null,
+      clozeDiagnostic: null,
+      duplicates: [],
       totalCount: 0,
       hasMore: false,
       currentOffset: 0,
@@ -1,6 +1,7 @@
 import { create } from 'zustand';
-import type { Flashcard, FlashcardPreview } from '../types';
+import type { ClozeDiagnostic, DuplicateMatch, Flashcard, FlashcardPreview } from '../types';
 import { api } from '../utils/tauri';
+import { cardErrorMessage, clozeDiagnosticOf } from '../utils/cloze';
 
 interface FlashcardState {
   flashcards: Flashcard[];
@@ -8,11 +9,15 @@ interface FlashcardState {
   mostRecentlyReadTextId: number | null;
   isLoading: boolean;
   error: string | null;
+  clozeDiagnostic: ClozeDiagnostic | null;
+  /** Existing notes that look like the same fact as the last created cards */
+  duplicates: DuplicateMatch[];
   loadFlashcards: (textId: number) => Promise<void>;
   createFlashcard: (textId: number, selectedText: string, clozeText: string) => Promise<Flashcard[]>;
   deleteFlashcard: (flashcardId: number) => Promise<void>;
   getPreview: (clozeText: string, clozeNumber: number) => Promise<FlashcardPreview>;
   setMostRecentlyReadTextId: (textId: number | null) => void;
+  dismissDuplicates: () => void;
   clearSession: () => void;
 }
 
@@ -22,6 +27,8 @@ export const useFlashcardStore = create<FlashcardState>((set) => ({
   mostRecentlyReadTextId: null,
   isLoading: false,
   error: null,
+  clozeDiagnostic: null,
+  duplicates: [],
 
   loadFlashcards: async (textId: number) => {
     set({ isLoading: true, error: null });
@@ -42,19 +49,21 @@ export const useFlashcardStore = create<FlashcardState>((set) => ({
   },
 
   createFlashcard: async (textId: number, selectedText: string, clozeText: string) => {
-    set({ isLoading: true, error: null });
+    set({ isLoading: true, error: null, clozeDiagnostic: null, duplicates: [] });
     try {
-      const newFlashcards = await api.flashcards.createFromCloze(textId, selectedText, clozeText);
+      const { flashcards: newFlashcards, duplicates } = await api.flashcards.createFromCloze(textId, selectedText, clozeText);
       set((state) => ({
         flashcards: [...newFlashcards, ...state.flashcards],
         currentTextFlashcards: [...newFlashcards, ...state.currentTextFlashcards],
+        duplicates,
         isLoading: false
       }));
       return newFlashcards;
     } catch (error) {
       console.error('Failed to create flashcard:', error);
       set({
-        error: error instanceof Error ? error.message : 'Failed to create flashcard',
+        error: cardErrorMessage(error, 'Failed to create flashcard'),
+        clozeDiagnostic: clozeDiagnosticOf(error),
         isLoading: false
       });
       throw error;
@@ -97,11 +106,16 @@ export const useFlashcardStore = create<FlashcardState>((set) => ({
     set({ mostRecentlyReadTextId: textId });
   },
 
+  dismissDuplicates: () => {
+    set({ duplicates: [] });
+  },
+
   clearSession: () => {
     set({
       flashcards: [],
       currentTextFlashcards: [],
-      mostRecentlyReadTextId: null
+      mostRecentlyReadTextId: null,
+      duplicates: []
     });
   },
 }));
@@ -26,6 +26,9 @@ interface ReadingState {
   isRangeRead: (startPosition: number, endPosition: number) => boolean;
   isRangeExcluded: (startPosition: number, endPosition: number) => boolean;
   setExcludedRanges: (ranges: ExcludedRange[]) => void;
+  getExcludedRanges: (textId: number) => Promise<void>;
+  excludeRange: (textId: number, startPosition: number, endPosition: number) => Promise<void>;
+  includeRange: (textId: number, startPosition: number, endPosition: number) => Promise<void>;
   getReadRanges: (textId: number) => Promise<void>;
   getParagraphs: (textId: number) => Promise<void>;
   calculateProgress: (textId: number) => Promise<void>;
@@ -283,6 +286,46 @@ export const useReadingStore = create<ReadingState>((set, get) => ({
     }
   },
 
+  getExcludedRanges: async (textId: number) => {
+    try {
+      const excludedRanges = await api.reading.getExcludedRanges(textId);
+      set({ excludedRanges });
+    } catch (error) {
+      console.error('Failed to load excluded ranges:', error);
+      set({
+        error: error instanceof Error ? error.message : 'Failed to load excluded ranges'
+      });
+    }
+  },
+
+  excludeRange: async (textId: number, startPosition: number, endPosition: number) => {
+    try {
+      const excludedRanges = await api.reading.excludeRange(textId, startPosition, endPosition);
+      set({ excludedRanges });
+      await get().calculateProgress(textId);
+      invalidateProgressCache(textId);
+    } catch (error) {
+      console.error('Failed to exclude range:', error);
+      set({
+        error: error instanceof Error ? error.message : 'Failed to exclude range'
+      });
+    }
+  },
+
+  includeRange: async (textId: number, startPosition: number, endPosition: number) => {
+    try {
+      const excludedRanges = await api.reading.includeRange(textId, startPosition, endPosition);
+      set({ excludedRanges });
+      await get().calculateProgress(textId);
+      invalidateProgressCache(textId);
+    } catch (error) {
+      console.error('Failed to include range:', error);
+      set({
+        error: error instanceof Error ? error.message : 'Failed to include range'
+      });
+    }
+  },
+
   calculateProgress: async (textId: number) => {
     try {
       const progress = await api.reading.calculateProgress(textId);
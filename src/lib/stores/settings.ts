@@ -22,6 +22,8 @@ interface SettingsState {
   setEnableFocusTracking: (enabled: boolean) => void;
   showLibraryControlsInSidebar: boolean;
   setShowLibraryControlsInSidebar: (show: boolean) => void;
+  mediawikiHosts: string[];
+  setMediawikiHosts: (apiUrls: string[]) => void;
   databaseSize: number;
   setDatabaseSize: (size: number) => void;
   loadDatabaseSize: () => Promise<void>;
@@ -41,6 +43,7 @@ export const useSettingsStore = create<SettingsState>()(
       defaultLinksVisible: false,
       enableFocusTracking: false,
       showLibraryControlsInSidebar: true,
+      mediawikiHosts: [],
       databaseSize: 0,
       themeMode: 'adaptive',
       customColors: {
@@ -76,6 +79,10 @@ export const useSettingsStore = create<SettingsState>()(
         set({ showLibraryControlsInSidebar: show });
       },
 
+      setMediawikiHosts: (apiUrls: string[]) => {
+        set({ mediawikiHosts: apiUrls });
+      },
+
       setDatabaseSize: (size: number) => {
         set({ databaseSize: size });
       },
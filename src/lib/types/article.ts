@@ -47,9 +47,18 @@ export interface Text {
   accessDate?: string;
   doi?: string;
   isbn?: string;
+  language?: string | null;
+  wikiPageId?: number | null;
+  wikiRevisionId?: number | null;
   folderId?: string | null;
+  parentTextId?: number | null;
+  parentStartPosition?: number | null;
+  parentEndPosition?: number | null;
+  status: TextStatus;
 }
 
+export type TextStatus = 'inbox' | 'reading' | 'finished' | 'archived';
+
 export interface CreateTextRequest {
   title: string;
   source: string;
@@ -62,10 +71,14 @@ export interface CreateTextRequest {
   accessDate?: string;
   doi?: string;
   isbn?: string;
+  language?: string;
+  wikiPageId?: number;
+  wikiRevisionId?: number;
   folderId?: string | null;
 }
 
 import type { ReadRange } from './reading';
+import type { Folder } from './folder';
 
 export interface SmartExcerpt {
   textId: number;
@@ -77,3 +90,68 @@ export interface SmartExcerpt {
   readRanges: ReadRange[];
   excerptType: 'unread' | 'current' | 'beginning';
 }
+
+export interface UpdateResult {
+  updatedMarks: number[];
+  flaggedMarks: number[];
+  unchangedMarks: number[];
+  reanchoredMarks: number[];
+}
+
+export interface TextRevision {
+  revisionNumber: number;
+  editStart: number;
+  removedLength: number;
+  insertedLength: number;
+  contentLength: number;
+  source: 'edit' | 'restore' | 'refresh';
+  restoredFrom: number | null;
+  createdAt: string;
+}
+
+export interface DiffLine {
+  op: 'equal' | 'insert' | 'delete';
+  text: string;
+}
+
+export interface RevisionDiff {
+  fromRevision: number;
+  toRevision: number;
+  lines: DiffLine[];
+}
+
+export interface RestoreResult {
+  revisionNumber: number | null;
+  marks: UpdateResult;
+}
+
+export interface ExtractNode {
+  id: number;
+  title: string;
+  contentLength: number;
+  parentTextId: number | null;
+  parentStartPosition: number | null;
+  parentEndPosition: number | null;
+  children: ExtractNode[];
+}
+
+export interface SplitResult {
+  folder: Folder;
+  texts: Text[];
+  unmovedMarks: number[];
+}
+
+export interface ImportFailure {
+  path: string;
+  error: string;
+}
+
+export interface ImportResult {
+  texts: Text[];
+  failures: ImportFailure[];
+}
+
+export interface EpubImportResult {
+  folder: Folder;
+  texts: Text[];
+}
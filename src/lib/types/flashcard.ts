@@ -69,6 +69,75 @@ export interface ReviewResult {
 
 export type ReviewQuality = 0 | 1 | 2 | 3 | 4 | 5;
 
+/**
+ * Passage a card was created from, split around the marked range
+ */
+export interface SourceContext {
+  clozeNoteId: number;
+  before: string;
+  marked: string;
+  after: string;
+  startPosition: number;  // UTF-16, DOM space
+  endPosition: number;
+}
+
+/**
+ * Due card as returned by the review queue
+ */
+export interface ReviewCard extends Flashcard {
+  sourceContext: SourceContext | null;
+}
+
+export interface CardSourceLocation {
+  flashcardId: number;
+  clozeNoteId: number | null;
+  textId: number;
+  textTitle: string;
+  startPosition: number | null;
+  endPosition: number | null;
+}
+
+/**
+ * Existing note that looks like the same fact as a newly created one
+ */
+export interface DuplicateMatch {
+  clozeNoteId: number;
+  textId: number;
+  textTitle: string;
+  noteText: string;     // Note text with cloze syntax stripped
+  similarity: number;   // 0-1 trigram similarity
+}
+
+export interface DuplicateCluster {
+  notes: DuplicateMatch[];
+  maxSimilarity: number;
+}
+
+export interface CreateFlashcardsResult {
+  flashcards: Flashcard[];
+  duplicates: DuplicateMatch[];
+}
+
+/**
+ * Problem found in cloze text, positioned in UTF-16 units like JS string indices
+ */
+export interface ClozeDiagnostic {
+  code: string;
+  severity: 'error' | 'warning';
+  message: string;
+  start: number;
+  end: number;
+  suggestion: string | null;
+}
+
+/**
+ * Error from creating or editing cards; `diagnostic` is set when the cloze text is at fault
+ */
+export interface ClozeCommandError {
+  message: string;
+  diagnostic: ClozeDiagnostic | null;
+}
+
 export interface FlashcardPreview {
   html: string;
   clozeNumber: number;
@@ -2,13 +2,18 @@
  * Types for the Flashcard Creation Hub feature
  */
 
-import type { ClozeNote } from './flashcard';
+import type { ClozeNote, DuplicateMatch } from './flashcard';
 
 /**
  * Scope types for filtering marks
  */
 export type HubScope = 'library' | 'folder' | 'text';
 
+/**
+ * Ordering for marks in the hub (default 'seen': least recently seen first)
+ */
+export type HubOrder = 'seen' | 'priority' | 'position' | 'title' | 'age' | 'round_robin';
+
 /**
  * Statistics for the hub
  */
@@ -33,6 +38,7 @@ export interface MarkWithContext {
   beforeContext: string;  // 200 chars before
   afterContext: string;   // 200 chars after
   hasCard: boolean;
+  priority: number;       // 0-10, higher is more important
   createdAt: string;
   clozeNote?: ClozeNote;  // Optional associated cloze note
 }
@@ -50,6 +56,39 @@ export interface CreatedCard {
   textTitle: string;
 }
 
+/**
+ * Cards created from a mark, with likely duplicates already in the library
+ */
+export interface CreateCardsResult {
+  cards: CreatedCard[];
+  duplicates: DuplicateMatch[];
+}
+
+/**
+ * Range located for a mark in the current text content
+ */
+export interface AnchorMatch {
+  start: number;
+  end: number;
+  matchedText: string;
+  confidence: number;   // 1.0 for an exact match
+}
+
+/**
+ * Mark flagged after a text edit, with its best match in the current text
+ */
+export interface MarkNeedingReview {
+  id: number;
+  textId: number;
+  textTitle: string;
+  originalText: string;          // Marked text before the edit
+  notes: string | null;          // Why the mark was flagged
+  startPosition: number | null;  // Stored (possibly stale) range
+  endPosition: number | null;
+  proposed: AnchorMatch | null;
+  updatedAt: string;
+}
+
 /**
  * Request to create a card from a mark
  */
@@ -1,4 +1,22 @@
-export type { Article, ArticleMetadata, ReadingPosition, Text, CreateTextRequest, SmartExcerpt } from './article';
+export type {
+  Article,
+  ArticleMetadata,
+  ReadingPosition,
+  Text,
+  TextStatus,
+  CreateTextRequest,
+  SmartExcerpt,
+  UpdateResult,
+  TextRevision,
+  DiffLine,
+  RevisionDiff,
+  RestoreResult,
+  ExtractNode,
+  SplitResult,
+  ImportFailure,
+  ImportResult,
+  EpubImportResult
+} from './article';
 export type {
   Flashcard,
   ClozeNote,
@@ -8,17 +26,30 @@ export type {
   ReviewResult,
   ReviewQuality,
   FlashcardPreview,
-  ReviewFilter
+  ReviewFilter,
+  SourceContext,
+  ReviewCard,
+  CardSourceLocation,
+  DuplicateMatch,
+  DuplicateCluster,
+  CreateFlashcardsResult,
+  ClozeDiagnostic,
+  ClozeCommandError
 } from './flashcard';
 export type { DailyProgress, ReadingStats, UserProgress } from './progress';
-export type { ReadRange, Paragraph, ExcludedRange } from './reading';
+export type { ReadRange, Paragraph, Sentence, ExcludedRange, ReadingQueueItem, NextReadingItem, OutlineSection } from './reading';
 export type { LimitStatus, ReviewStats, ReviewHistoryEntry } from './review';
-export type { WikipediaArticle } from './wikipedia';
+export type { WikipediaArticle, WikipediaRefreshPreview, WikipediaRefreshResult } from './wikipedia';
+export type { WebArticle } from './web';
 export type {
   HubScope,
+  HubOrder,
   HubStats,
   MarkWithContext,
   CreatedCard,
+  CreateCardsResult,
+  AnchorMatch,
+  MarkNeedingReview,
   CreateCardRequest,
   ScopeSelection,
   HubMarksResponse
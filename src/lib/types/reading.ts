@@ -20,7 +20,48 @@ export interface Paragraph {
   createdAt: string;
 }
 
+export interface Sentence {
+  id: number;
+  textId: number;
+  sentenceIndex: number;
+  startPosition: number;
+  endPosition: number;
+  createdAt: string;
+}
+
 export interface ExcludedRange {
   startPosition: number;
   endPosition: number;
 }
+
+export interface ReadingQueueItem {
+  textId: number;
+  title: string;
+  contentLength: number;
+  readCharacters: number;
+  priority: number;
+  intervalDays: number;
+  nextReadAt: string;
+  postponeCount: number;
+  lastReadAt: string | null;
+  addedAt: string;
+  isDue: boolean;
+}
+
+export interface NextReadingItem {
+  item: ReadingQueueItem | null;
+  readToday: number;
+  dailyBudget: number | null;
+  budgetRemaining: number | null;
+}
+
+export interface OutlineSection {
+  level: number;
+  title: string;
+  startPosition: number;
+  endPosition: number;
+  readPercentage: number;
+  markCount: number;
+  cardCount: number;
+  children: OutlineSection[];
+}
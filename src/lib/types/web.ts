@@ -0,0 +1,9 @@
+export interface WebArticle {
+  title: string;
+  content: string;
+  url: string;
+  author?: string | null;
+  publicationDate?: string | null;
+  publisher?: string | null;
+  accessDate: string;
+}
@@ -1,6 +1,25 @@
+import type { DiffLine, UpdateResult } from './article';
+
 export interface WikipediaArticle {
   title: string;
   content: string;
   url: string;
+  language?: string | null;
+  pageId: number;
+  revisionId?: number | null;
   timestamp: string;
 }
+
+export interface WikipediaRefreshPreview {
+  currentRevisionId: number | null;
+  latestRevisionId: number | null;
+  paragraphs: DiffLine[];
+  hasChanges: boolean;
+}
+
+export interface WikipediaRefreshResult {
+  revisionId: number | null;
+  revisionNumber: number | null;
+  marks: UpdateResult;
+  flaggedReadRanges: number[];
+}
@@ -0,0 +1,24 @@
+import { describe, it, expect } from 'vitest';
+import { isMediaWikiUrl } from '../mediawiki';
+
+describe('isMediaWikiUrl', () => {
+  it('accepts language editions of Wikimedia projects', () => {
+    expect(isMediaWikiUrl('https://en.wikipedia.org/wiki/Rust')).toBe(true);
+    expect(isMediaWikiUrl('https://de.wikipedia.org/wiki/K%C3%B6ln')).toBe(true);
+    expect(isMediaWikiUrl('https://fr.m.wiktionary.org/wiki/chat')).toBe(true);
+    expect(isMediaWikiUrl('https://en.wikibooks.org/wiki/Rust')).toBe(true);
+  });
+
+  it('rejects portals, lookalikes and other sites', () => {
+    expect(isMediaWikiUrl('https://www.wikipedia.org/')).toBe(false);
+    expect(isMediaWikiUrl('https://notwikipedia.org/wiki/Rust')).toBe(false);
+    expect(isMediaWikiUrl('https://example.com/wiki/Rust')).toBe(false);
+    expect(isMediaWikiUrl('not a url')).toBe(false);
+  });
+
+  it('accepts hosts of configured MediaWiki sites', () => {
+    const configured = ['https://wiki.archlinux.org/api.php'];
+    expect(isMediaWikiUrl('https://wiki.archlinux.org/title/Pacman', configured)).toBe(true);
+    expect(isMediaWikiUrl('https://wiki.example.org/wiki/Pacman', configured)).toBe(false);
+  });
+});
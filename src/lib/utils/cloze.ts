@@ -0,0 +1,21 @@
+import type { ClozeCommandError, ClozeDiagnostic } from '../types';
+
+function isClozeCommandError(error: unknown): error is ClozeCommandError {
+  return typeof error === 'object' && error !== null && 'message' in error && 'diagnostic' in error;
+}
+
+/**
+ * The located cloze problem behind a failed card creation or edit, if there is one
+ */
+export function clozeDiagnosticOf(error: unknown): ClozeDiagnostic | null {
+  return isClozeCommandError(error) ? error.diagnostic : null;
+}
+
+/**
+ * Message for an error thrown by a card command, falling back to `fallback`
+ */
+export function cardErrorMessage(error: unknown, fallback: string): string {
+  if (isClozeCommandError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return typeof error === 'string' ? error : fallback;
+}
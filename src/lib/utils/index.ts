@@ -6,3 +6,5 @@ export * from './textSearch';
 export * from './markPositions';
 export * from './format';
 export * from './theme';
+export * from './mediawiki';
+export * from './cloze';
@@ -0,0 +1,41 @@
+/**
+ * Wikimedia projects, served per language from `<lang>.<project>`. Mirrors
+ * WIKIMEDIA_PROJECTS in the backend's Wikipedia service.
+ */
+const WIKIMEDIA_PROJECTS = [
+  'wikipedia.org',
+  'wiktionary.org',
+  'wikibooks.org',
+  'wikiquote.org',
+  'wikisource.org',
+  'wikiversity.org',
+  'wikivoyage.org',
+  'wikinews.org',
+];
+
+function hostOf(url: string): string | null {
+  try {
+    return new URL(url.trim()).host.toLowerCase();
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Whether `url` is an article the MediaWiki importer can fetch: a page on a language
+ * edition of a Wikimedia project, or on a wiki whose api.php URL is configured
+ */
+export function isMediaWikiUrl(url: string, configuredApiUrls: string[] = []): boolean {
+  const host = hostOf(url);
+  if (!host) return false;
+
+  if (configuredApiUrls.some((apiUrl) => hostOf(apiUrl) === host)) {
+    return true;
+  }
+
+  return WIKIMEDIA_PROJECTS.some((project) => {
+    if (!host.endsWith(`.${project}`)) return false;
+    const labels = host.slice(0, -project.length - 1).split('.').filter((label) => label !== 'm');
+    return labels.length === 1 && labels[0] !== 'www';
+  });
+}
@@ -5,15 +5,29 @@ import type {
   FlashcardPreview,
   ReviewResult,
   Text,
+  TextStatus,
   CreateTextRequest,
   ReadRange,
   Paragraph,
+  Sentence,
+  ExcludedRange,
   ReviewFilter,
   ReviewStats,
   LimitStatus,
   WikipediaArticle,
-  CreatedCard,
+  WikipediaRefreshPreview,
+  WikipediaRefreshResult,
+  WebArticle,
+  ReviewCard,
+  CardSourceLocation,
+  CreateCardsResult,
+  CreateFlashcardsResult,
+  ClozeDiagnostic,
+  DuplicateCluster,
   HubStats,
+  HubOrder,
+  AnchorMatch,
+  MarkNeedingReview,
   HubMarksResponse,
   CreateCardRequest,
   ResetResult,
@@ -24,7 +38,18 @@ import type {
   StudyTimeStats,
   TextStatistics,
   FolderStatistics,
-  SmartExcerpt
+  SmartExcerpt,
+  UpdateResult,
+  TextRevision,
+  RevisionDiff,
+  RestoreResult,
+  ExtractNode,
+  SplitResult,
+  ImportResult,
+  EpubImportResult,
+  ReadingQueueItem,
+  NextReadingItem,
+  OutlineSection
 } from '../types';
 
 export async function loadArticle(id: string): Promise<Article> {
@@ -64,8 +89,8 @@ export const api = {
     create: async (request: CreateTextRequest): Promise<Text> => {
       return await invoke('create_text', { request });
     },
-    list: async (): Promise<Text[]> => {
-      return await invoke('list_texts');
+    list: async (status?: TextStatus): Promise<Text[]> => {
+      return await invoke('list_texts', { status });
     },
     listWithAvailableMarks: async (): Promise<Text[]> => {
       return await invoke('get_texts_with_available_marks');
@@ -79,6 +104,12 @@ export const api = {
     delete: async (id: number): Promise<void> => {
       return await invoke('delete_text', { id });
     },
+    archive: async (id: number, suspendCards?: boolean): Promise<void> => {
+      return await invoke('archive_text', { id, suspendCards });
+    },
+    unarchive: async (id: number): Promise<TextStatus> => {
+      return await invoke('unarchive_text', { id });
+    },
     updateContent: async (textId: number, newContent: string): Promise<void> => {
       return await invoke('update_text_content', { textId, newContent });
     },
@@ -88,17 +119,49 @@ export const api = {
       editEnd: number,
       newContent: string
     ) => {
-      return invoke<{
-        updated_marks: number[];
-        flagged_marks: number[];
-        unchanged_marks: number[];
-      }>('update_text_with_smart_marks', {
+      return invoke<UpdateResult>('update_text_with_smart_marks', {
         textId,
         editStart,
         editEnd,
         newContent,
       });
     },
+    listRevisions: async (textId: number): Promise<TextRevision[]> => {
+      return await invoke('list_text_revisions', { textId });
+    },
+    getRevisionContent: async (textId: number, revisionNumber: number): Promise<string> => {
+      return await invoke('get_text_revision_content', { textId, revisionNumber });
+    },
+    diffRevisions: async (textId: number, fromRevision: number, toRevision: number): Promise<RevisionDiff> => {
+      return await invoke('diff_text_revisions', { textId, fromRevision, toRevision });
+    },
+    restoreRevision: async (textId: number, revisionNumber: number): Promise<RestoreResult> => {
+      return await invoke('restore_text_revision', { textId, revisionNumber });
+    },
+    createExtract: async (
+      parentTextId: number,
+      startPosition: number,
+      endPosition: number,
+      title?: string,
+      markAsRead?: boolean
+    ): Promise<Text> => {
+      return await invoke('create_extract', { parentTextId, startPosition, endPosition, title, markAsRead });
+    },
+    getExtractTree: async (textId: number): Promise<ExtractNode> => {
+      return await invoke('get_extract_tree', { textId });
+    },
+    splitByHeaders: async (textId: number, maxLevel?: number): Promise<SplitResult> => {
+      return await invoke('split_text_by_headers', { textId, maxLevel });
+    },
+    importFiles: async (paths: string[], folderId?: string | null): Promise<ImportResult> => {
+      return await invoke('import_files', { paths, folderId });
+    },
+    importEpub: async (path: string, folderId?: string | null): Promise<EpubImportResult> => {
+      return await invoke('import_epub', { path, folderId });
+    },
+    importPdf: async (path: string, folderId?: string | null): Promise<Text> => {
+      return await invoke('import_pdf', { path, folderId });
+    },
     getSmartExcerpt: async (textId: number): Promise<SmartExcerpt> => {
       return await invoke('get_smart_excerpt', { textId });
     },
@@ -128,6 +191,24 @@ export const api = {
     getParagraphs: async (textId: number): Promise<Paragraph[]> => {
       return await invoke('get_paragraphs', { textId: textId });
     },
+    getSentences: async (textId: number): Promise<Sentence[]> => {
+      return await invoke('get_sentences', { textId });
+    },
+    getNextUnreadSentence: async (textId: number, currentPos: number): Promise<Sentence | null> => {
+      return await invoke('get_next_unread_sentence', { textId, currentPos });
+    },
+    getPreviousSentence: async (textId: number, currentPos: number): Promise<Sentence | null> => {
+      return await invoke('get_previous_sentence', { textId, currentPos });
+    },
+    getExcludedRanges: async (textId: number): Promise<ExcludedRange[]> => {
+      return await invoke('get_excluded_ranges', { textId });
+    },
+    excludeRange: async (textId: number, startPosition: number, endPosition: number): Promise<ExcludedRange[]> => {
+      return await invoke('exclude_range', { textId, startPos: startPosition, endPos: endPosition });
+    },
+    includeRange: async (textId: number, startPosition: number, endPosition: number): Promise<ExcludedRange[]> => {
+      return await invoke('include_range', { textId, startPos: startPosition, endPos: endPosition });
+    },
     clearReadProgress: async (textId: number): Promise<void> => {
       return await invoke('clear_read_progress', { textId });
     },
@@ -144,9 +225,32 @@ export const api = {
     getCountableLength: async (textId: number): Promise<number> => {
       return await invoke('get_countable_length', { textId: textId });
     },
+    getOutline: async (textId: number): Promise<OutlineSection[]> => {
+      return await invoke('get_text_outline', { textId });
+    },
+  },
+  readingQueue: {
+    add: async (textId: number, priority?: number): Promise<ReadingQueueItem> => {
+      return await invoke('add_to_reading_queue', { textId, priority });
+    },
+    remove: async (textId: number): Promise<void> => {
+      return await invoke('remove_from_reading_queue', { textId });
+    },
+    setPriority: async (textId: number, priority: number): Promise<number> => {
+      return await invoke('set_reading_priority', { textId, priority });
+    },
+    postpone: async (textId: number): Promise<ReadingQueueItem> => {
+      return await invoke('postpone_reading_item', { textId });
+    },
+    getQueue: async (): Promise<ReadingQueueItem[]> => {
+      return await invoke('get_reading_queue');
+    },
+    getNext: async (dailyBudget?: number): Promise<NextReadingItem> => {
+      return await invoke('get_next_reading_item', { dailyBudget });
+    },
   },
   flashcards: {
-    createFromCloze: async (textId: number, selectedText: string, clozeText: string): Promise<Flashcard[]> => {
+    createFromCloze: async (textId: number, selectedText: string, clozeText: string): Promise<CreateFlashcardsResult> => {
       return await invoke('create_flashcard_from_cloze', {
         textId: textId,
         selectedText: selectedText,
@@ -159,6 +263,12 @@ export const api = {
     delete: async (flashcardId: number): Promise<void> => {
       return await invoke('delete_flashcard', { flashcardId: flashcardId });
     },
+    getDuplicateClusters: async (threshold?: number): Promise<DuplicateCluster[]> => {
+      return await invoke('get_duplicate_clusters', { threshold: threshold ?? null });
+    },
+    validateCloze: async (clozeText: string): Promise<ClozeDiagnostic[]> => {
+      return await invoke('validate_cloze_text', { clozeText });
+    },
     getPreview: async (clozeText: string, clozeNumber: number): Promise<FlashcardPreview> => {
       return await invoke('get_flashcard_preview', {
         clozeText: clozeText,
@@ -194,7 +304,7 @@ export const api = {
     },
   },
   review: {
-    getDueCards: async (limit: number, order?: string): Promise<Flashcard[]> => {
+    getDueCards: async (limit: number, order?: string): Promise<ReviewCard[]> => {
       return await invoke('get_due_cards', { limit: limit, order: order || null });
     },
     gradeCard: async (
@@ -232,7 +342,7 @@ export const api = {
       filter?: ReviewFilter,
       limit?: number,
       order?: string
-    }): Promise<Flashcard[]> => {
+    }): Promise<ReviewCard[]> => {
       return await invoke('get_due_cards_filtered', params);
     },
     getReviewStatsFiltered: async (filter?: ReviewFilter): Promise<ReviewStats> => {
@@ -244,6 +354,9 @@ export const api = {
     buryCard: async (cardId: number): Promise<void> => {
       return await invoke('bury_card', { cardId });
     },
+    getCardSourceLocation: async (flashcardId: number): Promise<CardSourceLocation> => {
+      return await invoke('get_card_source_location', { flashcardId });
+    },
   },
   dashboard: {
     getMostRecentlyReadText: async (): Promise<number | null> => {
@@ -280,28 +393,53 @@ export const api = {
     fetch: async (url: string): Promise<WikipediaArticle> => {
       return await invoke('fetch_wikipedia_article', { url });
     },
+    previewRefresh: async (textId: number): Promise<WikipediaRefreshPreview> => {
+      return await invoke('preview_wikipedia_refresh', { textId });
+    },
+    refresh: async (textId: number, revisionId?: number): Promise<WikipediaRefreshResult> => {
+      return await invoke('refresh_wikipedia_article', { textId, revisionId });
+    },
+  },
+  web: {
+    fetchArticle: async (url: string): Promise<WebArticle> => {
+      return await invoke('fetch_web_article', { url });
+    },
   },
   hub: {
     getMarksForScope: async (
       scope: string,
       scopeId: string | number | null,
       limit?: number,
-      offset?: number
+      offset?: number,
+      order?: HubOrder
     ): Promise<HubMarksResponse> => {
       return await invoke('get_hub_marks', {
         scope,
         scopeId: scopeId !== null ? String(scopeId) : null,
         limit: limit ?? null,
         offset: offset ?? 0,
+        order: order ?? null,
       });
     },
-    skipMark: async (markId: number): Promise<void> => {
-      return await invoke('skip_mark', { markId });
+    skipMark: async (markId: number, lowerPriority?: boolean): Promise<void> => {
+      return await invoke('skip_mark', { markId, lowerPriority: lowerPriority ?? null });
+    },
+    setMarkPriority: async (markId: number, priority: number): Promise<number> => {
+      return await invoke('set_mark_priority', { markId, priority });
     },
     deleteMark: async (markId: number): Promise<void> => {
       return await invoke('delete_mark', { markId });
     },
-    createCardFromMark: async (request: CreateCardRequest): Promise<CreatedCard[]> => {
+    getMarksNeedingReview: async (textId?: number): Promise<MarkNeedingReview[]> => {
+      return await invoke('get_marks_needing_review', { textId: textId ?? null });
+    },
+    acceptMarkAnchor: async (markId: number): Promise<AnchorMatch> => {
+      return await invoke('accept_mark_anchor', { markId });
+    },
+    setMarkRange: async (markId: number, startPosition: number, endPosition: number): Promise<AnchorMatch> => {
+      return await invoke('set_mark_range', { markId, startPosition, endPosition });
+    },
+    createCardFromMark: async (request: CreateCardRequest): Promise<CreateCardsResult> => {
       return await invoke('create_card_from_mark', {
         markId: request.markId,
         selectedText: request.selectedText,
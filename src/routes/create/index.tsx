@@ -6,6 +6,7 @@ import { ScopeSelector } from '@/lib/components/create/ScopeSelector';
 import { MarkDisplay } from '@/lib/components/create/MarkDisplay';
 import { CardCreator } from '@/lib/components/create/CardCreator';
 import { CreatedCardsList } from '@/lib/components/create/CreatedCardsList';
+import { DuplicateNotice } from '@/lib/components/flashcard/DuplicateNotice';
 import { useCardCreationStore } from '@/lib/stores/cardCreation';
 import { BackToReadingButton } from '@/lib/components/shared/BackToReadingButton';
 import { getModifierKey } from '@/lib/utils/platform';
@@ -18,6 +19,8 @@ export function CreateCardsPage() {
   const marks = useCardCreationStore((state) => state.marks);
   const currentMarkIndex = useCardCreationStore((state) => state.currentMarkIndex);
   const createdCards = useCardCreationStore((state) => state.createdCards);
+  const duplicates = useCardCreationStore((state) => state.duplicates);
+  const dismissDuplicates = useCardCreationStore((state) => state.dismissDuplicates);
   const isLoading = useCardCreationStore((state) => state.isLoading);
   const error = useCardCreationStore((state) => state.error);
   const totalCount = useCardCreationStore((state) => state.totalCount);
@@ -259,6 +262,9 @@ export function CreateCardsPage() {
               />
             )}
 
+            {/* Possible duplicates of the cards just created */}
+            <DuplicateNotice duplicates={duplicates} onDismiss={dismissDuplicates} />
+
             {/* Card Creator */}
             {currentMark && (
               <div className="p-6 border border-border rounded-lg bg-card">
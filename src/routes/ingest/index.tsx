@@ -13,9 +13,11 @@ import {
 import { Loader2 } from 'lucide-react'
 import { useTextHistory } from '../../hooks/useTextHistory'
 import { api } from '../../lib/utils/tauri'
+import { isMediaWikiUrl } from '../../lib/utils/mediawiki'
+import { useSettingsStore } from '../../lib/stores/settings'
 import { FolderSelect } from '@/lib/components/folders/FolderSelect'
 import { BackToReadingButton } from '@/lib/components/shared/BackToReadingButton'
-import type { IngestPageLocationState } from '@/lib/types'
+import type { IngestPageLocationState, WebArticle } from '@/lib/types'
 
 export function IngestPage() {
   const navigate = useNavigate()
@@ -42,28 +44,48 @@ export function IngestPage() {
   const { texts, loadLibrary } = useLibraryStore()
   const { folderTree, loadFolderTree } = useFolderStore()
   const [wikipediaUrl, setWikipediaUrl] = useState(initialState?.wikipediaUrl || '')
+  const [webArticle, setWebArticle] = useState<WebArticle | null>(null)
+  const [language, setLanguage] = useState<string | null>(null)
+  const [wikiRevision, setWikiRevision] = useState<{ pageId: number; revisionId?: number | null } | null>(null)
+  const mediawikiHosts = useSettingsStore((state) => state.mediawikiHosts)
   const [isFetching, setIsFetching] = useState(false)
   const [fetchError, setFetchError] = useState<string | null>(null)
   const [titleError, setTitleError] = useState<string | null>(null)
   const mod = getModifierKey()
 
-  const handleFetchWikipedia = async () => {
+  const handleFetchArticle = async () => {
     if (!wikipediaUrl.trim()) return
 
     setIsFetching(true)
     setFetchError(null)
 
     try {
-      const article = await api.wikipedia.fetch(wikipediaUrl)
-
-      setTitle(article.title)
-      setContentImmediate(article.content)
-      setPublisher('Wikipedia')
-      setPublicationDate(article.timestamp.split('T')[0])
-      setAuthor('')
+      if (isMediaWikiUrl(wikipediaUrl, mediawikiHosts)) {
+        const article = await api.wikipedia.fetch(wikipediaUrl)
+
+        setWebArticle(null)
+        setLanguage(article.language ?? null)
+        setWikiRevision({ pageId: article.pageId, revisionId: article.revisionId })
+        setTitle(article.title)
+        setContentImmediate(article.content)
+        setPublisher(wikipediaUrl.includes('wikipedia.org') ? 'Wikipedia' : new URL(wikipediaUrl).host)
+        setPublicationDate(article.timestamp.split('T')[0])
+        setAuthor('')
+      } else {
+        const article = await api.web.fetchArticle(wikipediaUrl)
+
+        setWebArticle(article)
+        setLanguage(null)
+        setWikiRevision(null)
+        setTitle(article.title)
+        setContentImmediate(article.content)
+        setPublisher(article.publisher || '')
+        setPublicationDate(article.publicationDate || '')
+        setAuthor(article.author || '')
+      }
     } catch (error) {
-      console.error('Failed to fetch Wikipedia article:', error)
-      setFetchError(error instanceof Error ? error.message : 'Failed to fetch Wikipedia article')
+      console.error('Failed to fetch article:', error)
+      setFetchError(error instanceof Error ? error.message : String(error))
     } finally {
       setIsFetching(false)
     }
@@ -81,8 +103,12 @@ export function IngestPage() {
       await createText({
         title: trimmedTitle,
         content,
-        source: wikipediaUrl ? 'wikipedia' : 'paste',
-        sourceUrl: wikipediaUrl || undefined,
+        source: webArticle ? 'web' : wikipediaUrl ? 'wikipedia' : 'paste',
+        sourceUrl: webArticle?.url || wikipediaUrl || undefined,
+        accessDate: webArticle?.accessDate,
+        language: language || undefined,
+        wikiPageId: wikiRevision?.pageId,
+        wikiRevisionId: wikiRevision?.revisionId ?? undefined,
         author: author || undefined,
         publicationDate: publicationDate || undefined,
         publisher: publisher || undefined,
@@ -154,7 +180,7 @@ export function IngestPage() {
 
   useEffect(() => {
     if (initialState?.wikipediaUrl && !content) {
-      handleFetchWikipedia()
+      handleFetchArticle()
     }
   }, [])
 
@@ -243,19 +269,24 @@ export function IngestPage() {
                 <h2 className="text-lg font-semibold mb-4">Metadata</h2>
 
                 <div className="space-y-2">
-                  <Label htmlFor="wikipediaUrl">Wikipedia URL (optional)</Label>
+                  <Label htmlFor="wikipediaUrl">Article URL (optional)</Label>
                   <div className="flex gap-2">
                     <Input
                       id="wikipediaUrl"
                       value={wikipediaUrl}
-                      onChange={(e) => setWikipediaUrl(e.target.value)}
-                      placeholder="https://en.wikipedia.org/wiki/..."
+                      onChange={(e) => {
+                        setWikipediaUrl(e.target.value)
+                        setWebArticle(null)
+                        setLanguage(null)
+                        setWikiRevision(null)
+                      }}
+                      placeholder="https://en.wikipedia.org/wiki/... or any article URL"
                       disabled={isLoading || isFetching}
                       className="flex-1"
                     />
                     <Button
                       type="button"
-                      onClick={handleFetchWikipedia}
+                      onClick={handleFetchArticle}
                       disabled={isLoading || isFetching || !wikipediaUrl.trim()}
                       variant="outline"
                     >
@@ -272,7 +303,7 @@ export function IngestPage() {
                     <p className="text-sm text-destructive">{fetchError}</p>
                   )}
                   <p className="text-xs text-muted-foreground">
-                    Paste a Wikipedia URL and click "Fetch Article" to auto-fill the form, or manually enter text below.
+                    Paste a Wikipedia or other article URL and click "Fetch Article" to auto-fill the form, or manually enter text below.
                   </p>
                 </div>
 
@@ -80,11 +80,12 @@ export function ReadPage() {
     error,
     loadText,
     readRanges,
+    excludedRanges,
     totalProgress,
     getReadRanges,
     getParagraphs,
+    getExcludedRanges,
     calculateProgress,
-    setExcludedRanges,
     markAsFinished,
     clearProgress,
     markRangeAsRead,
@@ -142,20 +143,19 @@ export function ReadPage() {
       loadText(textId).then(() => {
         getReadRanges(textId)
         getParagraphs(textId)
+        getExcludedRanges(textId)
         calculateProgress(textId)
         loadMarks(textId)
       })
     }
-  }, [id, loadText, getReadRanges, getParagraphs, calculateProgress])
+  }, [id, loadText, getReadRanges, getParagraphs, getExcludedRanges, calculateProgress])
 
   useEffect(() => {
     if (currentText) {
-      const { excludedRanges } = parseExcludedRanges(currentText.content)
-      setExcludedRanges(excludedRanges)
       setRenameTextTitle(currentText.title)
       extractLinks(currentText.content)
     }
-  }, [currentText, setExcludedRanges, extractLinks])
+  }, [currentText, extractLinks])
 
   useEffect(() => {
     if (currentText) {
@@ -1203,6 +1203,7 @@ export function ReadPage() {
                             <ReadHighlighter
                               content={currentText.content}
                               readRanges={readRanges}
+                              excludedRanges={excludedRanges}
                               linksEnabled={linksEnabled}
                               searchMatches={matches}
                               activeSearchIndex={currentIndex}